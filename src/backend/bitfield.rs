@@ -0,0 +1,252 @@
+use crate::{Backend, BackendErr, BulkString, Value};
+
+// Integer type of a bitfield, `i1`..`i64` or `u1`..`u63`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitFieldType {
+    pub signed: bool,
+    pub bits: u32,
+}
+
+// Behavior of set and incrby when the result does not fit in the field.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    #[default]
+    Wrap,
+    Sat,
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitFieldOp {
+    Get {
+        ty: BitFieldType,
+        offset: usize,
+    },
+    Set {
+        ty: BitFieldType,
+        offset: usize,
+        value: i64,
+        overflow: Overflow,
+    },
+    IncrBy {
+        ty: BitFieldType,
+        offset: usize,
+        increment: i64,
+        overflow: Overflow,
+    },
+}
+
+impl BitFieldType {
+    fn min(&self) -> i128 {
+        if self.signed {
+            -(1i128 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+
+    fn max(&self) -> i128 {
+        if self.signed {
+            (1i128 << (self.bits - 1)) - 1
+        } else {
+            (1i128 << self.bits) - 1
+        }
+    }
+
+    // Fit a value into the field according to the overflow policy, None when it fails.
+    fn fit(&self, value: i128, overflow: Overflow) -> Option<i64> {
+        if (self.min()..=self.max()).contains(&value) {
+            return Some(value as i64);
+        }
+
+        match overflow {
+            Overflow::Wrap => Some(self.decode(value as u64)),
+            Overflow::Sat if value > self.max() => Some(self.max() as i64),
+            Overflow::Sat => Some(self.min() as i64),
+            Overflow::Fail => None,
+        }
+    }
+
+    // Interpret the low bits of raw as a value of this type.
+    fn decode(&self, raw: u64) -> i64 {
+        let shift = 64 - self.bits;
+        if self.signed {
+            ((raw << shift) as i64) >> shift
+        } else {
+            ((raw << shift) >> shift) as i64
+        }
+    }
+}
+
+impl BitFieldOp {
+    fn is_write(&self) -> bool {
+        !matches!(self, BitFieldOp::Get { .. })
+    }
+
+    fn end(&self) -> usize {
+        match self {
+            BitFieldOp::Get { ty, offset }
+            | BitFieldOp::Set { ty, offset, .. }
+            | BitFieldOp::IncrBy { ty, offset, .. } => offset + ty.bits as usize,
+        }
+    }
+}
+
+impl Backend {
+    // Run the operations in order, replying None for a set or incrby that failed on overflow.
+    pub fn bitfield(
+        &self,
+        key: String,
+        ops: &[BitFieldOp],
+    ) -> Result<Vec<Option<i64>>, BackendErr> {
+        if !ops.iter().any(BitFieldOp::is_write) {
+            return self.bitfield_ro(&key, ops);
+        }
+
+        let mut entry = self.get_entry_or_insert(key, || Value::String(BulkString::new([])));
+        let value = &mut entry.as_string_mut()?.0;
+
+        // the string grows to hold every written field even if the write itself fails
+        let len = ops
+            .iter()
+            .filter(|op| op.is_write())
+            .map(|op| op.end().div_ceil(8))
+            .max()
+            .unwrap_or_default();
+        if value.len() < len {
+            value.resize(len, 0);
+        }
+
+        let ret = ops
+            .iter()
+            .map(|op| match *op {
+                BitFieldOp::Get { ty, offset } => {
+                    Some(ty.decode(read_bits(value, offset, ty.bits)))
+                }
+                BitFieldOp::Set {
+                    ty,
+                    offset,
+                    value: new,
+                    overflow,
+                } => {
+                    // unsigned fields take the value as its two's complement bits like redis does
+                    let new = if ty.signed {
+                        new as i128
+                    } else {
+                        new as u64 as i128
+                    };
+                    let new = ty.fit(new, overflow)?;
+                    let old = ty.decode(read_bits(value, offset, ty.bits));
+                    write_bits(value, offset, ty.bits, new as u64);
+                    Some(old)
+                }
+                BitFieldOp::IncrBy {
+                    ty,
+                    offset,
+                    increment,
+                    overflow,
+                } => {
+                    let old = ty.decode(read_bits(value, offset, ty.bits));
+                    let new = ty.fit(old as i128 + increment as i128, overflow)?;
+                    write_bits(value, offset, ty.bits, new as u64);
+                    Some(new)
+                }
+            })
+            .collect();
+        Ok(ret)
+    }
+
+    // Read only variant of bitfield which only accepts get operations.
+    pub fn bitfield_ro(
+        &self,
+        key: &str,
+        ops: &[BitFieldOp],
+    ) -> Result<Vec<Option<i64>>, BackendErr> {
+        let entry = self.get_entry(key);
+        let value = match &entry {
+            Some(entry) => entry.as_string()?.0.as_slice(),
+            None => &[],
+        };
+
+        let ret = ops
+            .iter()
+            .map(|op| match *op {
+                BitFieldOp::Get { ty, offset } => {
+                    Some(ty.decode(read_bits(value, offset, ty.bits)))
+                }
+                _ => None,
+            })
+            .collect();
+        Ok(ret)
+    }
+}
+
+// Read `bits` bits starting at offset, most significant bit first. Bits past the end are zeros.
+fn read_bits(bytes: &[u8], offset: usize, bits: u32) -> u64 {
+    (offset..offset + bits as usize).fold(0u64, |acc, pos| {
+        let bit = bytes
+            .get(pos >> 3)
+            .is_some_and(|byte| byte & (1 << (7 - (pos & 7))) != 0);
+        (acc << 1) | bit as u64
+    })
+}
+
+// Write the low `bits` bits of value starting at offset, the bytes must be large enough.
+fn write_bits(bytes: &mut [u8], offset: usize, bits: u32, value: u64) {
+    for i in 0..bits as usize {
+        let pos = offset + i;
+        let mask = 1u8 << (7 - (pos & 7));
+        if (value >> (bits as usize - 1 - i)) & 1 == 1 {
+            bytes[pos >> 3] |= mask;
+        } else {
+            bytes[pos >> 3] &= !mask;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bits() {
+        let mut bytes = vec![0u8; 3];
+        write_bits(&mut bytes, 5, 8, 0xab);
+        assert_eq!(bytes, vec![0b0000_0101, 0b0101_1000, 0]);
+        assert_eq!(read_bits(&bytes, 5, 8), 0xab);
+        assert_eq!(read_bits(&bytes, 20, 8), 0);
+    }
+
+    #[test]
+    fn test_fit() {
+        let i8 = BitFieldType {
+            signed: true,
+            bits: 8,
+        };
+        assert_eq!(i8.fit(127, Overflow::Fail), Some(127));
+        assert_eq!(i8.fit(128, Overflow::Wrap), Some(-128));
+        assert_eq!(i8.fit(-129, Overflow::Wrap), Some(127));
+        assert_eq!(i8.fit(1000, Overflow::Sat), Some(127));
+        assert_eq!(i8.fit(-1000, Overflow::Sat), Some(-128));
+        assert_eq!(i8.fit(128, Overflow::Fail), None);
+
+        let u2 = BitFieldType {
+            signed: false,
+            bits: 2,
+        };
+        assert_eq!(u2.fit(4, Overflow::Wrap), Some(0));
+        assert_eq!(u2.fit(-1, Overflow::Wrap), Some(3));
+        assert_eq!(u2.fit(-1, Overflow::Sat), Some(0));
+        assert_eq!(u2.fit(5, Overflow::Sat), Some(3));
+
+        let i64 = BitFieldType {
+            signed: true,
+            bits: 64,
+        };
+        assert_eq!(
+            i64.fit(i64::MAX as i128 + 1, Overflow::Wrap),
+            Some(i64::MIN)
+        );
+        assert_eq!(i64.decode(u64::MAX), -1);
+    }
+}
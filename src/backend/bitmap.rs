@@ -0,0 +1,299 @@
+use crate::{Backend, BackendErr, BulkString, Entry, Value};
+
+// Bitwise operations of the bitop command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOperator {
+    And,
+    Or,
+    Xor,
+    Not,
+    // bits set in the first key but in none of the others
+    Diff,
+    // bits set in one or more of the other keys but not in the first one
+    Diff1,
+    // bits set in the first key and in at least one of the others
+    AndOr,
+    // bits set in exactly one of the keys
+    One,
+}
+
+// Unit of the range given to bitcount and bitpos.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BitUnit {
+    #[default]
+    Byte,
+    Bit,
+}
+
+impl Backend {
+    // Set or clear the bit at offset, returns the original bit value.
+    pub fn setbit(&self, key: String, offset: usize, on: bool) -> Result<bool, BackendErr> {
+        let byte = offset >> 3;
+        let mask = 1u8 << (7 - (offset & 7));
+
+        let mut entry = self.get_entry_or_insert(key, || Value::String(BulkString::new([])));
+        let value = &mut entry.as_string_mut()?.0;
+        if value.len() <= byte {
+            value.resize(byte + 1, 0);
+        }
+
+        let old = value[byte] & mask != 0;
+        if on {
+            value[byte] |= mask;
+        } else {
+            value[byte] &= !mask;
+        }
+        Ok(old)
+    }
+
+    pub fn getbit(&self, key: &str, offset: usize) -> Result<bool, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry
+                .as_string()?
+                .get(offset >> 3)
+                .is_some_and(|byte| byte & (1 << (7 - (offset & 7))) != 0)),
+            None => Ok(false),
+        }
+    }
+
+    // Count the set bits, optionally within an inclusive range counting negatives from the end.
+    pub fn bitcount(
+        &self,
+        key: &str,
+        range: Option<(i64, i64)>,
+        unit: BitUnit,
+    ) -> Result<usize, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(0);
+        };
+        let value = entry.as_string()?;
+
+        let Some((start, end)) = range else {
+            return Ok(popcount(value));
+        };
+        let total = match unit {
+            BitUnit::Byte => value.len() as i64,
+            BitUnit::Bit => value.len() as i64 * 8,
+        };
+        let Some((start, end)) = resolve_range(start, end, total) else {
+            return Ok(0);
+        };
+
+        match unit {
+            BitUnit::Byte => Ok(popcount(&value[start..=end])),
+            BitUnit::Bit => {
+                let count = popcount(&value[start >> 3..=end >> 3]);
+                // bits of the first byte before start, none when start is byte aligned
+                let head = value[start >> 3]
+                    .checked_shr(8 - (start & 7) as u32)
+                    .unwrap_or(0)
+                    .count_ones() as usize;
+                let tail =
+                    (value[end >> 3] & ((1u16 << (7 - (end & 7))) - 1) as u8).count_ones() as usize;
+                Ok(count - head - tail)
+            }
+        }
+    }
+
+    // Find the first bit set to `bit`, returns -1 when there is none.
+    // Searching a clear bit without an explicit end treats the string as right padded with zeros.
+    pub fn bitpos(
+        &self,
+        key: &str,
+        bit: bool,
+        start: Option<i64>,
+        end: Option<i64>,
+        unit: BitUnit,
+    ) -> Result<i64, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(if bit { -1 } else { 0 });
+        };
+        let value = entry.as_string()?;
+        let open_end = end.is_none();
+
+        let total = match unit {
+            BitUnit::Byte => value.len() as i64,
+            BitUnit::Bit => value.len() as i64 * 8,
+        };
+        let Some((start, end)) = resolve_range(start.unwrap_or(0), end.unwrap_or(-1), total) else {
+            return Ok(-1);
+        };
+
+        let (first, last) = match unit {
+            BitUnit::Byte => (start, end),
+            BitUnit::Bit => (start >> 3, end >> 3),
+        };
+        let mut bytes = value[first..=last].to_vec();
+        if unit == BitUnit::Bit {
+            // bits out of range never match the searched bit
+            let head_mask = !(0xffu8 >> (start & 7));
+            let tail_mask = ((1u16 << (7 - (end & 7))) - 1) as u8;
+            let last = bytes.len() - 1;
+            if bit {
+                bytes[0] &= !head_mask;
+                bytes[last] &= !tail_mask;
+            } else {
+                bytes[0] |= head_mask;
+                bytes[last] |= tail_mask;
+            }
+        }
+
+        match find_bit(&bytes, bit) {
+            Some(pos) => Ok((first * 8 + pos) as i64),
+            None if !bit && open_end => Ok(((first + bytes.len()) * 8) as i64),
+            None => Ok(-1),
+        }
+    }
+
+    // Perform a bitwise operation between the keys and store the result in dest.
+    // Returns the length of the result, an empty result deletes dest.
+    pub fn bitop(
+        &self,
+        op: BitOperator,
+        dest: String,
+        keys: &[String],
+    ) -> Result<usize, BackendErr> {
+        let mut guard = self.lock_keys(keys.iter().chain([&dest]));
+
+        let sources = keys
+            .iter()
+            .map(|key| match guard.get(key) {
+                Some(entry) => Ok(entry.as_string()?.0.as_slice()),
+                None => Ok([].as_slice()),
+            })
+            .collect::<Result<Vec<&[u8]>, BackendErr>>()?;
+        let ret = bitop(op, &sources);
+
+        let len = ret.len();
+        if ret.is_empty() {
+            guard.remove(&dest);
+        } else {
+            guard.insert(dest, Entry::new(Value::String(BulkString::new(ret))));
+        }
+        Ok(len)
+    }
+}
+
+// Count set bits eight bytes at a time so large bitmaps stay cheap.
+pub fn popcount(bytes: &[u8]) -> usize {
+    let chunks = bytes.chunks_exact(8);
+    let rest = chunks
+        .remainder()
+        .iter()
+        .map(|byte| byte.count_ones() as usize)
+        .sum::<usize>();
+
+    chunks
+        .map(|chunk| u64::from_ne_bytes(chunk.try_into().unwrap_or_default()).count_ones() as usize)
+        .sum::<usize>()
+        + rest
+}
+
+// Position of the first bit set to `bit`, skipping whole words that can not match.
+fn find_bit(bytes: &[u8], bit: bool) -> Option<usize> {
+    let skip = if bit { 0u8 } else { 0xff };
+
+    let mut offset = 0;
+    for chunk in bytes.chunks(8) {
+        if chunk.len() == 8 && chunk.iter().all(|byte| *byte == skip) {
+            offset += 8;
+            continue;
+        }
+
+        for byte in chunk {
+            if *byte != skip {
+                let pos = if bit {
+                    byte.leading_zeros()
+                } else {
+                    byte.leading_ones()
+                };
+                return Some(offset * 8 + pos as usize);
+            }
+            offset += 1;
+        }
+    }
+
+    None
+}
+
+// Normalize an inclusive range with negative offsets into [0, total), None if it is empty.
+fn resolve_range(start: i64, end: i64, total: i64) -> Option<(usize, usize)> {
+    if start < 0 && end < 0 && start > end {
+        return None;
+    }
+
+    let start = if start < 0 {
+        (total + start).max(0)
+    } else {
+        start
+    };
+    let end = if end < 0 { (total + end).max(0) } else { end };
+    let end = end.min(total - 1);
+    if start > end || total == 0 {
+        return None;
+    }
+
+    Some((start as usize, end as usize))
+}
+
+fn bitop(op: BitOperator, sources: &[&[u8]]) -> Vec<u8> {
+    let len = sources.iter().map(|s| s.len()).max().unwrap_or_default();
+    let byte = |source: &[u8], i: usize| source.get(i).copied().unwrap_or_default();
+
+    (0..len)
+        .map(|i| {
+            let rest = sources[1..].iter().map(|source| byte(source, i));
+            let first = byte(sources[0], i);
+            match op {
+                BitOperator::And => rest.fold(first, |acc, b| acc & b),
+                BitOperator::Or => rest.fold(first, |acc, b| acc | b),
+                BitOperator::Xor => rest.fold(first, |acc, b| acc ^ b),
+                BitOperator::Not => !first,
+                BitOperator::Diff => first & !rest.fold(0, |acc, b| acc | b),
+                BitOperator::Diff1 => !first & rest.fold(0, |acc, b| acc | b),
+                BitOperator::AndOr => first & rest.fold(0, |acc, b| acc | b),
+                BitOperator::One => {
+                    let (once, more) = rest.fold((first, 0u8), |(once, more), b| {
+                        (once ^ b, more | (once & b))
+                    });
+                    once & !more
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popcount() {
+        let bytes = (0..=255u8).cycle().take(1027).collect::<Vec<u8>>();
+        let expected = bytes.iter().map(|b| b.count_ones() as usize).sum::<usize>();
+        assert_eq!(popcount(&bytes), expected);
+        assert_eq!(popcount(&[]), 0);
+    }
+
+    #[test]
+    fn test_find_bit() {
+        let mut bytes = vec![0u8; 20];
+        assert_eq!(find_bit(&bytes, true), None);
+        assert_eq!(find_bit(&bytes, false), Some(0));
+
+        bytes[17] = 0b0010_0000;
+        assert_eq!(find_bit(&bytes, true), Some(17 * 8 + 2));
+
+        let bytes = vec![0xffu8; 20];
+        assert_eq!(find_bit(&bytes, false), None);
+    }
+
+    #[test]
+    fn test_resolve_range() {
+        assert_eq!(resolve_range(0, -1, 6), Some((0, 5)));
+        assert_eq!(resolve_range(-100, 100, 6), Some((0, 5)));
+        assert_eq!(resolve_range(4, 2, 6), None);
+        assert_eq!(resolve_range(-1, -2, 6), None);
+        assert_eq!(resolve_range(0, -1, 0), None);
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError, TryLockError};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::{Backend, RespFrame};
+
+// Try to serve a blocked client from one of its keys, returns the reply once it is served.
+// It is called with a handle bound to the database the client blocked in.
+pub type ServeFn = Box<dyn FnMut(&Backend, &str) -> Option<RespFrame> + Send>;
+
+// Clients blocked on keys, every key keeps its clients in the order they blocked.
+//
+// Keys that get data are signaled as ready and the clients blocked on them are served
+// right away by whoever holds the state, so a client is never woken just to find
+// its data taken by another one.
+#[derive(Debug, Default)]
+pub(crate) struct Blocking {
+    state: Mutex<BlockingState>,
+    // (db, key) that got data since the last time the blocked clients were served
+    ready: Mutex<VecDeque<(usize, String)>>,
+    // Clients inside block_on, checked before signaling so pushes cost nothing without them.
+    blocked: AtomicUsize,
+}
+
+#[derive(Debug, Default)]
+struct BlockingState {
+    waiters: HashMap<u64, Waiter>,
+    queues: HashMap<(usize, String), VecDeque<u64>>,
+    next_id: u64,
+}
+
+struct Waiter {
+    db: usize,
+    keys: Vec<String>,
+    serve: ServeFn,
+    tx: oneshot::Sender<RespFrame>,
+}
+
+// Unblocks the client however block_on ends, including when its future is dropped.
+struct BlockGuard<'a> {
+    backend: &'a Backend,
+    id: Option<u64>,
+}
+
+impl Backend {
+    // Serve a client from the first of the keys that has data, or block it until one of them
+    // gets some. Clients blocked on a key are served in the order they blocked.
+    // Returns None when the timeout elapses first, no timeout blocks forever.
+    pub async fn block_on(
+        &self,
+        keys: Vec<String>,
+        timeout: Option<Duration>,
+        mut serve: ServeFn,
+    ) -> Option<RespFrame> {
+        let blocking = &self.inner.blocking;
+        // counted before the first try, a push that does not see it is seen by the try
+        blocking.blocked.fetch_add(1, Ordering::SeqCst);
+        let mut guard = BlockGuard {
+            backend: self,
+            id: None,
+        };
+
+        let mut rx = {
+            let mut state = blocking.state();
+            if let Some(reply) = keys.iter().find_map(|key| serve(self, key)) {
+                drop(state);
+                self.serve_ready();
+                return Some(reply);
+            }
+
+            let (tx, rx) = oneshot::channel();
+            guard.id = Some(state.block(self.db(), keys, serve, tx));
+            rx
+        };
+        // keys signaled while the state was held are left to the one releasing it
+        self.serve_ready();
+
+        let reply = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, &mut rx).await.ok(),
+            None => Some((&mut rx).await),
+        };
+
+        // a client served right as it timed out still gets its reply
+        drop(guard);
+        reply.and_then(Result::ok).or_else(|| rx.try_recv().ok())
+    }
+
+    // Signal that a key got data, the clients blocked on it are served right away.
+    // Entry guards on the key must be dropped before.
+    pub(crate) fn signal_ready(&self, key: &str) {
+        let blocking = &self.inner.blocking;
+        if blocking.blocked.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        blocking.ready().push_back((self.db(), key.to_string()));
+        self.serve_ready();
+    }
+
+    // Signal every key of the database clients are blocked on that now holds a list,
+    // after the contents of the whole database were replaced.
+    pub(crate) fn signal_db_ready(&self) {
+        let blocking = &self.inner.blocking;
+        if blocking.blocked.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let keys = blocking
+            .state()
+            .queues
+            .keys()
+            .filter(|(db, _)| *db == self.db())
+            .map(|(_, key)| key.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            let is_list = matches!(self.get_entry(&key), Some(entry) if entry.as_list().is_ok());
+            if is_list {
+                blocking.ready().push_back((self.db(), key));
+            }
+        }
+        self.serve_ready();
+    }
+
+    // Serve the clients blocked on ready keys. When the state is held elsewhere, including
+    // by a serve function of this very thread, its holder serves them after releasing it.
+    fn serve_ready(&self) {
+        let blocking = &self.inner.blocking;
+        loop {
+            let mut state = match blocking.state.try_lock() {
+                Ok(state) => state,
+                Err(TryLockError::Poisoned(e)) => e.into_inner(),
+                Err(TryLockError::WouldBlock) => return,
+            };
+
+            loop {
+                let next = blocking.ready().pop_front();
+                match next {
+                    Some((db, key)) => state.serve(self, db, &key),
+                    None => break,
+                }
+            }
+
+            drop(state);
+            if blocking.ready().is_empty() {
+                return;
+            }
+        }
+    }
+}
+
+impl Blocking {
+    fn state(&self) -> MutexGuard<'_, BlockingState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn ready(&self) -> MutexGuard<'_, VecDeque<(usize, String)>> {
+        self.ready.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl BlockingState {
+    fn block(
+        &mut self,
+        db: usize,
+        keys: Vec<String>,
+        serve: ServeFn,
+        tx: oneshot::Sender<RespFrame>,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        for key in keys.iter() {
+            let queue = self.queues.entry((db, key.clone())).or_default();
+            // a key given twice is waited on once
+            if !queue.contains(&id) {
+                queue.push_back(id);
+            }
+        }
+        self.waiters.insert(
+            id,
+            Waiter {
+                db,
+                keys,
+                serve,
+                tx,
+            },
+        );
+        id
+    }
+
+    fn unblock(&mut self, id: u64) -> Option<Waiter> {
+        let waiter = self.waiters.remove(&id)?;
+        for key in waiter.keys.iter() {
+            let key = (waiter.db, key.clone());
+            if let Some(queue) = self.queues.get_mut(&key) {
+                queue.retain(|i| *i != id);
+                if queue.is_empty() {
+                    self.queues.remove(&key);
+                }
+            }
+        }
+        Some(waiter)
+    }
+
+    // Serve the clients blocked on a key in order until one of them is left waiting.
+    fn serve(&mut self, backend: &Backend, db: usize, key: &str) {
+        let Ok(backend) = backend.with_db(db) else {
+            return;
+        };
+
+        let queue = (db, key.to_string());
+        while let Some(id) = self.queues.get(&queue).and_then(|q| q.front().copied()) {
+            let Some(waiter) = self.waiters.get_mut(&id) else {
+                break;
+            };
+            // the client is gone, its data must not be taken
+            if waiter.tx.is_closed() {
+                self.unblock(id);
+                continue;
+            }
+
+            let Some(reply) = (waiter.serve)(&backend, key) else {
+                break;
+            };
+            if let Some(waiter) = self.unblock(id) {
+                let _ = waiter.tx.send(reply);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Waiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Waiter")
+            .field("db", &self.db)
+            .field("keys", &self.keys)
+            .finish()
+    }
+}
+
+impl Drop for BlockGuard<'_> {
+    fn drop(&mut self) {
+        let blocking = &self.backend.inner.blocking;
+        if let Some(id) = self.id {
+            blocking.state().unblock(id);
+        }
+        blocking.blocked.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BackendErr, BulkString};
+
+    fn lpop_serve() -> ServeFn {
+        Box::new(|backend: &Backend, key: &str| match backend.lpop(key, 1) {
+            Ok(values) => values?.pop(),
+            Err(e) => Some(e.into()),
+        })
+    }
+
+    fn value(value: &str) -> RespFrame {
+        BulkString::from(value).into()
+    }
+
+    #[tokio::test]
+    async fn test_block_on_timeout() {
+        let backend = Backend::new();
+        let timeout = Some(Duration::from_millis(10));
+
+        let ret = backend
+            .block_on(vec!["l".to_string()], timeout, lpop_serve())
+            .await;
+        assert_eq!(ret, None);
+        assert_eq!(backend.inner.blocking.blocked.load(Ordering::SeqCst), 0);
+        assert!(backend.inner.blocking.state().queues.is_empty());
+
+        backend.rpush("l".to_string(), vec![value("a")]).unwrap();
+        let ret = backend
+            .block_on(vec!["l".to_string()], timeout, lpop_serve())
+            .await;
+        assert_eq!(ret, Some(value("a")));
+    }
+
+    #[tokio::test]
+    async fn test_block_on_fifo() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+
+        let mut clients = Vec::new();
+        for _ in 0..3 {
+            let client = backend.clone();
+            clients.push(tokio::spawn(async move {
+                client
+                    .block_on(vec!["a".to_string(), "b".to_string()], None, lpop_serve())
+                    .await
+            }));
+            // let the client block before the next one
+            loop {
+                let blocked = backend.inner.blocking.state().waiters.len();
+                if blocked == clients.len() {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+        }
+
+        backend.rpush("b".to_string(), vec![value("1"), value("2")])?;
+        backend.rpush("a".to_string(), vec![value("3")])?;
+
+        let mut replies = Vec::new();
+        for client in clients {
+            replies.push(client.await.expect("client must not panic"));
+        }
+        assert_eq!(
+            replies,
+            [Some(value("1")), Some(value("2")), Some(value("3"))]
+        );
+        assert_eq!(backend.exists(&["a".to_string(), "b".to_string()]), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_block_on_woken_by_keyspace_changes() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let block = |client: Backend, key: &str| {
+            let keys = vec![key.to_string()];
+            let timeout = Some(Duration::from_secs(5));
+            tokio::spawn(async move { client.block_on(keys, timeout, lpop_serve()).await })
+        };
+        let wait_blocked = || async {
+            while backend.inner.blocking.state().waiters.is_empty() {
+                tokio::task::yield_now().await;
+            }
+        };
+
+        // a string renamed onto the key does not wake the client, a list does
+        let client = block(backend.clone(), "l");
+        wait_blocked().await;
+        backend.set("s".to_string(), BulkString::from("v"));
+        backend.rename("s", "l".to_string())?;
+        assert_eq!(backend.inner.blocking.state().waiters.len(), 1);
+        backend.del(&["l".to_string()]);
+        backend.rpush("src".to_string(), vec![value("renamed")])?;
+        backend.rename("src", "l".to_string())?;
+        assert_eq!(
+            client.await.expect("client must not panic"),
+            Some(value("renamed"))
+        );
+
+        let client = block(backend.clone(), "l");
+        wait_blocked().await;
+        backend.rpush("src".to_string(), vec![value("copied")])?;
+        backend.copy("src", "l".to_string(), None, false)?;
+        assert_eq!(
+            client.await.expect("client must not panic"),
+            Some(value("copied"))
+        );
+
+        let client = block(backend.with_db(1)?, "src");
+        wait_blocked().await;
+        backend.move_key("src", 1)?;
+        assert_eq!(
+            client.await.expect("client must not panic"),
+            Some(value("copied"))
+        );
+
+        let client = block(backend.clone(), "swapped");
+        wait_blocked().await;
+        backend
+            .with_db(2)?
+            .rpush("swapped".to_string(), vec![value("swapped")])?;
+        backend.swap_db(0, 2)?;
+        assert_eq!(
+            client.await.expect("client must not panic"),
+            Some(value("swapped"))
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::mem;
+use std::sync::atomic::Ordering;
+
+use crate::{Backend, BackendErr};
+
+use super::guard::KeysGuard;
+
+pub const DEFAULT_DATABASES: usize = 16;
+
+impl Backend {
+    pub fn databases(&self) -> usize {
+        self.inner.dbs.len()
+    }
+
+    // Index of the database the handle is bound to.
+    pub fn db(&self) -> usize {
+        self.db.load(Ordering::Relaxed)
+    }
+
+    pub fn select(&self, db: usize) -> Result<(), BackendErr> {
+        if db >= self.databases() {
+            return Err(BackendErr::DbIndexOutOfRange);
+        }
+
+        self.db.store(db, Ordering::Relaxed);
+        Ok(())
+    }
+
+    // A new handle on the same databases bound to another one.
+    pub fn with_db(&self, db: usize) -> Result<Backend, BackendErr> {
+        let backend = self.clone();
+        backend.select(db)?;
+        Ok(backend)
+    }
+
+    // Swap the contents of two databases atomically, connections keep their selected index
+    // and see the data of the other database from then on.
+    pub fn swap_db(&self, a: usize, b: usize) -> Result<(), BackendErr> {
+        if a >= self.databases() || b >= self.databases() {
+            return Err(BackendErr::DbIndexOutOfRange);
+        }
+        if a == b {
+            return Ok(());
+        }
+
+        // databases are always locked in ascending order, shards and then expires
+        let (low, high) = (&self.inner.dbs[a.min(b)], &self.inner.dbs[a.max(b)]);
+        let mut low_shards = low
+            .keyspace
+            .shards()
+            .iter()
+            .map(|shard| shard.write())
+            .collect::<Vec<_>>();
+        let mut high_shards = high
+            .keyspace
+            .shards()
+            .iter()
+            .map(|shard| shard.write())
+            .collect::<Vec<_>>();
+
+        // keyspaces share the hasher and the shard amount, so entries stay where they belong
+        for (low, high) in low_shards.iter_mut().zip(high_shards.iter_mut()) {
+            mem::swap(&mut **low, &mut **high);
+        }
+        mem::swap(&mut *low.expires(), &mut *high.expires());
+        mem::swap(&mut *low.field_expires(), &mut *high.field_expires());
+
+        drop((low_shards, high_shards));
+        for db in [a, b] {
+            self.with_db(db)?.signal_db_ready();
+        }
+        Ok(())
+    }
+
+    // Move a key to another database unless it already exists there.
+    pub fn move_key(&self, key: &str, db: usize) -> Result<bool, BackendErr> {
+        let target = self.with_db(db)?;
+        if target.db() == self.db() {
+            return Err(BackendErr::SameObject);
+        }
+
+        let (mut src, mut dest) = lock_pair(self, key, &target, key);
+        if dest.contains_key(key) {
+            return Ok(false);
+        }
+        let Some(entry) = src.remove(key) else {
+            return Ok(false);
+        };
+        let is_list = entry.as_list().is_ok();
+        dest.insert(key.to_string(), entry);
+
+        drop((src, dest));
+        if is_list {
+            target.signal_ready(key);
+        }
+        Ok(true)
+    }
+
+    pub fn flush_all(&self, lazy: bool) {
+        for db in 0..self.databases() {
+            if let Ok(backend) = self.with_db(db) {
+                backend.flush_db(lazy);
+            }
+        }
+    }
+}
+
+// Lock a key in each of two databases, the one with the lower index first so concurrent
+// cross-database operations never deadlock. Both keys may live in the same database.
+pub(crate) fn lock_pair<'a>(
+    src: &'a Backend,
+    src_key: &str,
+    dest: &'a Backend,
+    dest_key: &str,
+) -> (KeysGuard<'a>, KeysGuard<'a>) {
+    if src.db() <= dest.db() {
+        let src = src.lock_keys([src_key]);
+        let dest = dest.lock_keys([dest_key]);
+        (src, dest)
+    } else {
+        let dest = dest.lock_keys([dest_key]);
+        let src = src.lock_keys([src_key]);
+        (src, dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{now_ms, BulkString};
+
+    #[test]
+    fn test_select() -> Result<(), BackendErr> {
+        let backend = Backend::with_databases(4);
+        backend.set("key".to_string(), BulkString::from("db0"));
+
+        let other = backend.clone();
+        other.select(3)?;
+        assert_eq!(other.get("key")?, None);
+        other.set("key".to_string(), BulkString::from("db3"));
+
+        // selecting on a handle never changes the others
+        assert_eq!(backend.db(), 0);
+        assert_eq!(backend.get("key")?, Some(BulkString::from("db0")));
+        assert_eq!(backend.select(4), Err(BackendErr::DbIndexOutOfRange));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_swap_db() -> Result<(), BackendErr> {
+        let backend = Backend::with_databases(2);
+        let other = backend.with_db(1)?;
+        for i in 0..100 {
+            backend.set(format!("key:{}", i), BulkString::from("db0"));
+        }
+        other.set("only".to_string(), BulkString::from("db1"));
+        backend.expire_at("key:0", now_ms() + 10_000, Default::default());
+
+        backend.swap_db(0, 1)?;
+        assert_eq!(backend.db_size(), 1);
+        assert_eq!(backend.get("only")?, Some(BulkString::from("db1")));
+        assert_eq!(other.db_size(), 100);
+        assert_eq!(other.get("key:42")?, Some(BulkString::from("db0")));
+        assert!(other.expire_time("key:0").flatten().is_some());
+        assert!(backend.expires().is_empty());
+
+        assert_eq!(backend.swap_db(0, 2), Err(BackendErr::DbIndexOutOfRange));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_key() -> Result<(), BackendErr> {
+        let backend = Backend::with_databases(2);
+        let other = backend.with_db(1)?;
+        backend.set("a".to_string(), BulkString::from("1"));
+        backend.set("b".to_string(), BulkString::from("2"));
+        other.set("b".to_string(), BulkString::from("other"));
+
+        assert!(backend.move_key("a", 1)?);
+        assert_eq!(backend.get("a")?, None);
+        assert_eq!(other.get("a")?, Some(BulkString::from("1")));
+
+        assert!(!backend.move_key("b", 1)?);
+        assert!(!backend.move_key("missing", 1)?);
+        assert_eq!(backend.move_key("b", 0), Err(BackendErr::SameObject));
+        assert_eq!(backend.move_key("b", 2), Err(BackendErr::DbIndexOutOfRange));
+
+        Ok(())
+    }
+}
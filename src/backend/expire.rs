@@ -0,0 +1,215 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::Backend;
+
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+const ACTIVE_EXPIRE_KEYS_PER_CYCLE: usize = 200;
+
+// Condition options of the expire command family, XX may be combined with GT or LT.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExpireOpt {
+    // set expiry only when the key has no expiry
+    pub nx: bool,
+    // set expiry only when the key has an existing expiry
+    pub xx: bool,
+    // set expiry only when the new expiry is greater than current one
+    pub gt: bool,
+    // set expiry only when the new expiry is less than current one
+    pub lt: bool,
+}
+
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+impl ExpireOpt {
+    // A key without expiry is treated as having an infinite ttl.
+    pub fn allows(&self, current: Option<i64>, at: i64) -> bool {
+        !(self.nx && current.is_some()
+            || self.xx && current.is_none()
+            || self.gt && current.is_none_or(|current| at <= current)
+            || self.lt && current.is_some_and(|current| at >= current))
+    }
+}
+
+impl Backend {
+    // Set the absolute expire time (unix milliseconds) of a key.
+    // Returns false when the key does not exist or the condition is not met.
+    // A time in the past deletes the key immediately.
+    pub fn expire_at(&self, key: &str, at: i64, opt: ExpireOpt) -> bool {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return false;
+        };
+
+        if !opt.allows(entry.expire_at, at) {
+            return false;
+        }
+
+        if at <= now_ms() {
+            drop(entry);
+            self.keyspace.remove(key);
+            return true;
+        }
+
+        entry.expire_at = Some(at);
+        self.register_expire(key, at);
+        true
+    }
+
+    // Track a key that has been given an expire time so the active expiry can reap it.
+    pub(crate) fn register_expire(&self, key: &str, at: i64) {
+        self.expires().insert((at, key.to_string()));
+    }
+
+    // Get the absolute expire time (unix milliseconds) of a key.
+    // Returns None when the key does not exist and Some(None) when it has no expiry.
+    pub fn expire_time(&self, key: &str) -> Option<Option<i64>> {
+        self.get_entry(key).map(|entry| entry.expire_at)
+    }
+
+    pub fn persist(&self, key: &str) -> bool {
+        match self.get_entry_mut(key) {
+            Some(mut entry) => entry.expire_at.take().is_some(),
+            None => false,
+        }
+    }
+
+    // Reap at most `limit` keys whose deadline has passed, returns the number of records handled.
+    pub fn active_expire_cycle(&self, limit: usize) -> usize {
+        let now = now_ms();
+
+        let due = {
+            let mut expires = self.expires();
+            let mut due = Vec::with_capacity(limit);
+            while due.len() < limit {
+                match expires.first() {
+                    Some((at, _)) if *at <= now => due.extend(expires.pop_first()),
+                    _ => break,
+                }
+            }
+            due
+        };
+
+        for (at, key) in due.iter() {
+            self.keyspace.remove_if(key, |_, entry| {
+                entry.expire_at == Some(*at) && entry.is_expired(now)
+            });
+        }
+
+        due.len()
+    }
+
+    // Periodically reap expired keys and hash fields of every database in the background.
+    // A full cycle yields to other tasks before continuing so the server is never stalled.
+    pub async fn active_expire(self) {
+        let mut interval = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+        loop {
+            interval.tick().await;
+            for db in 0..self.databases() {
+                let Ok(backend) = self.with_db(db) else {
+                    continue;
+                };
+                while backend.active_expire_cycle(ACTIVE_EXPIRE_KEYS_PER_CYCLE)
+                    == ACTIVE_EXPIRE_KEYS_PER_CYCLE
+                {
+                    tokio::task::yield_now().await;
+                }
+                while backend.active_expire_fields_cycle(ACTIVE_EXPIRE_KEYS_PER_CYCLE)
+                    == ACTIVE_EXPIRE_KEYS_PER_CYCLE
+                {
+                    tokio::task::yield_now().await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BulkString;
+
+    #[test]
+    fn test_expire_opt() {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), BulkString::from("world"));
+
+        let (nx, xx, gt, lt) = (
+            ExpireOpt {
+                nx: true,
+                ..Default::default()
+            },
+            ExpireOpt {
+                xx: true,
+                ..Default::default()
+            },
+            ExpireOpt {
+                gt: true,
+                ..Default::default()
+            },
+            ExpireOpt {
+                lt: true,
+                ..Default::default()
+            },
+        );
+
+        let now = now_ms();
+        assert!(!backend.expire_at("hello", now + 10_000, xx));
+        assert!(!backend.expire_at("hello", now + 10_000, gt));
+        assert!(backend.expire_at("hello", now + 10_000, nx));
+        assert!(!backend.expire_at("hello", now + 20_000, nx));
+        assert!(!backend.expire_at("hello", now + 20_000, lt));
+        assert!(backend.expire_at("hello", now + 20_000, gt));
+        assert!(backend.expire_at("hello", now + 5_000, ExpireOpt { xx: true, ..lt }));
+        assert_eq!(backend.expire_time("hello"), Some(Some(now + 5_000)));
+
+        assert!(backend.persist("hello"));
+        assert!(!backend.persist("hello"));
+        assert_eq!(backend.expire_time("hello"), Some(None));
+        assert!(backend.expire_at("hello", now + 5_000, lt));
+
+        assert!(!backend.expire_at("missing", now + 10_000, ExpireOpt::default()));
+        assert_eq!(backend.expire_time("missing"), None);
+    }
+
+    #[test]
+    fn test_lazy_expire() {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), BulkString::from("world"));
+
+        assert!(backend.expire_at("hello", now_ms() - 1, ExpireOpt::default()));
+        assert!(backend.keyspace.is_empty());
+
+        backend.set("hello".to_string(), BulkString::from("world"));
+        backend.keyspace.get_mut("hello").unwrap().expire_at = Some(now_ms() - 1);
+        assert_eq!(backend.get("hello"), Ok(None));
+        assert!(backend.keyspace.is_empty());
+    }
+
+    #[test]
+    fn test_active_expire_cycle() {
+        let backend = Backend::new();
+        let at = now_ms() + 10;
+        for i in 0..5 {
+            let key = format!("key{}", i);
+            backend.set(key.clone(), BulkString::from("value"));
+            assert!(backend.expire_at(&key, at, ExpireOpt::default()));
+        }
+        backend.set("persist".to_string(), BulkString::from("value"));
+
+        // key reset before the active cycle must survive
+        backend.set("key0".to_string(), BulkString::from("value"));
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(backend.active_expire_cycle(3), 3);
+        assert_eq!(backend.active_expire_cycle(3), 2);
+        assert_eq!(backend.active_expire_cycle(3), 0);
+
+        assert_eq!(backend.keyspace.len(), 2);
+        assert!(backend.keyspace.contains_key("key0"));
+        assert!(backend.keyspace.contains_key("persist"));
+    }
+}
@@ -0,0 +1,147 @@
+// Limit of nested `*` while matching, so hostile patterns can not exhaust the stack.
+const MAX_NESTING: usize = 1000;
+
+// Glob-style matching like redis: `*`, `?`, `[abc]`, `[^abc]`, `[a-z]` and `\` escapes.
+pub fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    let mut skip_longer = false;
+    matches(pattern, string, &mut skip_longer, 0)
+}
+
+fn matches(pattern: &[u8], string: &[u8], skip_longer: &mut bool, nesting: usize) -> bool {
+    if nesting > MAX_NESTING {
+        return false;
+    }
+
+    let (mut p, mut s) = (0, 0);
+    while p < pattern.len() && s < string.len() {
+        match pattern[p] {
+            b'*' => {
+                while pattern.get(p + 1) == Some(&b'*') {
+                    p += 1;
+                }
+                if p + 1 == pattern.len() {
+                    return true;
+                }
+
+                while s < string.len() {
+                    if matches(&pattern[p + 1..], &string[s..], skip_longer, nesting + 1) {
+                        return true;
+                    }
+                    // a longer string can not match if the rest of the pattern already failed to
+                    if *skip_longer {
+                        return false;
+                    }
+                    s += 1;
+                }
+                *skip_longer = true;
+                return false;
+            }
+            b'?' => s += 1,
+            b'[' => {
+                p += 1;
+                let not = pattern.get(p) == Some(&b'^');
+                if not {
+                    p += 1;
+                }
+
+                let c = string[s];
+                let mut matched = false;
+                // an unterminated class ends with the pattern
+                while p < pattern.len() && pattern[p] != b']' {
+                    if pattern[p] == b'\\' && p + 1 < pattern.len() {
+                        p += 1;
+                        matched |= pattern[p] == c;
+                    } else if p + 2 < pattern.len() && pattern[p + 1] == b'-' {
+                        let (start, end) = (
+                            pattern[p].min(pattern[p + 2]),
+                            pattern[p].max(pattern[p + 2]),
+                        );
+                        matched |= (start..=end).contains(&c);
+                        p += 2;
+                    } else {
+                        matched |= pattern[p] == c;
+                    }
+                    p += 1;
+                }
+
+                if matched == not {
+                    return false;
+                }
+                s += 1;
+            }
+            b'\\' if p + 1 < pattern.len() => {
+                p += 1;
+                if pattern[p] != string[s] {
+                    return false;
+                }
+                s += 1;
+            }
+            c => {
+                if c != string[s] {
+                    return false;
+                }
+                s += 1;
+            }
+        }
+        p += 1;
+    }
+
+    if s == string.len() {
+        while pattern.get(p) == Some(&b'*') {
+            p += 1;
+        }
+    }
+    p >= pattern.len() && s == string.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("*", "", true),
+            ("*", "anything", true),
+            ("h?llo", "hello", true),
+            ("h?llo", "hllo", false),
+            ("h*llo", "heeeello", true),
+            ("h*llo", "hello world", false),
+            ("h[ae]llo", "hallo", true),
+            ("h[ae]llo", "hillo", false),
+            ("h[^e]llo", "hallo", true),
+            ("h[^e]llo", "hello", false),
+            ("h[a-b]llo", "hbllo", true),
+            ("h[b-a]llo", "hbllo", true),
+            ("h[a-b]llo", "hcllo", false),
+            ("h\\*llo", "h*llo", true),
+            ("h\\*llo", "hello", false),
+            ("h[\\]]llo", "h]llo", true),
+            ("user:*:name", "user:1000:name", true),
+            ("user:*:name", "user:1000:email", false),
+            ("a*b*c", "aXbYc", true),
+            ("a*b*c", "aXbY", false),
+            ("abc**", "abc", true),
+            ("[abc", "a", true),
+            ("", "", true),
+            ("", "a", false),
+        ];
+
+        for (pattern, string, expected) in cases {
+            assert_eq!(
+                glob_match(pattern.as_bytes(), string.as_bytes()),
+                *expected,
+                "{} ~ {}",
+                pattern,
+                string
+            );
+        }
+    }
+
+    #[test]
+    fn test_glob_match_nesting() {
+        let pattern = "a*".repeat(2000) + "b";
+        let string = "a".repeat(3000);
+        assert!(!glob_match(pattern.as_bytes(), string.as_bytes()));
+    }
+}
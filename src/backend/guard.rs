@@ -0,0 +1,134 @@
+use std::hash::BuildHasher;
+
+use dashmap::{RwLockWriteGuard, SharedValue};
+use hashbrown::raw::RawTable;
+
+use crate::{now_ms, Backend, Entry};
+
+type Shard = RawTable<(String, SharedValue<Entry>)>;
+
+// Write guards on every keyspace shard holding one of the given keys.
+// Shards are always locked in ascending order so multi-key operations are atomic
+// without deadlocking each other or the single-key operations.
+pub(crate) struct KeysGuard<'a> {
+    backend: &'a Backend,
+    shards: Vec<(usize, RwLockWriteGuard<'a, Shard>)>,
+    now: i64,
+}
+
+impl Backend {
+    pub(crate) fn lock_keys<'a, I, K>(&'a self, keys: I) -> KeysGuard<'a>
+    where
+        I: IntoIterator<Item = K>,
+        K: AsRef<str>,
+    {
+        let mut indexes = keys
+            .into_iter()
+            .map(|key| self.keyspace.determine_map(key.as_ref()))
+            .collect::<Vec<usize>>();
+        indexes.sort_unstable();
+        indexes.dedup();
+
+        let shards = indexes
+            .into_iter()
+            .map(|i| (i, self.keyspace.shards()[i].write()))
+            .collect();
+
+        KeysGuard {
+            backend: self,
+            shards,
+            now: now_ms(),
+        }
+    }
+}
+
+impl KeysGuard<'_> {
+    // Get the live entry of a locked key, expired entries are treated as absent
+    // and so are hashes whose fields have all expired, like get_entry does.
+    pub(crate) fn get(&self, key: &str) -> Option<&Entry> {
+        let (hash, shard) = self.shard_ref(key);
+
+        shard
+            .get(hash, |(k, _)| k == key)
+            .map(|(_, v)| v.get())
+            .filter(|entry| is_live(entry, self.now))
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &str) -> Option<&mut Entry> {
+        let now = self.now;
+        let (hash, shard) = self.shard(key);
+
+        shard
+            .get_mut(hash, |(k, _)| k == key)
+            .map(|(_, v)| v.get_mut())
+            .filter(|entry| is_live(entry, now))
+    }
+
+    pub(crate) fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Insert an entry into a locked key, returns the old live entry.
+    pub(crate) fn insert(&mut self, key: String, entry: Entry) -> Option<Entry> {
+        if let Some(at) = entry.expire_at {
+            self.backend.register_expire(&key, at);
+        }
+        if let Some(at) = entry.as_hash().ok().and_then(|hash| hash.next_expire()) {
+            self.backend.register_field_expire(&key, at);
+        }
+
+        let old = self.remove(&key);
+        let build = self.backend.keyspace.hasher().clone();
+        let (hash, shard) = self.shard(&key);
+        shard.insert(hash, (key, SharedValue::new(entry)), |(k, _)| {
+            build.hash_one(k)
+        });
+
+        old
+    }
+
+    // Remove a locked key, returns the old live entry.
+    pub(crate) fn remove(&mut self, key: &str) -> Option<Entry> {
+        let now = self.now;
+        let (hash, shard) = self.shard(key);
+
+        shard
+            .remove_entry(hash, |(k, _)| k == key)
+            .map(|(_, v)| v.into_inner())
+            .filter(|entry| is_live(entry, now))
+    }
+
+    fn shard_ref(&self, key: &str) -> (u64, &Shard) {
+        let (hash, index) = self.locate(key);
+        let (_, shard) = self
+            .shards
+            .iter()
+            .find(|(i, _)| *i == index)
+            .expect("key must be locked before accessing it");
+
+        (hash, shard)
+    }
+
+    fn shard(&mut self, key: &str) -> (u64, &mut Shard) {
+        let (hash, index) = self.locate(key);
+        let (_, shard) = self
+            .shards
+            .iter_mut()
+            .find(|(i, _)| *i == index)
+            .expect("key must be locked before accessing it");
+
+        (hash, shard)
+    }
+
+    // Hash of the key and index of the shard it lives in, the same way dashmap computes them.
+    fn locate(&self, key: &str) -> (u64, usize) {
+        let hash = self.backend.keyspace.hasher().hash_one(key);
+        (hash, self.backend.keyspace.determine_shard(hash as usize))
+    }
+}
+
+// Whether an entry is live, a hash reaped down to no field is not, even when an earlier
+// access already reaped it and left it in the shard.
+fn is_live(entry: &Entry, now: i64) -> bool {
+    !entry.is_expired(now) && !entry.reap_fields(now) && !entry.is_empty_collection()
+}
@@ -0,0 +1,390 @@
+use crate::{now_ms, Backend, BackendErr, ExpireOpt, HashField, HashValue, RespFrame, Value};
+
+// Outcome of setting the expire time of a hash field, the discriminant is the reply code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldExpire {
+    NoField = -2,
+    NotMet = 0,
+    Set = 1,
+    Deleted = 2,
+}
+
+// Condition on the existing fields of hsetex, FNX sets only if none exists and FXX only if all do.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FieldCond {
+    #[default]
+    Any,
+    Fnx,
+    Fxx,
+}
+
+impl Backend {
+    // Set the absolute expire time (unix milliseconds) of fields of a hash.
+    // A time in the past deletes the fields, and the key along with its last field.
+    pub fn hexpire_at(
+        &self,
+        key: &str,
+        fields: &[String],
+        at: i64,
+        opt: ExpireOpt,
+    ) -> Result<Vec<FieldExpire>, BackendErr> {
+        let Some(entry) = self.get_entry_mut(key) else {
+            return Ok(vec![FieldExpire::NoField; fields.len()]);
+        };
+
+        let hash = entry.as_hash()?;
+        let now = now_ms();
+        let ret = fields
+            .iter()
+            .map(|field| {
+                let Some(mut item) = hash.get_mut(field) else {
+                    return FieldExpire::NoField;
+                };
+                if !opt.allows(item.expire_at, at) {
+                    return FieldExpire::NotMet;
+                }
+                if at <= now {
+                    drop(item);
+                    hash.remove(field);
+                    return FieldExpire::Deleted;
+                }
+
+                item.expire_at = Some(at);
+                FieldExpire::Set
+            })
+            .collect::<Vec<_>>();
+
+        if ret.contains(&FieldExpire::Set) {
+            hash.track_expire(at);
+            self.register_field_expire(key, at);
+        }
+        let empty = hash.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(ret)
+    }
+
+    // Get the absolute expire time (unix milliseconds) of fields of a hash.
+    // Each is None when the field does not exist and Some(None) when it has no expiry.
+    pub fn hexpire_time(
+        &self,
+        key: &str,
+        fields: &[String],
+    ) -> Result<Vec<Option<Option<i64>>>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(vec![None; fields.len()]);
+        };
+
+        let hash = entry.as_hash()?;
+        Ok(fields
+            .iter()
+            .map(|field| hash.get(field).map(|item| item.expire_at))
+            .collect())
+    }
+
+    // Remove the expire time of fields of a hash.
+    // Each is None when the field does not exist, otherwise whether it had an expiry.
+    pub fn hpersist(&self, key: &str, fields: &[String]) -> Result<Vec<Option<bool>>, BackendErr> {
+        let Some(entry) = self.get_entry_mut(key) else {
+            return Ok(vec![None; fields.len()]);
+        };
+
+        let hash = entry.as_hash()?;
+        Ok(fields
+            .iter()
+            .map(|field| {
+                hash.get_mut(field)
+                    .map(|mut item| item.expire_at.take().is_some())
+            })
+            .collect())
+    }
+
+    // Get the values of fields of a hash and update their expire time like getex.
+    // ttl is None to keep the expire times, Some(None) to persist the fields.
+    pub fn hgetex(
+        &self,
+        key: &str,
+        fields: &[String],
+        ttl: Option<Option<i64>>,
+    ) -> Result<Vec<Option<RespFrame>>, BackendErr> {
+        let Some(entry) = self.get_entry_mut(key) else {
+            return Ok(vec![None; fields.len()]);
+        };
+
+        let hash = entry.as_hash()?;
+        let now = now_ms();
+        let mut values = Vec::with_capacity(fields.len());
+        let mut tracked = false;
+        for field in fields {
+            let Some(mut item) = hash.get_mut(field) else {
+                values.push(None);
+                continue;
+            };
+            values.push(Some(item.value.clone()));
+
+            match ttl {
+                Some(Some(at)) if at <= now => {
+                    drop(item);
+                    hash.remove(field);
+                }
+                Some(at) => {
+                    item.expire_at = at;
+                    tracked |= at.is_some();
+                }
+                None => {}
+            }
+        }
+
+        if let Some(Some(at)) = ttl.filter(|_| tracked) {
+            hash.track_expire(at);
+            self.register_field_expire(key, at);
+        }
+        let empty = hash.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(values)
+    }
+
+    // Set fields of a hash together with their expire time, returns whether they were set.
+    // ttl is None to keep the expire times of existing fields, Some(None) to clear them.
+    pub fn hsetex(
+        &self,
+        key: String,
+        pairs: Vec<(String, RespFrame)>,
+        cond: FieldCond,
+        ttl: Option<Option<i64>>,
+    ) -> Result<bool, BackendErr> {
+        let entry = self.get_entry_or_insert(key.clone(), || Value::Hash(HashValue::new()));
+        let hash = entry.as_hash()?;
+
+        let allowed = match cond {
+            FieldCond::Any => true,
+            FieldCond::Fnx => pairs.iter().all(|(field, _)| !hash.contains_key(field)),
+            FieldCond::Fxx => pairs.iter().all(|(field, _)| hash.contains_key(field)),
+        };
+        if allowed {
+            let now = now_ms();
+            for (field, value) in pairs {
+                match ttl {
+                    Some(Some(at)) if at <= now => {
+                        hash.remove(&field);
+                    }
+                    Some(expire_at) => {
+                        hash.insert(field, HashField { value, expire_at });
+                    }
+                    None => {
+                        let expire_at = hash.get(&field).and_then(|item| item.expire_at);
+                        hash.insert(field, HashField { value, expire_at });
+                    }
+                }
+            }
+
+            if let Some(Some(at)) = ttl.filter(|ttl| ttl.is_some_and(|at| at > now)) {
+                hash.track_expire(at);
+                self.register_field_expire(&key, at);
+            }
+        }
+        let empty = hash.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(&key);
+        }
+        Ok(allowed)
+    }
+
+    // Track a hash that has been given a field with an expire time so the active expiry
+    // can reap it.
+    pub(crate) fn register_field_expire(&self, key: &str, at: i64) {
+        self.field_expires().insert((at, key.to_string()));
+    }
+
+    // Reap expired fields of at most `limit` hashes, returns the number of records handled.
+    pub fn active_expire_fields_cycle(&self, limit: usize) -> usize {
+        let now = now_ms();
+
+        let due = {
+            let mut field_expires = self.field_expires();
+            let mut due = Vec::with_capacity(limit);
+            while due.len() < limit {
+                match field_expires.first() {
+                    Some((at, _)) if *at <= now => due.extend(field_expires.pop_first()),
+                    _ => break,
+                }
+            }
+            due
+        };
+
+        for (_, key) in due.iter() {
+            let Some(entry) = self.keyspace.get(key) else {
+                continue;
+            };
+            let Ok(hash) = entry.as_hash() else {
+                continue;
+            };
+
+            hash.reap(now);
+            let (empty, next) = (hash.is_empty(), hash.next_expire());
+            drop(entry);
+
+            if empty {
+                self.remove_if_empty(key);
+            } else if let Some(next) = next {
+                self.register_field_expire(key, next);
+            }
+        }
+
+        due.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::BulkString;
+
+    fn fields(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|field| field.to_string()).collect()
+    }
+
+    fn hset(backend: &Backend, key: &str, fields: &[&str]) -> Result<(), BackendErr> {
+        let pairs = fields
+            .iter()
+            .map(|field| (field.to_string(), BulkString::from(*field).into()))
+            .collect();
+        backend.hset(key.to_string(), pairs)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexpire_at() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        hset(&backend, "h", &["a", "b", "c"])?;
+
+        let at = now_ms() + 10_000;
+        let nx = ExpireOpt {
+            nx: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            backend.hexpire_at("h", &fields(&["a", "missing"]), at, nx)?,
+            [FieldExpire::Set, FieldExpire::NoField]
+        );
+        assert_eq!(
+            backend.hexpire_at("h", &fields(&["a", "b"]), at, nx)?,
+            [FieldExpire::NotMet, FieldExpire::Set]
+        );
+        assert_eq!(
+            backend.hexpire_time("h", &fields(&["a", "c", "missing"]))?,
+            [Some(Some(at)), Some(None), None]
+        );
+        assert_eq!(
+            backend.hpersist("h", &fields(&["a", "c", "missing"]))?,
+            [Some(true), Some(false), None]
+        );
+
+        // a deadline in the past deletes the fields right away
+        let past = now_ms() - 1;
+        assert_eq!(
+            backend.hexpire_at("h", &fields(&["a", "b", "c"]), past, ExpireOpt::default())?,
+            [FieldExpire::Deleted; 3]
+        );
+        assert_eq!(backend.exists(&fields(&["h"])), 0);
+        assert_eq!(
+            backend.hexpire_at("h", &fields(&["a"]), at, ExpireOpt::default())?,
+            [FieldExpire::NoField]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_field_expire() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        hset(&backend, "h", &["a", "b"])?;
+
+        let at = now_ms() + 10;
+        backend.hexpire_at("h", &fields(&["a"]), at, ExpireOpt::default())?;
+        std::thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(backend.hlen("h")?, 1);
+        assert_eq!(backend.hget("h", "a")?, None);
+
+        backend.hexpire_at("h", &fields(&["b"]), now_ms() + 10, ExpireOpt::default())?;
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(backend.key_type("h"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_active_expire_fields_cycle() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        hset(&backend, "h1", &["a", "b"])?;
+        hset(&backend, "h2", &["a"])?;
+
+        let at = now_ms() + 10;
+        backend.hexpire_at("h1", &fields(&["a"]), at, ExpireOpt::default())?;
+        backend.hexpire_at("h2", &fields(&["a"]), at, ExpireOpt::default())?;
+        std::thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(backend.active_expire_fields_cycle(10), 2);
+        assert_eq!(backend.active_expire_fields_cycle(10), 0);
+        assert!(!backend.keyspace.contains_key("h2"));
+
+        let entry = backend.keyspace.get("h1").expect("h1 keeps a live field");
+        let hash = entry.as_hash()?;
+        assert_eq!(hash.len(), 1);
+        assert!(hash.contains_key("b"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hgetex_and_hsetex() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let pairs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(field, value)| (field.to_string(), BulkString::from(*value).into()))
+                .collect::<Vec<_>>()
+        };
+
+        let at = now_ms() + 10_000;
+        let set = backend.hsetex("h".to_string(), pairs(&[("a", "1")]), FieldCond::Fxx, None)?;
+        assert!(!set);
+        assert_eq!(backend.exists(&fields(&["h"])), 0);
+
+        let ttl = Some(Some(at));
+        let set = backend.hsetex("h".to_string(), pairs(&[("a", "1")]), FieldCond::Fnx, ttl)?;
+        assert!(set);
+        let set = backend.hsetex("h".to_string(), pairs(&[("a", "2")]), FieldCond::Any, None)?;
+        assert!(set);
+        assert_eq!(
+            backend.hexpire_time("h", &fields(&["a"]))?,
+            [Some(Some(at))]
+        );
+
+        assert_eq!(
+            backend.hgetex("h", &fields(&["a", "missing"]), Some(None))?,
+            [Some(BulkString::from("2").into()), None]
+        );
+        assert_eq!(backend.hexpire_time("h", &fields(&["a"]))?, [Some(None)]);
+
+        assert_eq!(
+            backend.hgetex("h", &fields(&["a"]), Some(Some(now_ms() - 1)))?,
+            [Some(BulkString::from("2").into())]
+        );
+        assert_eq!(backend.exists(&fields(&["h"])), 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,307 @@
+use dashmap::mapref::entry::Entry as DashEntry;
+use dashmap::mapref::multiple::RefMulti;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+
+use super::map::incr_float;
+use crate::{
+    parse_f64, parse_i64, Backend, BackendErr, BulkString, HashField, HashValue, RespFrame, Value,
+};
+
+impl Backend {
+    // Set fields of a hash, returns the number of fields that did not exist before.
+    pub fn hset(&self, key: String, pairs: Vec<(String, RespFrame)>) -> Result<usize, BackendErr> {
+        let entry = self.get_entry_or_insert(key, || Value::Hash(HashValue::new()));
+        let hash = entry.as_hash()?;
+
+        let mut added = 0;
+        for (field, value) in pairs {
+            if hash.insert(field, HashField::new(value)).is_none() {
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    // Set a field only when it does not exist yet, returns whether it was set.
+    pub fn hsetnx(&self, key: String, field: String, value: RespFrame) -> Result<bool, BackendErr> {
+        let entry = self.get_entry_or_insert(key, || Value::Hash(HashValue::new()));
+        let hash = entry.as_hash()?;
+        if hash.contains_key(&field) {
+            return Ok(false);
+        }
+
+        hash.insert(field, HashField::new(value));
+        Ok(true)
+    }
+
+    // Increment the integer stored in a field, a missing field is treated as 0.
+    pub fn hincr_by(&self, key: String, field: String, delta: i64) -> Result<i64, BackendErr> {
+        let entry = self.get_entry_or_insert(key, || Value::Hash(HashValue::new()));
+        let mut value = entry
+            .as_hash()?
+            .entry(field)
+            .or_insert_with(|| HashField::new(BulkString::from("0").into()));
+
+        let current = match &value.value {
+            RespFrame::BulkString(s) => parse_i64(s),
+            RespFrame::Integer(i) => Some(*i),
+            _ => None,
+        };
+        let current = current.ok_or(BackendErr::HashNotInteger)?;
+        let ret = current.checked_add(delta).ok_or(BackendErr::Overflow)?;
+
+        value.value = BulkString::from(ret.to_string()).into();
+        Ok(ret)
+    }
+
+    // Increment the float stored in a field, a missing field is treated as 0.
+    pub fn hincr_by_float(
+        &self,
+        key: String,
+        field: String,
+        delta: f64,
+    ) -> Result<BulkString, BackendErr> {
+        let entry = self.get_entry_or_insert(key.clone(), || Value::Hash(HashValue::new()));
+        let hash = entry.as_hash()?;
+
+        // the field is only written once the result is known to be valid
+        let ret = match hash.entry(field) {
+            DashEntry::Occupied(mut value) => {
+                let current = match &value.get().value {
+                    RespFrame::BulkString(s) => parse_f64(s),
+                    RespFrame::Integer(i) => Some(*i as f64),
+                    _ => None,
+                };
+                current
+                    .ok_or(BackendErr::HashNotFloat)
+                    .and_then(|current| incr_float(current, delta))
+                    .inspect(|ret| value.get_mut().value = ret.clone().into())
+            }
+            DashEntry::Vacant(value) => incr_float(0.0, delta).inspect(|ret| {
+                value.insert(HashField::new(ret.clone().into()));
+            }),
+        };
+        let empty = hash.is_empty();
+
+        // a failed increment on a missing key leaves no empty hash behind
+        drop(entry);
+        if empty {
+            self.remove_if_empty(&key);
+        }
+        ret
+    }
+
+    pub fn hget(&self, key: &str, field: &str) -> Result<Option<RespFrame>, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry.as_hash()?.get(field).map(|v| v.value.clone())),
+            None => Ok(None),
+        }
+    }
+
+    pub fn hmget(
+        &self,
+        key: &str,
+        fields: &[String],
+    ) -> Result<Vec<Option<RespFrame>>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(vec![None; fields.len()]);
+        };
+
+        let hash = entry.as_hash()?;
+        Ok(fields
+            .iter()
+            .map(|field| hash.get(field).map(|v| v.value.clone()))
+            .collect())
+    }
+
+    pub fn hgetall(&self, key: &str) -> Result<Option<Vec<(String, RespFrame)>>, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(Some(
+                entry
+                    .as_hash()?
+                    .iter()
+                    .map(|item| (item.key().clone(), item.value.clone()))
+                    .collect(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    // Delete fields of a hash, the key is removed together with its last field.
+    pub fn hdel(&self, key: &str, fields: &[String]) -> Result<usize, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(0);
+        };
+
+        let hash = entry.as_hash()?;
+        let deleted = fields
+            .iter()
+            .filter(|field| hash.remove(*field).is_some())
+            .count();
+        let empty = hash.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(deleted)
+    }
+
+    pub fn hexists(&self, key: &str, field: &str) -> Result<bool, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry.as_hash()?.contains_key(field)),
+            None => Ok(false),
+        }
+    }
+
+    pub fn hlen(&self, key: &str) -> Result<usize, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry.as_hash()?.len()),
+            None => Ok(0),
+        }
+    }
+
+    pub fn hkeys(&self, key: &str) -> Result<Vec<String>, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry
+                .as_hash()?
+                .iter()
+                .map(|item| item.key().clone())
+                .collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn hvals(&self, key: &str) -> Result<Vec<RespFrame>, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry
+                .as_hash()?
+                .iter()
+                .map(|item| item.value.clone())
+                .collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // Length of the value of a field, 0 when the field does not exist.
+    pub fn hstrlen(&self, key: &str, field: &str) -> Result<usize, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(0);
+        };
+
+        let len = match entry
+            .as_hash()?
+            .get(field)
+            .as_deref()
+            .map(|field| &field.value)
+        {
+            Some(RespFrame::BulkString(value)) => value.len(),
+            Some(RespFrame::Integer(value)) => value.to_string().len(),
+            _ => 0,
+        };
+        Ok(len)
+    }
+
+    // Random fields of a hash, distinct ones for a positive count
+    // and possibly repeated ones for a negative count.
+    pub fn hrandfield(
+        &self,
+        key: &str,
+        count: i64,
+    ) -> Result<Vec<(String, RespFrame)>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(Vec::new());
+        };
+
+        let hash = entry.as_hash()?;
+        let mut rng = rand::thread_rng();
+        let item =
+            |item: &RefMulti<'_, String, HashField>| (item.key().clone(), item.value.clone());
+
+        if count >= 0 {
+            // the sample is allocated upfront, it can not hold more than the whole hash
+            let count = (count as usize).min(hash.len());
+            let items = hash.iter().choose_multiple(&mut rng, count);
+            return Ok(items.iter().map(item).collect());
+        }
+
+        let items = hash.iter().collect::<Vec<_>>();
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok((0..count.unsigned_abs())
+            .map(|_| item(&items[rng.gen_range(0..items.len())]))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hdel_removes_empty_hash() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        for field in ["a", "b"] {
+            backend.hset(
+                "h".to_string(),
+                vec![(field.to_string(), BulkString::from("v").into())],
+            )?;
+        }
+
+        let fields = ["a".to_string(), "c".to_string()];
+        assert_eq!(backend.hdel("h", &fields)?, 1);
+        assert_eq!(backend.exists(&["h".to_string()]), 1);
+
+        assert_eq!(backend.hdel("h", &["b".to_string()])?, 1);
+        assert_eq!(backend.exists(&["h".to_string()]), 0);
+        assert_eq!(backend.hdel("h", &["b".to_string()])?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincr_by_float_error_keeps_hash_absent() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let incr =
+            |field: &str, delta| backend.hincr_by_float("h".to_string(), field.to_string(), delta);
+
+        assert_eq!(incr("f", f64::INFINITY), Err(BackendErr::NanOrInfinity));
+        assert_eq!(backend.exists(&["h".to_string()]), 0);
+
+        assert_eq!(incr("f", 0.1)?, BulkString::from("0.1"));
+        assert_eq!(incr("g", f64::INFINITY), Err(BackendErr::NanOrInfinity));
+        assert_eq!(backend.hget("h", "g")?, None);
+        assert_eq!(incr("f", 0.2)?, BulkString::from("0.3"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        for field in ["a", "b", "c"] {
+            backend.hset(
+                "h".to_string(),
+                vec![(field.to_string(), BulkString::from(field).into())],
+            )?;
+        }
+
+        let mut fields = backend.hrandfield("h", 5)?;
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        let keys = fields.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, ["a", "b", "c"]);
+        assert!(fields
+            .iter()
+            .all(|(k, v)| *v == BulkString::from(k.as_str()).into()));
+
+        assert_eq!(backend.hrandfield("h", 2)?.len(), 2);
+        assert_eq!(backend.hrandfield("h", i64::MAX)?.len(), 3);
+        assert_eq!(backend.hrandfield("h", -10)?.len(), 10);
+        assert!(backend.hrandfield("h", 0)?.is_empty());
+        assert!(backend.hrandfield("missing", -3)?.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use rand::Rng;
+
+use crate::{now_ms, Backend, BackendErr, Entry};
+
+use super::db::lock_pair;
+use super::guard::KeysGuard;
+
+// Attempts at sampling a live key from a random shard before falling back to a full scan.
+const RANDOM_KEY_TRIES: usize = 100;
+
+impl Backend {
+    // Delete the keys atomically, returns the number of live keys removed.
+    pub fn del(&self, keys: &[String]) -> usize {
+        let mut guard = self.lock_keys(keys);
+
+        keys.iter()
+            .filter(|key| guard.remove(key).is_some())
+            .count()
+    }
+
+    // Number of live keys among the given ones, a key given twice is counted twice.
+    pub fn exists(&self, keys: &[String]) -> usize {
+        keys.iter()
+            .filter(|key| self.get_entry(key).is_some())
+            .count()
+    }
+
+    pub fn key_type(&self, key: &str) -> Option<&'static str> {
+        self.get_entry(key).map(|entry| entry.type_name())
+    }
+
+    // Rename a key atomically, the value keeps its time to live and overwrites the new key.
+    pub fn rename(&self, key: &str, new_key: String) -> Result<(), BackendErr> {
+        let mut guard = self.lock_keys([key, &new_key]);
+
+        let entry = guard.remove(key).ok_or(BackendErr::NoSuchKey)?;
+        let is_list = entry.as_list().is_ok();
+        guard.insert(new_key.clone(), entry);
+
+        drop(guard);
+        if is_list {
+            self.signal_ready(&new_key);
+        }
+        Ok(())
+    }
+
+    // Rename a key only if the new key does not exist yet.
+    pub fn rename_nx(&self, key: &str, new_key: String) -> Result<bool, BackendErr> {
+        let mut guard = self.lock_keys([key, &new_key]);
+
+        if !guard.contains_key(key) {
+            return Err(BackendErr::NoSuchKey);
+        }
+        if guard.contains_key(&new_key) {
+            return Ok(false);
+        }
+
+        let Some(entry) = guard.remove(key) else {
+            return Ok(true);
+        };
+        let is_list = entry.as_list().is_ok();
+        guard.insert(new_key.clone(), entry);
+
+        drop(guard);
+        if is_list {
+            self.signal_ready(&new_key);
+        }
+        Ok(true)
+    }
+
+    // Copy the value and time to live of a key, into another database when db is given.
+    // The destination is only overwritten with replace.
+    pub fn copy(
+        &self,
+        key: &str,
+        dest: String,
+        db: Option<usize>,
+        replace: bool,
+    ) -> Result<bool, BackendErr> {
+        let target = self.with_db(db.unwrap_or(self.db()))?;
+        if target.db() == self.db() && key == dest {
+            return Err(BackendErr::SameObject);
+        }
+
+        let copied = if target.db() == self.db() {
+            let mut guard = self.lock_keys([key, &dest]);
+            let entry = guard.get(key).cloned();
+            copy_entry(entry, &mut guard, dest.clone(), replace)
+        } else {
+            let (src, mut guard) = lock_pair(self, key, &target, &dest);
+            let entry = src.get(key).cloned();
+            copy_entry(entry, &mut guard, dest.clone(), replace)
+        };
+
+        if copied == Some(true) {
+            target.signal_ready(&dest);
+        }
+        Ok(copied.is_some())
+    }
+
+    // Pick a random live key, sampling random shards first so large keyspaces are not scanned.
+    pub fn random_key(&self) -> Option<String> {
+        let now = now_ms();
+        let shards = self.keyspace.shards();
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..RANDOM_KEY_TRIES {
+            let shard = shards[rng.gen_range(0..shards.len())].read();
+            if shard.is_empty() {
+                continue;
+            }
+
+            let nth = rng.gen_range(0..shard.len());
+            // safety: the bucket is only read while the shard lock is held
+            let found = unsafe { shard.iter().nth(nth).map(|bucket| bucket.as_ref()) };
+            if let Some((key, entry)) = found {
+                if !entry.get().is_expired(now) {
+                    return Some(key.clone());
+                }
+            }
+        }
+
+        self.keyspace
+            .iter()
+            .find(|item| !item.value().is_expired(now))
+            .map(|item| item.key().clone())
+    }
+
+    // Number of keys in the keyspace, including expired keys that are not reaped yet.
+    pub fn db_size(&self) -> usize {
+        self.keyspace.len()
+    }
+}
+
+// Returns None when nothing was copied, otherwise whether the copied entry is a list.
+fn copy_entry(
+    entry: Option<Entry>,
+    guard: &mut KeysGuard,
+    dest: String,
+    replace: bool,
+) -> Option<bool> {
+    let entry = entry?;
+    if !replace && guard.contains_key(&dest) {
+        return None;
+    }
+
+    let is_list = entry.as_list().is_ok();
+    guard.insert(dest, entry);
+    Some(is_list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, ExpireOpt};
+
+    #[test]
+    fn test_rename() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::from("1"));
+        backend.set("b".to_string(), BulkString::from("2"));
+        backend.expire_at("a", now_ms() + 10_000, Default::default());
+
+        backend.rename("a", "b".to_string())?;
+        assert_eq!(backend.get("a")?, None);
+        assert_eq!(backend.get("b")?, Some(BulkString::from("1")));
+        assert!(backend.expire_time("b").flatten().is_some());
+
+        assert_eq!(
+            backend.rename("missing", "c".to_string()),
+            Err(BackendErr::NoSuchKey)
+        );
+
+        // renaming a key to itself keeps it
+        backend.rename("b", "b".to_string())?;
+        assert_eq!(backend.get("b")?, Some(BulkString::from("1")));
+
+        backend.set("c".to_string(), BulkString::from("3"));
+        assert!(!backend.rename_nx("b", "c".to_string())?);
+        assert!(backend.rename_nx("b", "d".to_string())?);
+        assert_eq!(backend.exists(&["b".to_string(), "d".to_string()]), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_guarded_hash_with_expired_fields() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let keys = |keys: &[&str]| keys.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+        for key in ["h1", "h2"] {
+            backend.hset(
+                key.to_string(),
+                vec![
+                    ("a".to_string(), BulkString::from("1").into()),
+                    ("b".to_string(), BulkString::from("2").into()),
+                ],
+            )?;
+        }
+        let at = now_ms() + 10;
+        backend.hexpire_at("h1", &keys(&["a", "b"]), at, ExpireOpt::default())?;
+        backend.hexpire_at("h2", &keys(&["a"]), at, ExpireOpt::default())?;
+        std::thread::sleep(std::time::Duration::from_millis(20));
+
+        // a hash whose fields have all expired is gone for guarded paths too
+        assert!(!backend.copy("h1", "c1".to_string(), None, false)?);
+        assert_eq!(backend.del(&keys(&["h1"])), 0);
+
+        // expired fields are not copied along with the live ones
+        assert!(backend.copy("h2", "c2".to_string(), None, false)?);
+        assert_eq!(backend.hlen("c2")?, 1);
+        assert_eq!(backend.hget("c2", "a")?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_key() {
+        let backend = Backend::new();
+        assert_eq!(backend.random_key(), None);
+
+        backend.set("a".to_string(), BulkString::from("1"));
+        assert_eq!(backend.random_key(), Some("a".to_string()));
+
+        backend.expire_at("a", now_ms() - 1, Default::default());
+        assert_eq!(backend.random_key(), None);
+
+        for i in 0..100 {
+            backend.set(format!("key:{}", i), BulkString::from("v"));
+        }
+        assert!(backend
+            .random_key()
+            .is_some_and(|key| key.starts_with("key:")));
+    }
+}
@@ -0,0 +1,134 @@
+use std::mem;
+use std::sync::mpsc::{self, SendError, Sender};
+use std::sync::OnceLock;
+use std::thread;
+
+use dashmap::SharedValue;
+use hashbrown::raw::RawTable;
+
+use crate::{Backend, Entry, Value};
+
+// Values made of more elements than this are dropped on the lazy free thread, smaller ones
+// are cheaper to drop in place than to hand over.
+const LAZY_FREE_THRESHOLD: usize = 64;
+
+type Shard = RawTable<(String, SharedValue<Entry>)>;
+
+// Anything that is only kept around to be dropped.
+type Garbage = Box<dyn Send>;
+
+// Hands values over to a background thread that drops them, started on first use.
+#[derive(Debug, Default)]
+pub(crate) struct LazyFree(OnceLock<Sender<Garbage>>);
+
+impl LazyFree {
+    fn free(&self, garbage: Garbage) {
+        let sender = self.0.get_or_init(|| {
+            let (sender, receiver) = mpsc::channel::<Garbage>();
+            let spawned = thread::Builder::new()
+                .name("lazy-free".to_string())
+                .spawn(move || receiver.into_iter().for_each(drop));
+            if let Err(e) = spawned {
+                tracing::warn!("failed to spawn the lazy free thread: {:?}", e);
+            }
+            sender
+        });
+
+        // the thread is gone, fall back to dropping in place
+        if let Err(SendError(garbage)) = sender.send(garbage) {
+            drop(garbage);
+        }
+    }
+}
+
+impl Value {
+    // Rough cost of dropping the value, in number of allocations.
+    fn free_effort(&self) -> usize {
+        match self {
+            Value::String(_) => 1,
+            Value::Hash(hash) => hash.len(),
+            Value::List(list) => list.len(),
+            Value::Set(set) => set.len(),
+            Value::ZSet(zset) => zset.len(),
+        }
+    }
+}
+
+impl Backend {
+    // Delete the keys like del, but large values are dropped on the lazy free thread.
+    pub fn unlink(&self, keys: &[String]) -> usize {
+        let mut guard = self.lock_keys(keys);
+
+        let removed = keys
+            .iter()
+            .filter_map(|key| guard.remove(key))
+            .collect::<Vec<Entry>>();
+        drop(guard);
+
+        let count = removed.len();
+        for entry in removed {
+            if entry.free_effort() > LAZY_FREE_THRESHOLD {
+                self.inner.lazy_free.free(Box::new(entry));
+            }
+        }
+        count
+    }
+
+    // Remove every key at once, dropping them on the lazy free thread when lazy is set.
+    pub fn flush_db(&self, lazy: bool) {
+        let mut shards = self
+            .keyspace
+            .shards()
+            .iter()
+            .map(|shard| shard.write())
+            .collect::<Vec<_>>();
+        let tables = shards
+            .iter_mut()
+            .map(|shard| mem::take(&mut **shard))
+            .collect::<Vec<Shard>>();
+        self.expires().clear();
+        self.field_expires().clear();
+        drop(shards);
+
+        if lazy {
+            self.inner.lazy_free.free(Box::new(tables));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespFrame};
+
+    #[test]
+    fn test_unlink() {
+        let backend = Backend::new();
+        backend.set("small".to_string(), BulkString::from("v"));
+        for i in 0..1000 {
+            let value: RespFrame = BulkString::from(i.to_string()).into();
+            backend.rpush("big".to_string(), vec![value]).unwrap();
+        }
+
+        let keys = ["small", "big", "missing", "big"].map(String::from);
+        assert_eq!(backend.unlink(&keys), 2);
+        assert_eq!(backend.exists(&keys), 0);
+    }
+
+    #[test]
+    fn test_flush_db() {
+        let backend = Backend::new();
+        for i in 0..100 {
+            backend.set(format!("key:{}", i), BulkString::from("v"));
+        }
+        backend.expire_at("key:0", crate::now_ms() + 10_000, Default::default());
+
+        backend.flush_db(true);
+        assert_eq!(backend.db_size(), 0);
+        assert!(backend.expires().is_empty());
+
+        backend.set("key".to_string(), BulkString::from("v"));
+        backend.flush_db(false);
+        assert_eq!(backend.db_size(), 0);
+    }
+}
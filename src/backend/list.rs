@@ -0,0 +1,463 @@
+use std::collections::VecDeque;
+
+use crate::{Backend, BackendErr, Entry, RespFrame, Value};
+
+// End of a list to push to or pop from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEnd {
+    Left,
+    Right,
+}
+
+impl ListEnd {
+    fn push(self, list: &mut VecDeque<RespFrame>, value: RespFrame) {
+        match self {
+            ListEnd::Left => list.push_front(value),
+            ListEnd::Right => list.push_back(value),
+        }
+    }
+
+    fn pop(self, list: &mut VecDeque<RespFrame>) -> Option<RespFrame> {
+        match self {
+            ListEnd::Left => list.pop_front(),
+            ListEnd::Right => list.pop_back(),
+        }
+    }
+}
+
+// Options of lpos, rank picks the nth match and a negative one searches from the tail.
+// A count or max_len of 0 means all matches and the whole list respectively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LPosOpt {
+    pub rank: i64,
+    pub count: usize,
+    pub max_len: usize,
+}
+
+impl Default for LPosOpt {
+    fn default() -> Self {
+        LPosOpt {
+            rank: 1,
+            count: 1,
+            max_len: 0,
+        }
+    }
+}
+
+impl Backend {
+    // Push values to the head one after another, returns the length of the list.
+    pub fn lpush(&self, key: String, values: Vec<RespFrame>) -> Result<usize, BackendErr> {
+        self.push(key, values, ListEnd::Left)
+    }
+
+    pub fn rpush(&self, key: String, values: Vec<RespFrame>) -> Result<usize, BackendErr> {
+        self.push(key, values, ListEnd::Right)
+    }
+
+    // Push values only when the list exists, returns its length or 0 when it does not.
+    pub fn lpushx(&self, key: &str, values: Vec<RespFrame>) -> Result<usize, BackendErr> {
+        self.push_exists(key, values, ListEnd::Left)
+    }
+
+    pub fn rpushx(&self, key: &str, values: Vec<RespFrame>) -> Result<usize, BackendErr> {
+        self.push_exists(key, values, ListEnd::Right)
+    }
+
+    // Pop at most count values from the head, None when the key does not exist.
+    pub fn lpop(&self, key: &str, count: usize) -> Result<Option<Vec<RespFrame>>, BackendErr> {
+        self.pop(key, count, ListEnd::Left)
+    }
+
+    pub fn rpop(&self, key: &str, count: usize) -> Result<Option<Vec<RespFrame>>, BackendErr> {
+        self.pop(key, count, ListEnd::Right)
+    }
+
+    pub fn llen(&self, key: &str) -> Result<Option<usize>, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(Some(entry.as_list()?.len())),
+            None => Ok(None),
+        }
+    }
+
+    // Atomically pop a value from one end of src and push it to one end of dest,
+    // the same key rotates the list. Returns None when src does not exist.
+    pub fn lmove(
+        &self,
+        src: &str,
+        dest: &str,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> Result<Option<RespFrame>, BackendErr> {
+        let moved = self.move_value(src, dest, from, to)?;
+        if moved.is_some() && src != dest {
+            self.signal_ready(dest);
+        }
+        Ok(moved)
+    }
+
+    fn move_value(
+        &self,
+        src: &str,
+        dest: &str,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> Result<Option<RespFrame>, BackendErr> {
+        let mut guard = self.lock_keys([src, dest]);
+
+        match guard.get(src) {
+            Some(entry) => entry.as_list()?,
+            None => return Ok(None),
+        };
+        if let Some(entry) = guard.get(dest) {
+            entry.as_list()?;
+        }
+
+        let Some(entry) = guard.get_mut(src) else {
+            return Ok(None);
+        };
+        let list = entry.as_list_mut()?;
+        let Some(value) = from.pop(list) else {
+            return Ok(None);
+        };
+        if src == dest {
+            to.push(list, value.clone());
+            return Ok(Some(value));
+        }
+        if list.is_empty() {
+            guard.remove(src);
+        }
+
+        match guard.get_mut(dest) {
+            Some(entry) => to.push(entry.as_list_mut()?, value.clone()),
+            None => {
+                let list = VecDeque::from([value.clone()]);
+                guard.insert(dest.to_string(), Entry::new(Value::List(list)));
+            }
+        }
+        Ok(Some(value))
+    }
+
+    // Pop at most count values from the first non-empty list among the keys.
+    // Returns the key popped from together with its values, None when every list is empty.
+    pub fn lmpop(
+        &self,
+        keys: &[String],
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<(String, Vec<RespFrame>)>, BackendErr> {
+        for key in keys {
+            if let Some(values) = self.pop(key, count, end)? {
+                return Ok(Some((key.clone(), values)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn push(&self, key: String, values: Vec<RespFrame>, end: ListEnd) -> Result<usize, BackendErr> {
+        let mut entry = self.get_entry_or_insert(key.clone(), || Value::List(VecDeque::new()));
+        let list = entry.as_list_mut()?;
+        for value in values {
+            end.push(list, value);
+        }
+        let len = list.len();
+
+        drop(entry);
+        self.signal_ready(&key);
+        Ok(len)
+    }
+
+    fn push_exists(
+        &self,
+        key: &str,
+        values: Vec<RespFrame>,
+        end: ListEnd,
+    ) -> Result<usize, BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(0);
+        };
+
+        let list = entry.as_list_mut()?;
+        for value in values {
+            end.push(list, value);
+        }
+        let len = list.len();
+
+        drop(entry);
+        self.signal_ready(key);
+        Ok(len)
+    }
+
+    // The key is removed along with its last value.
+    fn pop(
+        &self,
+        key: &str,
+        count: usize,
+        end: ListEnd,
+    ) -> Result<Option<Vec<RespFrame>>, BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(None);
+        };
+
+        let list = entry.as_list_mut()?;
+        let values = (0..count.min(list.len()))
+            .filter_map(|_| end.pop(list))
+            .collect();
+        let empty = list.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(Some(values))
+    }
+
+    // Elements between the inclusive indexes, negative indexes count from the tail.
+    pub fn lrange(&self, key: &str, start: i64, stop: i64) -> Result<Vec<RespFrame>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(Vec::new());
+        };
+
+        let list = entry.as_list()?;
+        match resolve_range(start, stop, list.len()) {
+            Some((start, stop)) => Ok(list.range(start..=stop).cloned().collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn lindex(&self, key: &str, index: i64) -> Result<Option<RespFrame>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(None);
+        };
+
+        let list = entry.as_list()?;
+        Ok(resolve_index(index, list.len()).and_then(|index| list.get(index).cloned()))
+    }
+
+    pub fn lset(&self, key: &str, index: i64, value: RespFrame) -> Result<(), BackendErr> {
+        let mut entry = self.get_entry_mut(key).ok_or(BackendErr::NoSuchKey)?;
+
+        let list = entry.as_list_mut()?;
+        let index = resolve_index(index, list.len()).ok_or(BackendErr::IndexOutOfRange)?;
+        list[index] = value;
+        Ok(())
+    }
+
+    // Insert an element before or after the first occurrence of pivot.
+    // Returns the length of the list, Some(0) when the key does not exist
+    // and None when the pivot is not found.
+    pub fn linsert(
+        &self,
+        key: &str,
+        before: bool,
+        pivot: &RespFrame,
+        value: RespFrame,
+    ) -> Result<Option<usize>, BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(Some(0));
+        };
+
+        let list = entry.as_list_mut()?;
+        let Some(index) = list.iter().position(|item| item == pivot) else {
+            return Ok(None);
+        };
+
+        list.insert(if before { index } else { index + 1 }, value);
+        Ok(Some(list.len()))
+    }
+
+    // Remove the first count occurrences of an element from the head, from the tail
+    // for a negative count and all of them for 0. The key is removed along with its last element.
+    pub fn lrem(&self, key: &str, count: i64, value: &RespFrame) -> Result<usize, BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(0);
+        };
+
+        let list = entry.as_list_mut()?;
+        let limit = match count {
+            0 => usize::MAX,
+            count => count.unsigned_abs() as usize,
+        };
+        let mut matches = list
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| *item == value)
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        if count < 0 {
+            matches.reverse();
+        }
+        matches.truncate(limit);
+        matches.sort_unstable();
+
+        // remove from the tail so the indexes ahead stay valid
+        for index in matches.iter().rev() {
+            list.remove(*index);
+        }
+        let empty = list.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(matches.len())
+    }
+
+    // Keep only the elements between the inclusive indexes, an empty range deletes the key.
+    pub fn ltrim(&self, key: &str, start: i64, stop: i64) -> Result<(), BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(());
+        };
+
+        let list = entry.as_list_mut()?;
+        match resolve_range(start, stop, list.len()) {
+            Some((start, stop)) => {
+                list.truncate(stop + 1);
+                list.drain(..start);
+            }
+            None => list.clear(),
+        }
+        let empty = list.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(())
+    }
+
+    // Indexes of the matching elements, always counted from the head.
+    pub fn lpos(
+        &self,
+        key: &str,
+        value: &RespFrame,
+        opt: LPosOpt,
+    ) -> Result<Vec<usize>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(Vec::new());
+        };
+
+        let list = entry.as_list()?;
+        let max_len = match opt.max_len {
+            0 => list.len(),
+            max_len => max_len.min(list.len()),
+        };
+        let count = match opt.count {
+            0 => usize::MAX,
+            count => count,
+        };
+        let skip = (opt.rank.unsigned_abs() - 1) as usize;
+
+        let indexes: Box<dyn Iterator<Item = usize>> = if opt.rank > 0 {
+            Box::new(0..max_len)
+        } else {
+            Box::new((list.len() - max_len..list.len()).rev())
+        };
+        Ok(indexes
+            .filter(|index| list[*index] == *value)
+            .skip(skip)
+            .take(count)
+            .collect())
+    }
+}
+
+// Normalize an inclusive range with negative indexes into [0, len), None if it is empty.
+// Unlike string ranges, a stop before the head is not clamped to the first element.
+fn resolve_range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+    if start > stop || start >= len {
+        return None;
+    }
+
+    Some((start as usize, stop as usize))
+}
+
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    usize::try_from(index).ok().filter(|index| *index < len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BulkString;
+
+    fn rpush(backend: &Backend, key: &str, values: &[&str]) -> Result<(), BackendErr> {
+        for value in values {
+            backend.rpush(key.to_string(), vec![BulkString::from(*value).into()])?;
+        }
+        Ok(())
+    }
+
+    fn frames(values: &[&str]) -> Vec<RespFrame> {
+        values.iter().map(|v| BulkString::from(*v).into()).collect()
+    }
+
+    #[test]
+    fn test_resolve_range() {
+        assert_eq!(resolve_range(0, -1, 6), Some((0, 5)));
+        assert_eq!(resolve_range(-100, 100, 6), Some((0, 5)));
+        assert_eq!(resolve_range(0, -10, 6), None);
+        assert_eq!(resolve_range(6, 10, 6), None);
+        assert_eq!(resolve_range(0, -1, 0), None);
+        assert_eq!(resolve_range(i64::MIN, i64::MAX, 3), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_lrem_and_ltrim() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let a = RespFrame::from(BulkString::from("a"));
+        rpush(&backend, "l", &["a", "b", "a", "c", "a"])?;
+
+        assert_eq!(backend.lrem("l", -2, &a)?, 2);
+        assert_eq!(backend.lrange("l", 0, -1)?, frames(&["a", "b", "c"]));
+        assert_eq!(backend.lrem("l", 0, &a)?, 1);
+
+        backend.ltrim("l", 1, -1)?;
+        assert_eq!(backend.lrange("l", 0, -1)?, frames(&["c"]));
+        backend.ltrim("l", 5, 10)?;
+        assert_eq!(backend.exists(&["l".to_string()]), 0);
+
+        rpush(&backend, "l", &["a"])?;
+        assert_eq!(backend.lrem("l", 1, &a)?, 1);
+        assert_eq!(backend.exists(&["l".to_string()]), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpos() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let a = RespFrame::from(BulkString::from("a"));
+        rpush(&backend, "l", &["a", "b", "a", "c", "a"])?;
+
+        let lpos = |rank, count, max_len| {
+            backend.lpos(
+                "l",
+                &a,
+                LPosOpt {
+                    rank,
+                    count,
+                    max_len,
+                },
+            )
+        };
+        assert_eq!(lpos(1, 1, 0)?, [0]);
+        assert_eq!(lpos(2, 0, 0)?, [2, 4]);
+        assert_eq!(lpos(-1, 2, 0)?, [4, 2]);
+        assert_eq!(lpos(1, 0, 3)?, [0, 2]);
+        assert_eq!(lpos(-1, 0, 2)?, [4]);
+        assert!(lpos(4, 1, 0)?.is_empty());
+
+        Ok(())
+    }
+}
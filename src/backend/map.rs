@@ -0,0 +1,660 @@
+use dashmap::mapref::entry::Entry as MapEntry;
+
+use crate::{now_ms, Backend, BackendErr, BulkString, Entry, Value};
+
+// Max length of a string value, the same as redis proto-max-bulk-len default.
+pub const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+// Options of the set command.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SetOpt {
+    // only set the key if it does not already exist
+    pub nx: bool,
+    // only set the key if it already exists
+    pub xx: bool,
+    // return the old string stored at key
+    pub get: bool,
+    // retain the time to live associated with the key
+    pub keep_ttl: bool,
+    // absolute expire time in unix milliseconds
+    pub expire_at: Option<i64>,
+}
+
+impl Backend {
+    pub fn set(&self, key: String, value: BulkString) {
+        self.keyspace.insert(key, Entry::new(Value::String(value)));
+    }
+
+    // Set a string value with options atomically.
+    // Returns whether the value has been set together with the old value when GET is given.
+    pub fn set_opt(
+        &self,
+        key: String,
+        value: BulkString,
+        opt: SetOpt,
+    ) -> Result<(bool, Option<BulkString>), BackendErr> {
+        let now = now_ms();
+
+        match self.keyspace.entry(key) {
+            MapEntry::Occupied(mut occupied) => {
+                let live = !occupied.get().is_expired(now);
+                let old = if live && opt.get {
+                    Some(occupied.get().as_string()?.clone())
+                } else {
+                    None
+                };
+
+                if (opt.nx && live) || (opt.xx && !live) {
+                    return Ok((false, old));
+                }
+
+                let expire_at = if opt.keep_ttl && live {
+                    occupied.get().expire_at
+                } else {
+                    opt.expire_at
+                };
+                if let Some(at) = expire_at {
+                    self.register_expire(occupied.key(), at);
+                }
+                occupied.insert(Entry {
+                    value: Value::String(value),
+                    expire_at,
+                });
+
+                Ok((true, old))
+            }
+            MapEntry::Vacant(vacant) => {
+                if opt.xx {
+                    return Ok((false, None));
+                }
+
+                if let Some(at) = opt.expire_at {
+                    self.register_expire(vacant.key(), at);
+                }
+                vacant.insert(Entry {
+                    value: Value::String(value),
+                    expire_at: opt.expire_at,
+                });
+
+                Ok((true, None))
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<BulkString>, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(Some(entry.as_string()?.clone())),
+            None => Ok(None),
+        }
+    }
+
+    // Set multiple keys atomically, existing keys are overwritten and their ttl discarded.
+    pub fn mset(&self, pairs: Vec<(String, BulkString)>) {
+        let mut guard = self.lock_keys(pairs.iter().map(|(key, _)| key));
+        for (key, value) in pairs {
+            guard.insert(key, Entry::new(Value::String(value)));
+        }
+    }
+
+    // Set multiple keys atomically only when none of them exists.
+    pub fn mset_nx(&self, pairs: Vec<(String, BulkString)>) -> bool {
+        let mut guard = self.lock_keys(pairs.iter().map(|(key, _)| key));
+        if pairs.iter().any(|(key, _)| guard.contains_key(key)) {
+            return false;
+        }
+
+        for (key, value) in pairs {
+            guard.insert(key, Entry::new(Value::String(value)));
+        }
+        true
+    }
+
+    // Get the values of multiple keys atomically, keys not holding a string are treated as missing.
+    pub fn mget(&self, keys: &[String]) -> Vec<Option<BulkString>> {
+        let guard = self.lock_keys(keys);
+        keys.iter()
+            .map(|key| {
+                guard
+                    .get(key)
+                    .and_then(|entry| entry.as_string().ok().cloned())
+            })
+            .collect()
+    }
+
+    pub fn get_del(&self, key: &str) -> Result<Option<BulkString>, BackendErr> {
+        let mut guard = self.lock_keys([key]);
+        match guard.get(key) {
+            Some(entry) => {
+                let value = entry.as_string()?.clone();
+                guard.remove(key);
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Get the value of key and optionally update its expire time,
+    // `Some(None)` removes the ttl and `Some(Some(at))` sets it to the absolute time.
+    pub fn get_ex(
+        &self,
+        key: &str,
+        ttl: Option<Option<i64>>,
+    ) -> Result<Option<BulkString>, BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(None);
+        };
+        let value = entry.as_string()?.clone();
+
+        match ttl {
+            Some(Some(at)) if at <= now_ms() => {
+                drop(entry);
+                self.keyspace.remove(key);
+            }
+            Some(Some(at)) => {
+                entry.expire_at = Some(at);
+                self.register_expire(key, at);
+            }
+            Some(None) => entry.expire_at = None,
+            None => {}
+        }
+
+        Ok(Some(value))
+    }
+
+    // Append value to the string stored at key, returns the length after the append.
+    pub fn append(&self, key: String, value: &[u8]) -> Result<usize, BackendErr> {
+        let mut entry = self.get_entry_or_insert(key, || Value::String(BulkString::new([])));
+        let current = entry.as_string_mut()?;
+        if current.len() + value.len() > MAX_STRING_LEN {
+            return Err(BackendErr::StringTooLong);
+        }
+
+        current.0.extend_from_slice(value);
+        Ok(current.len())
+    }
+
+    pub fn strlen(&self, key: &str) -> Result<usize, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry.as_string()?.len()),
+            None => Ok(0),
+        }
+    }
+
+    // Get the substring between the inclusive offsets, negative offsets count from the end.
+    pub fn get_range(&self, key: &str, start: i64, end: i64) -> Result<BulkString, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(BulkString::new([]));
+        };
+        let value = entry.as_string()?;
+        let len = value.len() as i64;
+
+        if start < 0 && end < 0 && start > end {
+            return Ok(BulkString::new([]));
+        }
+
+        let start = if start < 0 {
+            (len + start).max(0)
+        } else {
+            start
+        };
+        let end = if end < 0 {
+            (len + end).max(0)
+        } else {
+            end.min(len - 1)
+        };
+        if start > end || len == 0 {
+            return Ok(BulkString::new([]));
+        }
+
+        Ok(BulkString::from(&value[start as usize..=end as usize]))
+    }
+
+    // Overwrite part of the string stored at key starting at offset, zero-padding if needed.
+    // Returns the length of the string after it was modified.
+    pub fn set_range(&self, key: String, offset: usize, value: &[u8]) -> Result<usize, BackendErr> {
+        if value.is_empty() {
+            return self.strlen(&key);
+        }
+        if offset + value.len() > MAX_STRING_LEN {
+            return Err(BackendErr::StringTooLong);
+        }
+
+        let mut entry = self.get_entry_or_insert(key, || Value::String(BulkString::new([])));
+        let current = &mut entry.as_string_mut()?.0;
+        if current.len() < offset + value.len() {
+            current.resize(offset + value.len(), 0);
+        }
+        current[offset..offset + value.len()].copy_from_slice(value);
+
+        Ok(current.len())
+    }
+
+    // Get the string values of multiple keys atomically, missing keys are treated as empty strings.
+    pub fn get_strings(&self, keys: &[&str]) -> Result<Vec<BulkString>, BackendErr> {
+        let guard = self.lock_keys(keys);
+        keys.iter()
+            .map(|key| match guard.get(key) {
+                Some(entry) => Ok(entry.as_string()?.clone()),
+                None => Ok(BulkString::new([])),
+            })
+            .collect()
+    }
+
+    // Increment the integer stored at key, a missing key is treated as 0.
+    pub fn incr_by(&self, key: String, delta: i64) -> Result<i64, BackendErr> {
+        let mut entry = self.get_entry_or_insert(key, || Value::String(BulkString::from("0")));
+        let value = entry.as_string_mut()?;
+
+        let current = parse_i64(value).ok_or(BackendErr::NotInteger)?;
+        let ret = current.checked_add(delta).ok_or(BackendErr::Overflow)?;
+
+        *value = BulkString::from(ret.to_string());
+        Ok(ret)
+    }
+
+    // Increment the float stored at key, a missing key is treated as 0.
+    pub fn incr_by_float(&self, key: String, delta: f64) -> Result<BulkString, BackendErr> {
+        // the key is only created or written once the result is known to be valid
+        match self.keyspace.entry(key) {
+            MapEntry::Occupied(mut occupied) if !occupied.get().is_expired(now_ms()) => {
+                let value = occupied.get_mut().as_string_mut()?;
+                let current = parse_f64(value).ok_or(BackendErr::NotFloat)?;
+                *value = incr_float(current, delta)?;
+                Ok(value.clone())
+            }
+            MapEntry::Occupied(mut occupied) => {
+                let ret = incr_float(0.0, delta)?;
+                occupied.insert(Entry::new(Value::String(ret.clone())));
+                Ok(ret)
+            }
+            MapEntry::Vacant(vacant) => {
+                let ret = incr_float(0.0, delta)?;
+                vacant.insert(Entry::new(Value::String(ret.clone())));
+                Ok(ret)
+            }
+        }
+    }
+}
+
+// Add a float increment, a result that is not finite is an error.
+pub(crate) fn incr_float(current: f64, delta: f64) -> Result<BulkString, BackendErr> {
+    let ret = current + delta;
+    if !ret.is_finite() {
+        return Err(BackendErr::NanOrInfinity);
+    }
+    Ok(BulkString::from(format_f64(ret)))
+}
+
+// Parse an integer the way redis does, no spaces, sign prefix or leading zeros are allowed.
+pub fn parse_i64(s: &[u8]) -> Option<i64> {
+    let digits = s.strip_prefix(b"-").unwrap_or(s);
+    match digits {
+        [b'0'] if digits.len() == s.len() => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => {
+            std::str::from_utf8(s).ok()?.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+pub fn parse_f64(s: &[u8]) -> Option<f64> {
+    if s.is_empty() || s.iter().any(u8::is_ascii_whitespace) {
+        return None;
+    }
+
+    let ret: f64 = std::str::from_utf8(s).ok()?.parse().ok()?;
+    (!ret.is_nan()).then_some(ret)
+}
+
+// Human readable float format as redis replies it: no exponent and no trailing zeros.
+// Redis prints 17 decimals of a long double, a double only holds 15 significant digits
+// exactly so it is rounded to them to hide the noise of binary fractions like 0.1 + 0.2.
+pub fn format_f64(value: f64) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+
+    // values below the 17th decimal round to 0
+    let value: f64 = format!("{:.17}", value).parse().unwrap_or(value);
+    let sci = format!("{:.14e}", value);
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+
+    let int_len = exp + 1;
+    let mut ret = if int_len <= 0 {
+        format!("0.{}{}", "0".repeat(-int_len as usize), digits)
+    } else if int_len as usize >= digits.len() {
+        format!("{}{}", digits, "0".repeat(int_len as usize - digits.len()))
+    } else {
+        let (int, frac) = digits.split_at(int_len as usize);
+        format!("{}.{}", int, frac)
+    };
+    if ret.contains('.') {
+        ret.truncate(ret.trim_end_matches('0').trim_end_matches('.').len());
+    }
+
+    match ret.as_str() {
+        "0" => ret,
+        _ => format!("{}{}", sign, ret),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespFrame;
+
+    #[test]
+    fn test_set_opt() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let nx = SetOpt {
+            nx: true,
+            get: true,
+            ..Default::default()
+        };
+
+        let ret = backend.set_opt("hello".to_string(), BulkString::from("world"), nx)?;
+        assert_eq!(ret, (true, None));
+
+        let ret = backend.set_opt("hello".to_string(), BulkString::from("foo"), nx)?;
+        assert_eq!(ret, (false, Some(BulkString::from("world"))));
+
+        let at = now_ms() + 10_000;
+        let xx = SetOpt {
+            xx: true,
+            expire_at: Some(at),
+            ..Default::default()
+        };
+        let ret = backend.set_opt("hello".to_string(), BulkString::from("bar"), xx)?;
+        assert_eq!(ret, (true, None));
+        assert_eq!(backend.expire_time("hello"), Some(Some(at)));
+
+        let keep_ttl = SetOpt {
+            keep_ttl: true,
+            ..Default::default()
+        };
+        backend.set_opt("hello".to_string(), BulkString::from("baz"), keep_ttl)?;
+        assert_eq!(backend.expire_time("hello"), Some(Some(at)));
+
+        backend.set_opt(
+            "hello".to_string(),
+            BulkString::from("baz"),
+            SetOpt::default(),
+        )?;
+        assert_eq!(backend.expire_time("hello"), Some(None));
+
+        let ret = backend.set_opt("missing".to_string(), BulkString::from("foo"), xx)?;
+        assert_eq!(ret, (false, None));
+        assert_eq!(backend.get("missing")?, None);
+
+        backend.lpush(
+            "list".to_string(),
+            vec![RespFrame::BulkString("foo".into())],
+        )?;
+        let get = SetOpt {
+            get: true,
+            ..Default::default()
+        };
+        let ret = backend.set_opt("list".to_string(), BulkString::from("foo"), get);
+        assert_eq!(ret, Err(BackendErr::WrongType));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let keys = (0..64)
+            .map(|i| format!("key{}", i))
+            .collect::<Vec<String>>();
+
+        backend.mset(
+            keys.iter()
+                .map(|key| (key.clone(), BulkString::from(key.as_str())))
+                .collect(),
+        );
+        let ret = backend.mget(&keys);
+        assert!(ret
+            .iter()
+            .zip(keys.iter())
+            .all(|(value, key)| value == &Some(BulkString::from(key.as_str()))));
+
+        let pairs = vec![
+            ("new".to_string(), BulkString::from("value")),
+            ("key0".to_string(), BulkString::from("value")),
+        ];
+        assert!(!backend.mset_nx(pairs));
+        assert_eq!(backend.get("new")?, None);
+
+        let pairs = vec![
+            ("new".to_string(), BulkString::from("value")),
+            ("other".to_string(), BulkString::from("value")),
+        ];
+        assert!(backend.mset_nx(pairs));
+        assert_eq!(backend.get("new")?, Some(BulkString::from("value")));
+
+        backend.lpush(
+            "list".to_string(),
+            vec![RespFrame::BulkString("foo".into())],
+        )?;
+        let ret = backend.mget(&["list".to_string(), "missing".to_string()]);
+        assert_eq!(ret, vec![None, None]);
+
+        assert_eq!(backend.get_del("list"), Err(BackendErr::WrongType));
+        assert_eq!(backend.get_del("new")?, Some(BulkString::from("value")));
+        assert_eq!(backend.get_del("new")?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_concurrent() {
+        let backend = Backend::new();
+        let keys = (0..32)
+            .map(|i| format!("key{}", i))
+            .collect::<Vec<String>>();
+
+        let handles = (0..8)
+            .map(|i| {
+                let (backend, keys) = (backend.clone(), keys.clone());
+                std::thread::spawn(move || {
+                    let pairs = keys
+                        .iter()
+                        .map(|key| (key.clone(), BulkString::from(i.to_string())))
+                        .collect();
+                    backend.mset_nx(pairs)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let succeeded = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|ret| *ret)
+            .count();
+        assert_eq!(succeeded, 1);
+
+        let values = backend.mget(&keys);
+        assert!(values.iter().all(|value| value == &values[0]));
+    }
+
+    #[test]
+    fn test_get_ex() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), BulkString::from("world"));
+
+        let at = now_ms() + 10_000;
+        assert_eq!(
+            backend.get_ex("hello", Some(Some(at)))?,
+            Some(BulkString::from("world"))
+        );
+        assert_eq!(backend.expire_time("hello"), Some(Some(at)));
+
+        backend.get_ex("hello", None)?;
+        assert_eq!(backend.expire_time("hello"), Some(Some(at)));
+
+        backend.get_ex("hello", Some(None))?;
+        assert_eq!(backend.expire_time("hello"), Some(None));
+
+        assert_eq!(
+            backend.get_ex("hello", Some(Some(1)))?,
+            Some(BulkString::from("world"))
+        );
+        assert_eq!(backend.get("hello")?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_by() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+
+        assert_eq!(backend.incr_by("counter".to_string(), 1)?, 1);
+        assert_eq!(backend.incr_by("counter".to_string(), -11)?, -10);
+        assert_eq!(backend.get("counter")?, Some(BulkString::from("-10")));
+
+        backend.set(
+            "counter".to_string(),
+            BulkString::from(i64::MAX.to_string()),
+        );
+        assert_eq!(
+            backend.incr_by("counter".to_string(), 1),
+            Err(BackendErr::Overflow)
+        );
+
+        backend.set("counter".to_string(), BulkString::from("012"));
+        assert_eq!(
+            backend.incr_by("counter".to_string(), 1),
+            Err(BackendErr::NotInteger)
+        );
+
+        backend.set("counter".to_string(), BulkString::from("10.50"));
+        assert_eq!(
+            backend.incr_by_float("counter".to_string(), 0.1)?,
+            BulkString::from("10.6")
+        );
+
+        backend.set("counter".to_string(), BulkString::from("5.0e3"));
+        assert_eq!(
+            backend.incr_by_float("counter".to_string(), 2.0e2)?,
+            BulkString::from("5200")
+        );
+
+        backend.set("counter".to_string(), BulkString::from("0.1"));
+        assert_eq!(
+            backend.incr_by_float("counter".to_string(), 0.2)?,
+            BulkString::from("0.3")
+        );
+
+        backend.set("counter".to_string(), BulkString::from("abc"));
+        assert_eq!(
+            backend.incr_by_float("counter".to_string(), 1.0),
+            Err(BackendErr::NotFloat)
+        );
+
+        backend.set("counter".to_string(), BulkString::from("1"));
+        assert_eq!(
+            backend.incr_by_float("counter".to_string(), f64::MAX),
+            Ok(BulkString::from(format_f64(f64::MAX)))
+        );
+        assert_eq!(
+            backend.incr_by_float("counter".to_string(), f64::MAX),
+            Err(BackendErr::NanOrInfinity)
+        );
+
+        // a failed increment does not create the key
+        assert_eq!(
+            backend.incr_by_float("missing".to_string(), f64::INFINITY),
+            Err(BackendErr::NanOrInfinity)
+        );
+        assert_eq!(backend.get("missing")?, None);
+        assert_eq!(
+            backend.incr_by_float("missing".to_string(), 1.5)?,
+            BulkString::from("1.5")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_range() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+
+        assert_eq!(backend.append("hello".to_string(), b"Hello")?, 5);
+        assert_eq!(backend.append("hello".to_string(), b" World")?, 11);
+        assert_eq!(backend.strlen("hello")?, 11);
+        assert_eq!(backend.strlen("missing")?, 0);
+
+        assert_eq!(backend.get_range("hello", 0, 3)?, BulkString::from("Hell"));
+        assert_eq!(backend.get_range("hello", -3, -1)?, BulkString::from("rld"));
+        assert_eq!(
+            backend.get_range("hello", 0, -1)?,
+            BulkString::from("Hello World")
+        );
+        assert_eq!(backend.get_range("hello", 10, 100)?, BulkString::from("d"));
+        assert_eq!(backend.get_range("hello", 5, 3)?, BulkString::new([]));
+        assert_eq!(backend.get_range("hello", -1, -5)?, BulkString::new([]));
+        assert_eq!(
+            backend.get_range("hello", -100, 2)?,
+            BulkString::from("Hel")
+        );
+        assert_eq!(backend.get_range("missing", 0, -1)?, BulkString::new([]));
+
+        assert_eq!(backend.set_range("hello".to_string(), 6, b"Redis")?, 11);
+        assert_eq!(backend.get("hello")?, Some(BulkString::from("Hello Redis")));
+
+        assert_eq!(backend.set_range("padded".to_string(), 3, b"abc")?, 6);
+        assert_eq!(backend.get("padded")?, Some(BulkString::from(b"\0\0\0abc")));
+
+        assert_eq!(backend.set_range("missing".to_string(), 3, b"")?, 0);
+        assert_eq!(backend.get("missing")?, None);
+        assert_eq!(
+            backend.set_range("missing".to_string(), MAX_STRING_LEN, b"a"),
+            Err(BackendErr::StringTooLong)
+        );
+
+        backend.lpush(
+            "list".to_string(),
+            vec![RespFrame::BulkString("foo".into())],
+        )?;
+        assert_eq!(backend.strlen("list"), Err(BackendErr::WrongType));
+        assert_eq!(
+            backend.get_strings(&["hello", "list"]),
+            Err(BackendErr::WrongType)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_f64() {
+        assert_eq!(format_f64(0.1 + 0.2), "0.3");
+        assert_eq!(format_f64(3.0e3), "3000");
+        assert_eq!(format_f64(10.5 + 0.1), "10.6");
+        assert_eq!(format_f64(-1.25), "-1.25");
+        assert_eq!(format_f64(1e-20), "0");
+        assert_eq!(format_f64(-1e-20), "0");
+        assert_eq!(format_f64(1.5e-17), "0.00000000000000002");
+        assert_eq!(format_f64(1e20), "100000000000000000000");
+        assert_eq!(format_f64(f64::INFINITY), "inf");
+    }
+
+    #[test]
+    fn test_parse_i64() {
+        assert_eq!(parse_i64(b"0"), Some(0));
+        assert_eq!(parse_i64(b"-123"), Some(-123));
+        assert_eq!(parse_i64(b"9223372036854775807"), Some(i64::MAX));
+        assert_eq!(parse_i64(b"-9223372036854775808"), Some(i64::MIN));
+        assert_eq!(parse_i64(b"9223372036854775808"), None);
+        assert_eq!(parse_i64(b"-0"), None);
+        assert_eq!(parse_i64(b"+1"), None);
+        assert_eq!(parse_i64(b"01"), None);
+        assert_eq!(parse_i64(b" 1"), None);
+        assert_eq!(parse_i64(b""), None);
+    }
+}
@@ -1,19 +1,107 @@
-use std::collections::VecDeque;
+mod bitfield;
+mod bitmap;
+mod blocking;
+mod db;
+mod expire;
+mod glob;
+mod guard;
+mod hash_expire;
+mod hash_map;
+mod keyspace;
+mod lazy_free;
+mod list;
+mod map;
+mod scan;
+mod set;
+mod skiplist;
+mod sorted_set;
+mod value;
+
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeSet;
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
+use dashmap::mapref::one::{Ref, RefMut};
 use dashmap::DashMap;
+use thiserror::Error;
+
+use crate::{RespFrame, SimpleError};
+
+pub use bitfield::{BitFieldOp, BitFieldType, Overflow};
+pub use bitmap::{BitOperator, BitUnit};
+pub use blocking::ServeFn;
+pub use db::DEFAULT_DATABASES;
+pub use expire::{now_ms, ExpireOpt};
+pub use glob::glob_match;
+pub use hash_expire::{FieldCond, FieldExpire};
+pub use list::{LPosOpt, ListEnd};
+pub use map::{format_f64, parse_f64, parse_i64, SetOpt, MAX_STRING_LEN};
+pub use scan::ScanOpt;
+pub use set::SetOperator;
+pub use skiplist::SortedSet;
+pub use sorted_set::{LexBound, ZAddOpt, ZRangeBy, ZRangeOpt};
+pub use value::{Entry, HashField, HashValue, Value};
+
+// Protocol version a connection speaks until it switches with hello.
+pub const DEFAULT_PROTOCOL: u8 = 2;
+
+// A handle on the shared databases, bound to the one it currently works on.
+// Every connection owns its handle, so selecting a database only affects that connection.
+#[derive(Debug)]
+pub struct Backend {
+    inner: Arc<BackendInner>,
+    db: AtomicUsize,
+    // RESP version negotiated by the connection, decides how aggregate replies are shaped.
+    protocol: AtomicU8,
+}
 
-use crate::RespFrame;
+#[derive(Debug)]
+pub struct BackendInner {
+    dbs: Vec<Db>,
+    lazy_free: lazy_free::LazyFree,
+    blocking: blocking::Blocking,
+}
 
-#[derive(Debug, Clone)]
-pub struct Backend(Arc<BackendInner>);
+#[derive(Debug)]
+pub struct Db {
+    pub(crate) keyspace: DashMap<String, Entry>,
+    // (expire_at, key) of every key that has been given a ttl, ordered by deadline.
+    // Records may be stale, the entry in keyspace is always the source of truth.
+    pub(crate) expires: Mutex<BTreeSet<(i64, String)>>,
+    // (expire_at, key) of hashes holding fields with a ttl, stale the same way as expires.
+    pub(crate) field_expires: Mutex<BTreeSet<(i64, String)>>,
+}
 
-#[derive(Debug, Default)]
-pub struct BackendInner {
-    pub(crate) map: DashMap<String, RespFrame>,
-    pub(crate) hash_map: DashMap<String, DashMap<String, RespFrame>>,
-    pub(crate) list_map: DashMap<String, VecDeque<RespFrame>>,
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BackendErr {
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR value is not a valid float")]
+    NotFloat,
+    #[error("ERR hash value is not an integer")]
+    HashNotInteger,
+    #[error("ERR hash value is not a float")]
+    HashNotFloat,
+    #[error("ERR increment or decrement would overflow")]
+    Overflow,
+    #[error("ERR increment would produce NaN or Infinity")]
+    NanOrInfinity,
+    #[error("ERR string exceeds maximum allowed size (proto-max-bulk-len)")]
+    StringTooLong,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR index out of range")]
+    IndexOutOfRange,
+    #[error("ERR DB index is out of range")]
+    DbIndexOutOfRange,
+    #[error("ERR source and destination objects are the same")]
+    SameObject,
+    #[error("ERR resulting score is not a number (NaN)")]
+    ScoreNaN,
 }
 
 impl Backend {
@@ -21,62 +109,133 @@ impl Backend {
         Self::default()
     }
 
-    pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+    pub fn with_databases(databases: usize) -> Self {
+        // every keyspace shares one hasher so swapdb can exchange their shards as they are
+        let hasher = RandomState::new();
+        let dbs = (0..databases.max(1))
+            .map(|_| Db {
+                keyspace: DashMap::with_hasher(hasher.clone()),
+                expires: Mutex::default(),
+                field_expires: Mutex::default(),
+            })
+            .collect();
+
+        Self {
+            inner: Arc::new(BackendInner {
+                dbs,
+                lazy_free: Default::default(),
+                blocking: Default::default(),
+            }),
+            db: AtomicUsize::new(0),
+            protocol: AtomicU8::new(DEFAULT_PROTOCOL),
+        }
     }
 
-    pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+    pub fn protocol(&self) -> u8 {
+        self.protocol.load(Ordering::Relaxed)
     }
 
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let map = self.hash_map.entry(key).or_default();
-        map.insert(field, value);
+    pub fn set_protocol(&self, protocol: u8) {
+        self.protocol.store(protocol, Ordering::Relaxed);
     }
 
-    pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hash_map
-            .get(key)
-            .and_then(|ret| ret.get(field).map(|v| v.value().clone()))
+    // Get the entry of a key, deleting it first if it has already expired.
+    // Expired fields of a hash are reaped, the key is deleted along with its last field.
+    pub(crate) fn get_entry(&self, key: &str) -> Option<Ref<'_, String, Entry>> {
+        let now = now_ms();
+        let entry = self.keyspace.get(key)?;
+        if !entry.is_expired(now) && !entry.reap_fields(now) {
+            return Some(entry);
+        }
+
+        drop(entry);
+        self.keyspace.remove_if(key, |_, entry| {
+            entry.is_expired(now) || entry.is_empty_collection()
+        });
+        None
     }
 
-    pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hash_map.get(key).map(|v| v.clone())
+    pub(crate) fn get_entry_mut(&self, key: &str) -> Option<RefMut<'_, String, Entry>> {
+        let now = now_ms();
+        let entry = self.keyspace.get_mut(key)?;
+        if !entry.is_expired(now) && !entry.reap_fields(now) {
+            return Some(entry);
+        }
+
+        drop(entry);
+        self.keyspace.remove_if(key, |_, entry| {
+            entry.is_expired(now) || entry.is_empty_collection()
+        });
+        None
     }
 
-    pub fn lpush(&self, key: String, value: RespFrame) {
-        self.list_map.entry(key).or_default().push_front(value);
+    // Get the entry of a key, creating it with the default value when the key
+    // does not exist or has already expired.
+    pub(crate) fn get_entry_or_insert(
+        &self,
+        key: String,
+        default: impl Fn() -> Value,
+    ) -> RefMut<'_, String, Entry> {
+        let mut entry = self
+            .keyspace
+            .entry(key)
+            .or_insert_with(|| Entry::new(default()));
+
+        let now = now_ms();
+        if entry.is_expired(now) {
+            *entry = Entry::new(default());
+        }
+        entry.reap_fields(now);
+        entry
     }
 
-    pub fn lpop(&self, key: &str) -> Option<RespFrame> {
-        let mut ret = self.list_map.get_mut(key)?;
-        ret.value_mut().pop_front()
+    // Remove a key whose collection has become empty, an empty collection is never kept.
+    // Entry guards on the key must be dropped before.
+    pub(crate) fn remove_if_empty(&self, key: &str) {
+        self.keyspace
+            .remove_if(key, |_, entry| entry.is_empty_collection());
     }
+}
 
-    pub fn rpush(&self, key: String, value: RespFrame) {
-        self.list_map.entry(key).or_default().push_back(value);
+impl Db {
+    pub(crate) fn expires(&self) -> MutexGuard<'_, BTreeSet<(i64, String)>> {
+        self.expires.lock().unwrap_or_else(PoisonError::into_inner)
     }
 
-    pub fn rpop(&self, key: &str) -> Option<RespFrame> {
-        let mut ret = self.list_map.get_mut(key)?;
-        ret.value_mut().pop_back()
+    pub(crate) fn field_expires(&self) -> MutexGuard<'_, BTreeSet<(i64, String)>> {
+        self.field_expires
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
     }
+}
 
-    pub fn llen(&self, key: &str) -> Option<usize> {
-        self.list_map.get(key).map(|ret| ret.len())
+impl From<BackendErr> for RespFrame {
+    fn from(value: BackendErr) -> Self {
+        SimpleError::new(value.to_string()).into()
     }
 }
 
+// Commands work on the database the handle is bound to.
 impl Deref for Backend {
-    type Target = BackendInner;
+    type Target = Db;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.inner.dbs[self.db.load(Ordering::Relaxed)]
+    }
+}
+
+impl Clone for Backend {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            db: AtomicUsize::new(self.db()),
+            protocol: AtomicU8::new(self.protocol()),
+        }
     }
 }
 
 impl Default for Backend {
     fn default() -> Self {
-        Self(Arc::new(BackendInner::default()))
+        Self::with_databases(DEFAULT_DATABASES)
     }
 }
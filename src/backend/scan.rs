@@ -0,0 +1,241 @@
+use std::hash::BuildHasher;
+
+use dashmap::DashMap;
+
+use crate::{glob_match, now_ms, Backend, BackendErr, RespFrame};
+
+// Options of the scan family, keys failing a filter still count against `count`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanOpt {
+    pub pattern: Option<Vec<u8>>,
+    pub count: usize,
+    pub ty: Option<String>,
+}
+
+impl Default for ScanOpt {
+    fn default() -> Self {
+        ScanOpt {
+            pattern: None,
+            count: 10,
+            ty: None,
+        }
+    }
+}
+
+impl ScanOpt {
+    fn matches(&self, key: &str) -> bool {
+        self.pattern
+            .as_ref()
+            .is_none_or(|pattern| glob_match(pattern, key.as_bytes()))
+    }
+}
+
+impl Backend {
+    pub fn keys(&self, pattern: &[u8]) -> Vec<String> {
+        let now = now_ms();
+
+        self.keyspace
+            .iter()
+            .filter(|item| !item.value().is_expired(now))
+            .filter(|item| glob_match(pattern, item.key().as_bytes()))
+            .map(|item| item.key().clone())
+            .collect()
+    }
+
+    // Scan the keyspace from cursor, returns the next cursor which is 0 once the scan is done.
+    pub fn scan(&self, cursor: u64, opt: &ScanOpt) -> (u64, Vec<String>) {
+        let now = now_ms();
+
+        let mut keys = Vec::new();
+        let cursor = scan_map(&self.keyspace, cursor, opt.count, |key, entry| {
+            let matched = !entry.is_expired(now)
+                && opt.ty.as_deref().is_none_or(|ty| ty == entry.type_name())
+                && opt.matches(key);
+            if matched {
+                keys.push(key.clone());
+            }
+        });
+
+        (cursor, keys)
+    }
+
+    // Scan the fields of a hash, a missing key is an empty hash.
+    pub fn hscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        opt: &ScanOpt,
+    ) -> Result<(u64, Vec<(String, RespFrame)>), BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok((0, Vec::new()));
+        };
+
+        let mut fields = Vec::new();
+        let cursor = scan_map(entry.as_hash()?, cursor, opt.count, |field, value| {
+            if opt.matches(field) {
+                fields.push((field.clone(), value.value.clone()));
+            }
+        });
+
+        Ok((cursor, fields))
+    }
+}
+
+// Visit about `count` entries of the map in a fixed order, starting from cursor.
+//
+// Entries are ordered by their hash rotated so that the bits dashmap picks the shard with come
+// first, which makes the rotated hash both the position of a shard and the position inside it.
+// Neither moves when a shard resizes, so the cursor needs no state on the server and every entry
+// present during the whole scan is visited at least once.
+//
+// Shards keep no order, so every shard a call visits is read whole, but only the entries it
+// still visits are sorted: a call costs O(shard size + count log count).
+pub(crate) fn scan_map<V>(
+    map: &DashMap<String, V>,
+    cursor: u64,
+    count: usize,
+    mut visit: impl FnMut(&String, &V),
+) -> u64 {
+    let position = |key: &String| map.hasher().hash_one(key).rotate_left(7);
+    let shards = map.shards();
+
+    let count = count.max(1);
+    let mut visited = 0;
+    let mut last = None;
+    let start = map.determine_shard(cursor.rotate_right(7) as usize);
+    for (i, shard) in shards.iter().enumerate().skip(start) {
+        let shard = shard.read();
+        // safety: buckets are only read while the shard lock is held
+        let mut items = unsafe { shard.iter().map(|bucket| bucket.as_ref()) }
+            .map(|(key, value)| (position(key), key, value.get()))
+            // only the shard the cursor points into is partially scanned
+            .filter(|(pos, _, _)| i > start || *pos >= cursor)
+            .collect::<Vec<_>>();
+
+        // keep the entries with the lowest positions this call still visits, in linear time
+        let remaining = count - visited;
+        if items.len() > remaining {
+            items.select_nth_unstable_by_key(remaining - 1, |(pos, _, _)| *pos);
+            let cutoff = items[remaining - 1].0;
+            // entries sharing a position are visited together, the next cursor skips them all
+            let mut kept = remaining;
+            for j in remaining..items.len() {
+                if items[j].0 == cutoff {
+                    items.swap(kept, j);
+                    kept += 1;
+                }
+            }
+            items.truncate(kept);
+        }
+        items.sort_unstable_by_key(|(pos, _, _)| *pos);
+
+        for (pos, key, value) in items {
+            visit(key, value);
+            visited += 1;
+            last = Some(pos);
+        }
+        if visited >= count {
+            return last.map_or(0, |last: u64| last.wrapping_add(1));
+        }
+    }
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::BulkString;
+
+    #[test]
+    fn test_scan() {
+        let backend = Backend::new();
+        for i in 0..1000 {
+            backend.set(format!("key:{}", i), BulkString::from("v"));
+        }
+        backend
+            .lpush("list".to_string(), vec![BulkString::from("v").into()])
+            .unwrap();
+
+        let opt = ScanOpt {
+            pattern: Some(b"key:*".to_vec()),
+            count: 17,
+            ty: Some("string".to_string()),
+        };
+        let mut keys = HashSet::new();
+        let mut cursor = 0;
+        loop {
+            let (next, batch) = backend.scan(cursor, &opt);
+            keys.extend(batch);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        assert_eq!(keys.len(), 1000);
+        assert!(!keys.contains("list"));
+    }
+
+    #[test]
+    fn test_scan_while_resizing() {
+        let map = DashMap::<String, usize>::new();
+        for i in 0..100 {
+            map.insert(format!("old:{}", i), i);
+        }
+
+        let mut seen = HashSet::new();
+        let mut cursor = 0;
+        let mut round = 0;
+        loop {
+            cursor = scan_map(&map, cursor, 5, |key, _| {
+                seen.insert(key.clone());
+            });
+            // grow and shrink the map between calls so the shards resize
+            for i in 0..200 {
+                map.insert(format!("new:{}:{}", round, i), i);
+            }
+            if round % 2 == 1 {
+                map.retain(|key, _| key.starts_with("old:"));
+                map.shrink_to_fit();
+            }
+            round += 1;
+
+            if cursor == 0 {
+                break;
+            }
+        }
+
+        assert!((0..100).all(|i| seen.contains(&format!("old:{}", i))));
+    }
+
+    #[test]
+    fn test_scan_large_keyspace() {
+        // enough shards for a call of the larger count to span several of them
+        let map = DashMap::<String, usize>::with_shard_amount(64);
+        for i in 0..20_000 {
+            map.insert(format!("key:{}", i), i);
+        }
+        assert!(map.len() / map.shards().len() < 1000);
+
+        for count in [7, 1000] {
+            let mut seen = HashSet::new();
+            let mut cursor = 0;
+            loop {
+                let mut batch = 0;
+                cursor = scan_map(&map, cursor, count, |key, _| {
+                    assert!(seen.insert(key.clone()), "{} visited twice", key);
+                    batch += 1;
+                });
+                if cursor == 0 {
+                    break;
+                }
+                // every call but the last visits at least count entries, across shards if needed
+                assert!(batch >= count);
+            }
+            assert_eq!(seen.len(), map.len());
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use std::collections::HashSet;
+
+use rand::seq::IteratorRandom;
+use rand::Rng;
+
+use crate::{Backend, BackendErr, Entry, Value};
+
+// Operations between sets of the set algebra commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperator {
+    Inter,
+    Union,
+    // members of the first set in none of the others
+    Diff,
+}
+
+impl Backend {
+    // Add members to a set, returns the number of members that were not there before.
+    pub fn sadd(&self, key: String, members: Vec<String>) -> Result<usize, BackendErr> {
+        let mut entry = self.get_entry_or_insert(key, || Value::Set(HashSet::new()));
+        let set = entry.as_set_mut()?;
+        Ok(members
+            .into_iter()
+            .filter(|member| set.insert(member.clone()))
+            .count())
+    }
+
+    // Remove members from a set, the key is removed together with its last member.
+    pub fn srem(&self, key: &str, members: &[String]) -> Result<usize, BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(0);
+        };
+
+        let set = entry.as_set_mut()?;
+        let removed = members.iter().filter(|member| set.remove(*member)).count();
+        let empty = set.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(removed)
+    }
+
+    pub fn smembers(&self, key: &str) -> Result<Vec<String>, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry.as_set()?.iter().cloned().collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn sismember(&self, key: &str, member: &str) -> Result<bool, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry.as_set()?.contains(member)),
+            None => Ok(false),
+        }
+    }
+
+    pub fn smismember(&self, key: &str, members: &[String]) -> Result<Vec<bool>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(vec![false; members.len()]);
+        };
+
+        let set = entry.as_set()?;
+        Ok(members.iter().map(|member| set.contains(member)).collect())
+    }
+
+    pub fn scard(&self, key: &str) -> Result<usize, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry.as_set()?.len()),
+            None => Ok(0),
+        }
+    }
+
+    // Remove and return at most count random members, the key is removed once it is empty.
+    pub fn spop(&self, key: &str, count: usize) -> Result<Vec<String>, BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(Vec::new());
+        };
+
+        let set = entry.as_set_mut()?;
+        let popped = if count >= set.len() {
+            set.drain().collect()
+        } else {
+            // only the chosen members are cloned, before they are removed
+            let popped = set
+                .iter()
+                .choose_multiple(&mut rand::thread_rng(), count)
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            for member in popped.iter() {
+                set.remove(member);
+            }
+            popped
+        };
+        let empty = set.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(popped)
+    }
+
+    // Random members of a set, distinct ones for a positive count
+    // and possibly repeated ones for a negative count.
+    pub fn srandmember(&self, key: &str, count: i64) -> Result<Vec<String>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(Vec::new());
+        };
+
+        let set = entry.as_set()?;
+        let mut rng = rand::thread_rng();
+        if count >= 0 {
+            // the sample is allocated upfront, it can not hold more than the whole set
+            let count = (count as usize).min(set.len());
+            let members = set.iter().choose_multiple(&mut rng, count);
+            return Ok(members.into_iter().cloned().collect());
+        }
+
+        let members = set.iter().collect::<Vec<_>>();
+        if members.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok((0..count.unsigned_abs())
+            .map(|_| members[rng.gen_range(0..members.len())].clone())
+            .collect())
+    }
+
+    // Move a member from one set to another, returns whether it was moved.
+    pub fn smove(&self, src: &str, dest: &str, member: &str) -> Result<bool, BackendErr> {
+        let mut guard = self.lock_keys([src, dest]);
+
+        let Some(entry) = guard.get(src) else {
+            return Ok(false);
+        };
+        let found = entry.as_set()?.contains(member);
+        if let Some(entry) = guard.get(dest) {
+            entry.as_set()?;
+        }
+        if !found || src == dest {
+            return Ok(found);
+        }
+
+        if let Some(entry) = guard.get_mut(src) {
+            let set = entry.as_set_mut()?;
+            set.remove(member);
+            if set.is_empty() {
+                guard.remove(src);
+            }
+        }
+        match guard.get_mut(dest) {
+            Some(entry) => {
+                entry.as_set_mut()?.insert(member.to_string());
+            }
+            None => {
+                let set = HashSet::from([member.to_string()]);
+                guard.insert(dest.to_string(), Entry::new(Value::Set(set)));
+            }
+        }
+        Ok(true)
+    }
+
+    // Members of the result of an operation between the sets, missing keys are empty sets.
+    pub fn set_op(&self, op: SetOperator, keys: &[String]) -> Result<Vec<String>, BackendErr> {
+        let guard = self.lock_keys(keys);
+
+        let sets = keys
+            .iter()
+            .map(|key| guard.get(key).map(|entry| entry.as_set()).transpose())
+            .collect::<Result<Vec<_>, BackendErr>>()?;
+        Ok(set_op(op, &sets).into_iter().cloned().collect())
+    }
+
+    // Store the result of an operation between the sets in dest, replacing whatever it held.
+    // Returns the size of the result, an empty result deletes dest.
+    pub fn set_op_store(
+        &self,
+        op: SetOperator,
+        dest: String,
+        keys: &[String],
+    ) -> Result<usize, BackendErr> {
+        let mut guard = self.lock_keys(keys.iter().chain([&dest]));
+
+        let sets = keys
+            .iter()
+            .map(|key| guard.get(key).map(|entry| entry.as_set()).transpose())
+            .collect::<Result<Vec<_>, BackendErr>>()?;
+        let ret = set_op(op, &sets)
+            .into_iter()
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        let len = ret.len();
+        if ret.is_empty() {
+            guard.remove(&dest);
+        } else {
+            guard.insert(dest, Entry::new(Value::Set(ret)));
+        }
+        Ok(len)
+    }
+
+    // Size of the intersection of the sets, counting stops once it reaches a non-zero limit.
+    pub fn sintercard(&self, keys: &[String], limit: usize) -> Result<usize, BackendErr> {
+        let guard = self.lock_keys(keys);
+
+        let sets = keys
+            .iter()
+            .map(|key| guard.get(key).map(|entry| entry.as_set()).transpose())
+            .collect::<Result<Vec<_>, BackendErr>>()?;
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        Ok(intersection(&sets).take(limit).count())
+    }
+}
+
+fn set_op<'a>(op: SetOperator, sets: &[Option<&'a HashSet<String>>]) -> Vec<&'a String> {
+    match op {
+        SetOperator::Inter => intersection(sets).collect(),
+        SetOperator::Union => sets
+            .iter()
+            .flatten()
+            .flat_map(|set| set.iter())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect(),
+        SetOperator::Diff => {
+            let Some((Some(first), rest)) = sets.split_first() else {
+                return Vec::new();
+            };
+            first
+                .iter()
+                .filter(|member| rest.iter().flatten().all(|set| !set.contains(*member)))
+                .collect()
+        }
+    }
+}
+
+// Members in every set, only the smallest set is iterated and the others are probed.
+fn intersection<'a>(sets: &[Option<&'a HashSet<String>>]) -> impl Iterator<Item = &'a String> + 'a {
+    // a missing key makes the intersection empty
+    let mut sets = sets
+        .iter()
+        .copied()
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default();
+    sets.sort_by_key(|set| set.len());
+
+    let smallest = sets.first().copied();
+    smallest
+        .into_iter()
+        .flatten()
+        .filter(move |member| sets[1..].iter().all(|set| set.contains(*member)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(members: &[&str]) -> Vec<String> {
+        members.iter().map(|m| m.to_string()).collect()
+    }
+
+    fn sorted(mut members: Vec<String>) -> Vec<String> {
+        members.sort();
+        members
+    }
+
+    #[test]
+    fn test_sadd_srem() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+
+        assert_eq!(backend.sadd("s".to_string(), members(&["a", "b", "a"]))?, 2);
+        assert_eq!(backend.sadd("s".to_string(), members(&["b", "c"]))?, 1);
+        assert_eq!(sorted(backend.smembers("s")?), ["a", "b", "c"]);
+        assert_eq!(backend.scard("s")?, 3);
+        assert_eq!(
+            backend.smismember("s", &members(&["a", "x"]))?,
+            [true, false]
+        );
+
+        assert_eq!(backend.srem("s", &members(&["a", "x"]))?, 1);
+        assert_eq!(backend.srem("s", &members(&["b", "c"]))?, 2);
+        assert_eq!(backend.exists(&["s".to_string()]), 0);
+        assert_eq!(backend.srem("s", &members(&["a"]))?, 0);
+
+        backend.lpush("l".to_string(), vec![crate::BulkString::from("v").into()])?;
+        assert_eq!(
+            backend.sadd("l".to_string(), members(&["a"])),
+            Err(BackendErr::WrongType)
+        );
+        assert_eq!(backend.sismember("l", "a"), Err(BackendErr::WrongType));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spop_srandmember() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        backend.sadd("s".to_string(), members(&["a", "b", "c"]))?;
+
+        assert_eq!(sorted(backend.srandmember("s", 5)?), ["a", "b", "c"]);
+        assert_eq!(backend.srandmember("s", 2)?.len(), 2);
+        assert_eq!(backend.srandmember("s", i64::MAX)?.len(), 3);
+        let repeated = backend.srandmember("s", -10)?;
+        assert_eq!(repeated.len(), 10);
+        assert!(repeated
+            .iter()
+            .all(|m| ["a", "b", "c"].contains(&m.as_str())));
+        assert!(backend.srandmember("missing", -3)?.is_empty());
+
+        let popped = backend.spop("s", 2)?;
+        assert_eq!(popped.len(), 2);
+        assert_eq!(backend.scard("s")?, 1);
+        assert!(popped.iter().all(|m| !backend.sismember("s", m).unwrap()));
+        assert_eq!(backend.spop("s", 5)?.len(), 1);
+        assert_eq!(backend.exists(&["s".to_string()]), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_srandmember_uniform() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        backend.sadd("s".to_string(), members(&["a", "b", "c", "d"]))?;
+
+        let mut hits = [0; 4];
+        for member in backend.srandmember("s", -4000)? {
+            hits[(member.as_bytes()[0] - b'a') as usize] += 1;
+        }
+        // 1000 expected each, far beyond any plausible deviation
+        assert!(
+            hits.iter().all(|hit| (700..1300).contains(hit)),
+            "{:?}",
+            hits
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_op() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        backend.sadd("a".to_string(), members(&["1", "2", "3", "4"]))?;
+        backend.sadd("b".to_string(), members(&["2", "3", "5"]))?;
+        backend.sadd("c".to_string(), members(&["3", "4", "6"]))?;
+
+        let keys = members(&["a", "b", "c"]);
+        assert_eq!(sorted(backend.set_op(SetOperator::Inter, &keys)?), ["3"]);
+        assert_eq!(
+            sorted(backend.set_op(SetOperator::Union, &keys)?),
+            ["1", "2", "3", "4", "5", "6"]
+        );
+        assert_eq!(sorted(backend.set_op(SetOperator::Diff, &keys)?), ["1"]);
+
+        let keys = members(&["a", "missing"]);
+        assert!(backend.set_op(SetOperator::Inter, &keys)?.is_empty());
+        assert_eq!(backend.set_op(SetOperator::Diff, &keys)?.len(), 4);
+        let keys = members(&["missing", "a"]);
+        assert!(backend.set_op(SetOperator::Diff, &keys)?.is_empty());
+        assert_eq!(backend.set_op(SetOperator::Union, &keys)?.len(), 4);
+
+        backend.set("string".to_string(), crate::BulkString::from("v"));
+        let keys = members(&["missing", "string"]);
+        assert_eq!(
+            backend.set_op(SetOperator::Inter, &keys),
+            Err(BackendErr::WrongType)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_op_store() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        backend.sadd("a".to_string(), members(&["1", "2", "3"]))?;
+        backend.sadd("b".to_string(), members(&["2", "3", "4"]))?;
+        backend.set("dest".to_string(), crate::BulkString::from("v"));
+
+        let keys = members(&["a", "b"]);
+        let len = backend.set_op_store(SetOperator::Inter, "dest".to_string(), &keys)?;
+        assert_eq!(len, 2);
+        assert_eq!(sorted(backend.smembers("dest")?), ["2", "3"]);
+
+        // the destination may be one of the sources
+        let keys = members(&["dest", "a"]);
+        let len = backend.set_op_store(SetOperator::Diff, "dest".to_string(), &keys)?;
+        assert_eq!(len, 0);
+        assert_eq!(backend.exists(&["dest".to_string()]), 0);
+
+        let keys = members(&["a", "b"]);
+        let len = backend.set_op_store(SetOperator::Union, "a".to_string(), &keys)?;
+        assert_eq!(len, 4);
+        assert_eq!(sorted(backend.smembers("a")?), ["1", "2", "3", "4"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sintercard() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        backend.sadd("a".to_string(), members(&["1", "2", "3", "4"]))?;
+        backend.sadd("b".to_string(), members(&["1", "2", "3", "5"]))?;
+
+        let keys = members(&["a", "b"]);
+        assert_eq!(backend.sintercard(&keys, 0)?, 3);
+        assert_eq!(backend.sintercard(&keys, 2)?, 2);
+        assert_eq!(backend.sintercard(&keys, 10)?, 3);
+        assert_eq!(backend.sintercard(&members(&["a", "missing"]), 0)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_smove() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        backend.sadd("src".to_string(), members(&["a", "b"]))?;
+
+        assert!(backend.smove("src", "dest", "a")?);
+        assert!(!backend.smove("src", "dest", "x")?);
+        assert!(backend.smove("src", "src", "b")?);
+        assert!(backend.smove("src", "dest", "b")?);
+        assert_eq!(backend.exists(&["src".to_string()]), 0);
+        assert_eq!(sorted(backend.smembers("dest")?), ["a", "b"]);
+        assert!(!backend.smove("missing", "dest", "a")?);
+
+        backend.set("string".to_string(), crate::BulkString::from("v"));
+        assert_eq!(
+            backend.smove("dest", "string", "a"),
+            Err(BackendErr::WrongType)
+        );
+        assert!(backend.sismember("dest", "a")?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+const MAX_LEVEL: usize = 32;
+// chance of a node to also be linked at the next level
+const LEVEL_P: f64 = 0.25;
+// the head is a node without member that is never freed
+const HEAD: usize = 0;
+
+// Members of a sorted set ordered by score then member, with their scores indexed by member.
+// The skiplist keeps the span of every link, so ranks are found in O(log n) like lookups.
+#[derive(Debug, Clone)]
+pub struct SortedSet {
+    scores: HashMap<String, f64>,
+    list: SkipList,
+}
+
+// Nodes live in an arena and link to each other by index, freed slots are reused.
+#[derive(Debug, Clone)]
+struct SkipList {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    tail: Option<usize>,
+    level: usize,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    member: String,
+    score: f64,
+    backward: Option<usize>,
+    levels: Vec<Link>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Link {
+    forward: Option<usize>,
+    // number of nodes the link skips over, the linked one included
+    span: usize,
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        SortedSet {
+            scores: HashMap::new(),
+            list: SkipList::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    // Set the score of a member, returns its old score when it was already there.
+    pub(crate) fn insert(&mut self, member: String, score: f64) -> Option<f64> {
+        match self.scores.get_mut(&member) {
+            Some(old) if *old == score => Some(score),
+            Some(old) => {
+                let prev = std::mem::replace(old, score);
+                let member = self.list.remove(prev, &member)?;
+                self.list.insert(score, member);
+                Some(prev)
+            }
+            None => {
+                self.scores.insert(member.clone(), score);
+                self.list.insert(score, member);
+                None
+            }
+        }
+    }
+
+    // Remove a member, returns its score when it was there.
+    pub(crate) fn remove(&mut self, member: &str) -> Option<f64> {
+        let score = self.scores.remove(member)?;
+        self.list.remove(score, member);
+        Some(score)
+    }
+
+    // Rank of a member counted from 0 in ascending order, or in descending order with rev.
+    pub fn rank(&self, member: &str, rev: bool) -> Option<usize> {
+        let score = self.score(member)?;
+        let rank = self.list.rank(score, member)?;
+        Some(if rev { self.len() - 1 - rank } else { rank })
+    }
+
+    // Members with their scores starting at a rank, ascending or descending with rev.
+    pub fn iter_from(&self, rank: usize, rev: bool) -> impl Iterator<Item = (&str, f64)> + '_ {
+        let start = match rev {
+            false => self.list.by_rank(rank),
+            true => rank
+                .checked_add(1)
+                .and_then(|n| self.len().checked_sub(n))
+                .and_then(|rank| self.list.by_rank(rank)),
+        };
+        self.list.walk(start, rev)
+    }
+
+    // First and last ranks of the members within a range, None when it holds none.
+    // The range is given by whether a member is above its min and below its max.
+    pub(crate) fn range_ranks(
+        &self,
+        above_min: impl Fn(f64, &str) -> bool,
+        below_max: impl Fn(f64, &str) -> bool,
+    ) -> Option<(usize, usize)> {
+        let first = self.list.first_in(&above_min, &below_max)?;
+        let last = self.list.last_in(&above_min, &below_max)?;
+        (first <= last).then_some((first, last))
+    }
+}
+
+impl Default for SortedSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SkipList {
+    fn new() -> Self {
+        let head = Node {
+            member: String::new(),
+            score: 0.0,
+            backward: None,
+            levels: vec![Link::default(); MAX_LEVEL],
+        };
+        SkipList {
+            nodes: vec![head],
+            free: Vec::new(),
+            tail: None,
+            level: 1,
+            len: 0,
+        }
+    }
+
+    fn link(&self, node: usize, level: usize) -> Link {
+        self.nodes[node].levels[level]
+    }
+
+    fn link_mut(&mut self, node: usize, level: usize) -> &mut Link {
+        &mut self.nodes[node].levels[level]
+    }
+
+    // Whether a node orders before the given score and member.
+    fn before(&self, node: usize, score: f64, member: &str) -> bool {
+        let node = &self.nodes[node];
+        node.score < score || (node.score == score && node.member.as_str() < member)
+    }
+
+    fn random_level() -> usize {
+        let mut rng = rand::thread_rng();
+        let mut level = 1;
+        while level < MAX_LEVEL && rng.gen_bool(LEVEL_P) {
+            level += 1;
+        }
+        level
+    }
+
+    // Last node before the score and member at every level, with its rank.
+    fn find(&self, score: f64, member: &str) -> ([usize; MAX_LEVEL], [usize; MAX_LEVEL]) {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL];
+
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            rank[i] = if i + 1 == self.level { 0 } else { rank[i + 1] };
+            while let Some(next) = self.link(x, i).forward {
+                if !self.before(next, score, member) {
+                    break;
+                }
+                rank[i] += self.link(x, i).span;
+                x = next;
+            }
+            update[i] = x;
+        }
+        (update, rank)
+    }
+
+    // Insert a member that is not in the list yet.
+    fn insert(&mut self, score: f64, member: String) {
+        let (mut update, mut rank) = self.find(score, &member);
+
+        let level = Self::random_level();
+        if level > self.level {
+            for i in self.level..level {
+                rank[i] = 0;
+                update[i] = HEAD;
+                self.link_mut(HEAD, i).span = self.len;
+            }
+            self.level = level;
+        }
+
+        let node = Node {
+            member,
+            score,
+            backward: (update[0] != HEAD).then_some(update[0]),
+            levels: vec![Link::default(); level],
+        };
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = node;
+                id
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+
+        for i in 0..level {
+            let prev = self.link(update[i], i);
+            let skipped = rank[0] - rank[i];
+            *self.link_mut(id, i) = Link {
+                forward: prev.forward,
+                span: prev.span - skipped,
+            };
+            *self.link_mut(update[i], i) = Link {
+                forward: Some(id),
+                span: skipped + 1,
+            };
+        }
+        for (i, prev) in update.iter().enumerate().take(self.level).skip(level) {
+            self.link_mut(*prev, i).span += 1;
+        }
+
+        match self.link(id, 0).forward {
+            Some(next) => self.nodes[next].backward = Some(id),
+            None => self.tail = Some(id),
+        }
+        self.len += 1;
+    }
+
+    // Remove a member, returns it when it was in the list.
+    fn remove(&mut self, score: f64, member: &str) -> Option<String> {
+        let (update, _) = self.find(score, member);
+        let id = self.link(update[0], 0).forward?;
+        if self.nodes[id].score != score || self.nodes[id].member != member {
+            return None;
+        }
+
+        for (i, prev) in update.iter().enumerate().take(self.level) {
+            let link = self.link(*prev, i);
+            if link.forward == Some(id) {
+                let removed = self.link(id, i);
+                *self.link_mut(*prev, i) = Link {
+                    forward: removed.forward,
+                    span: link.span + removed.span - 1,
+                };
+            } else {
+                self.link_mut(*prev, i).span -= 1;
+            }
+        }
+
+        let backward = self.nodes[id].backward;
+        match self.link(id, 0).forward {
+            Some(next) => self.nodes[next].backward = backward,
+            None => self.tail = backward,
+        }
+        while self.level > 1 && self.link(HEAD, self.level - 1).forward.is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+
+        self.free.push(id);
+        let node = &mut self.nodes[id];
+        node.levels = Vec::new();
+        Some(std::mem::take(&mut node.member))
+    }
+
+    // Rank of a member counted from 0.
+    fn rank(&self, score: f64, member: &str) -> Option<usize> {
+        let mut rank = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.link(x, i).forward {
+                let node = &self.nodes[next];
+                if self.before(next, score, member)
+                    || (node.score == score && node.member == member)
+                {
+                    rank += self.link(x, i).span;
+                    x = next;
+                } else {
+                    break;
+                }
+            }
+            if x != HEAD && self.nodes[x].member == member {
+                return Some(rank - 1);
+            }
+        }
+        None
+    }
+
+    // Node at a rank counted from 0.
+    fn by_rank(&self, rank: usize) -> Option<usize> {
+        let target = rank.checked_add(1)?;
+        let mut traversed = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.link(x, i).forward {
+                if traversed + self.link(x, i).span > target {
+                    break;
+                }
+                traversed += self.link(x, i).span;
+                x = next;
+            }
+            if traversed == target {
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    // Rank of the first node above min, when it is also below max.
+    fn first_in(
+        &self,
+        above_min: impl Fn(f64, &str) -> bool,
+        below_max: impl Fn(f64, &str) -> bool,
+    ) -> Option<usize> {
+        let mut rank = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.link(x, i).forward {
+                let node = &self.nodes[next];
+                if above_min(node.score, &node.member) {
+                    break;
+                }
+                rank += self.link(x, i).span;
+                x = next;
+            }
+        }
+
+        let node = &self.nodes[self.link(x, 0).forward?];
+        below_max(node.score, &node.member).then_some(rank)
+    }
+
+    // Rank of the last node below max, when it is also above min.
+    fn last_in(
+        &self,
+        above_min: impl Fn(f64, &str) -> bool,
+        below_max: impl Fn(f64, &str) -> bool,
+    ) -> Option<usize> {
+        let mut rank = 0;
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.link(x, i).forward {
+                let node = &self.nodes[next];
+                if !below_max(node.score, &node.member) {
+                    break;
+                }
+                rank += self.link(x, i).span;
+                x = next;
+            }
+        }
+
+        let node = &self.nodes[x];
+        (x != HEAD && above_min(node.score, &node.member)).then(|| rank - 1)
+    }
+
+    // Members with their scores from a node on, following the backward links with rev.
+    fn walk(&self, start: Option<usize>, rev: bool) -> impl Iterator<Item = (&str, f64)> + '_ {
+        std::iter::successors(start, move |id| match rev {
+            false => self.link(*id, 0).forward,
+            true => self.nodes[*id].backward,
+        })
+        .map(|id| (self.nodes[id].member.as_str(), self.nodes[id].score))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(zset: &SortedSet, rev: bool) -> Vec<(String, f64)> {
+        zset.iter_from(0, rev)
+            .map(|(member, score)| (member.to_string(), score))
+            .collect()
+    }
+
+    #[test]
+    fn test_sorted_set_order() {
+        let mut zset = SortedSet::new();
+        assert_eq!(zset.insert("b".to_string(), 2.0), None);
+        assert_eq!(zset.insert("c".to_string(), 1.0), None);
+        assert_eq!(zset.insert("a".to_string(), 2.0), None);
+        assert_eq!(zset.insert("c".to_string(), 3.0), Some(1.0));
+
+        let expected = [("a", 2.0), ("b", 2.0), ("c", 3.0)]
+            .map(|(m, s)| (m.to_string(), s))
+            .to_vec();
+        assert_eq!(members(&zset, false), expected);
+        assert_eq!(
+            members(&zset, true),
+            expected.into_iter().rev().collect::<Vec<_>>()
+        );
+
+        assert_eq!(zset.rank("a", false), Some(0));
+        assert_eq!(zset.rank("c", false), Some(2));
+        assert_eq!(zset.rank("c", true), Some(0));
+        assert_eq!(zset.rank("missing", false), None);
+
+        assert_eq!(zset.remove("b"), Some(2.0));
+        assert_eq!(zset.remove("b"), None);
+        assert_eq!(zset.rank("c", false), Some(1));
+        assert_eq!(zset.iter_from(1, true).next(), Some(("a", 2.0)));
+        assert_eq!(zset.iter_from(2, false).next(), None);
+    }
+
+    #[test]
+    fn test_sorted_set_range_ranks() {
+        let mut zset = SortedSet::new();
+        for (i, member) in ["a", "b", "c", "d", "e"].into_iter().enumerate() {
+            zset.insert(member.to_string(), i as f64);
+        }
+
+        let ranks = zset.range_ranks(|score, _| score > 0.0, |score, _| score <= 3.0);
+        assert_eq!(ranks, Some((1, 3)));
+        let ranks = zset.range_ranks(|_, member| member >= "d", |_, _| true);
+        assert_eq!(ranks, Some((3, 4)));
+        let ranks = zset.range_ranks(|score, _| score >= 1.5, |score, _| score <= 1.8);
+        assert_eq!(ranks, None);
+        let ranks = zset.range_ranks(|score, _| score >= 3.0, |score, _| score <= 1.0);
+        assert_eq!(ranks, None);
+        assert_eq!(SortedSet::new().range_ranks(|_, _| true, |_, _| true), None);
+    }
+
+    #[test]
+    fn test_sorted_set_ranks() {
+        let mut zset = SortedSet::new();
+        let mut rng = rand::thread_rng();
+        let mut expected = Vec::new();
+        for i in 0..2000 {
+            let score = rng.gen_range(0..500) as f64;
+            zset.insert(i.to_string(), score);
+            expected.push((score, i.to_string()));
+        }
+        // remove every third member and move every fifth one
+        for i in (0..2000).step_by(3) {
+            zset.remove(&i.to_string());
+        }
+        for i in (0..2000).step_by(5) {
+            zset.insert(i.to_string(), -(i as f64));
+        }
+        let mut expected = expected
+            .into_iter()
+            .filter_map(|(score, member)| {
+                let i = member.parse::<usize>().unwrap();
+                match (i % 5, i % 3) {
+                    (0, _) => Some((-(i as f64), member)),
+                    (_, 0) => None,
+                    _ => Some((score, member)),
+                }
+            })
+            .collect::<Vec<_>>();
+        expected.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+        assert_eq!(zset.len(), expected.len());
+        for (rank, (score, member)) in expected.iter().enumerate() {
+            assert_eq!(zset.rank(member, false), Some(rank));
+            assert_eq!(
+                zset.iter_from(rank, false).next(),
+                Some((member.as_str(), *score))
+            );
+        }
+        let walked = zset
+            .iter_from(0, false)
+            .map(|(member, score)| (score, member.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(walked, expected);
+    }
+}
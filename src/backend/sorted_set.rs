@@ -0,0 +1,537 @@
+use std::ops::Bound;
+
+use crate::{Backend, BackendErr, Entry, SortedSet, Value};
+
+// Options of zadd, nx and xx restrict it to new and existing members respectively,
+// gt and lt only let it update a score upwards or downwards.
+// ch counts the members whose score changed together with the added ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZAddOpt {
+    pub nx: bool,
+    pub xx: bool,
+    pub gt: bool,
+    pub lt: bool,
+    pub ch: bool,
+}
+
+// Members selected by a range query, always given from its low end to its high end.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZRangeBy {
+    // indexes where negative ones count from the end, in the order of the query
+    Rank(i64, i64),
+    Score(Bound<f64>, Bound<f64>),
+    // members compared bytewise, only meaningful when they all share a score
+    Lex(LexBound, LexBound),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexBound {
+    NegInf,
+    PosInf,
+    Included(String),
+    Excluded(String),
+}
+
+// Order and paging of a range query, the offset and count apply after the order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZRangeOpt {
+    pub rev: bool,
+    pub offset: usize,
+    // None takes every member from the offset on
+    pub count: Option<usize>,
+}
+
+// What adding a single member did.
+enum Added {
+    New(f64),
+    Changed(f64),
+    Unchanged(f64),
+    // the options did not let it through
+    Skipped,
+}
+
+impl Backend {
+    // Add members with their scores or update the scores of existing ones.
+    // Returns the number of added members, changed ones included with ch.
+    pub fn zadd(
+        &self,
+        key: String,
+        pairs: Vec<(f64, String)>,
+        opt: ZAddOpt,
+    ) -> Result<usize, BackendErr> {
+        let mut entry = self.get_entry_or_insert(key.clone(), || Value::ZSet(SortedSet::new()));
+        let zset = entry.as_zset_mut()?;
+
+        let mut count = 0;
+        for (score, member) in pairs {
+            match add(zset, member, score, false, opt)? {
+                Added::New(_) => count += 1,
+                Added::Changed(_) if opt.ch => count += 1,
+                _ => {}
+            }
+        }
+        let empty = zset.is_empty();
+
+        // xx on a missing key adds nothing
+        drop(entry);
+        if empty {
+            self.remove_if_empty(&key);
+        }
+        Ok(count)
+    }
+
+    // Increment the score of a member, a missing member is added with the increment.
+    // Returns the new score, None when the options did not let it through.
+    pub fn zincr_by(
+        &self,
+        key: String,
+        member: String,
+        delta: f64,
+        opt: ZAddOpt,
+    ) -> Result<Option<f64>, BackendErr> {
+        let mut entry = self.get_entry_or_insert(key.clone(), || Value::ZSet(SortedSet::new()));
+        let zset = entry.as_zset_mut()?;
+
+        let ret = add(zset, member, delta, true, opt);
+        let empty = zset.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(&key);
+        }
+        match ret? {
+            Added::New(score) | Added::Changed(score) | Added::Unchanged(score) => Ok(Some(score)),
+            Added::Skipped => Ok(None),
+        }
+    }
+
+    // Remove members, the key is removed together with its last member.
+    pub fn zrem(&self, key: &str, members: &[String]) -> Result<usize, BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(0);
+        };
+
+        let zset = entry.as_zset_mut()?;
+        let removed = members
+            .iter()
+            .filter(|member| zset.remove(member).is_some())
+            .count();
+        let empty = zset.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(removed)
+    }
+
+    pub fn zscore(&self, key: &str, member: &str) -> Result<Option<f64>, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry.as_zset()?.score(member)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn zmscore(&self, key: &str, members: &[String]) -> Result<Vec<Option<f64>>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(vec![None; members.len()]);
+        };
+
+        let zset = entry.as_zset()?;
+        Ok(members.iter().map(|member| zset.score(member)).collect())
+    }
+
+    pub fn zcard(&self, key: &str) -> Result<usize, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(entry.as_zset()?.len()),
+            None => Ok(0),
+        }
+    }
+
+    // Rank of a member together with its score, ranks count from the highest score with rev.
+    pub fn zrank(
+        &self,
+        key: &str,
+        member: &str,
+        rev: bool,
+    ) -> Result<Option<(usize, f64)>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(None);
+        };
+
+        let zset = entry.as_zset()?;
+        Ok(zset.rank(member, rev).zip(zset.score(member)))
+    }
+
+    // Members with their scores in a range, in the order of the query.
+    pub fn zrange(
+        &self,
+        key: &str,
+        range: &ZRangeBy,
+        opt: ZRangeOpt,
+    ) -> Result<Vec<(String, f64)>, BackendErr> {
+        let Some(entry) = self.get_entry(key) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(select(entry.as_zset()?, range, opt))
+    }
+
+    // Store the members of a range of src in dest, replacing whatever it held.
+    // Returns the number of stored members, an empty result deletes dest.
+    pub fn zrange_store(
+        &self,
+        dest: String,
+        src: &str,
+        range: &ZRangeBy,
+        opt: ZRangeOpt,
+    ) -> Result<usize, BackendErr> {
+        let mut guard = self.lock_keys([src, dest.as_str()]);
+
+        let members = match guard.get(src) {
+            Some(entry) => select(entry.as_zset()?, range, opt),
+            None => Vec::new(),
+        };
+
+        let len = members.len();
+        if members.is_empty() {
+            guard.remove(&dest);
+            return Ok(0);
+        }
+
+        let mut zset = SortedSet::new();
+        for (member, score) in members {
+            zset.insert(member, score);
+        }
+        guard.insert(dest, Entry::new(Value::ZSet(zset)));
+        Ok(len)
+    }
+
+    // Number of members in a range, counted from their ranks without visiting them.
+    pub fn zcount(&self, key: &str, range: &ZRangeBy) -> Result<usize, BackendErr> {
+        match self.get_entry(key) {
+            Some(entry) => Ok(resolve(entry.as_zset()?, range, false).1),
+            None => Ok(0),
+        }
+    }
+
+    // Remove the members in a range, the key is removed together with its last member.
+    pub fn zremrange(&self, key: &str, range: &ZRangeBy) -> Result<usize, BackendErr> {
+        let Some(mut entry) = self.get_entry_mut(key) else {
+            return Ok(0);
+        };
+
+        let zset = entry.as_zset_mut()?;
+        let members = select(zset, range, ZRangeOpt::default());
+        for (member, _) in members.iter() {
+            zset.remove(member);
+        }
+        let empty = zset.is_empty();
+
+        drop(entry);
+        if empty {
+            self.remove_if_empty(key);
+        }
+        Ok(members.len())
+    }
+}
+
+// Members of a range with their scores after ordering and paging.
+fn select(zset: &SortedSet, range: &ZRangeBy, opt: ZRangeOpt) -> Vec<(String, f64)> {
+    let (start, len) = resolve(zset, range, opt.rev);
+    let len = len
+        .saturating_sub(opt.offset)
+        .min(opt.count.unwrap_or(usize::MAX));
+
+    zset.iter_from(start.saturating_add(opt.offset), opt.rev)
+        .take(len)
+        .map(|(member, score)| (member.to_string(), score))
+        .collect()
+}
+
+// First rank of a range in the order of the query and its number of members.
+fn resolve(zset: &SortedSet, range: &ZRangeBy, rev: bool) -> (usize, usize) {
+    let len = zset.len() as i64;
+    let ranks = match range {
+        ZRangeBy::Rank(start, stop) => {
+            let start = if *start < 0 { len + start } else { *start }.max(0);
+            let stop = if *stop < 0 { len + stop } else { *stop }.min(len - 1);
+            return match start <= stop {
+                true => (start as usize, (stop - start + 1) as usize),
+                false => (0, 0),
+            };
+        }
+        ZRangeBy::Score(min, max) => zset.range_ranks(
+            |score, _| match min {
+                Bound::Included(min) => score >= *min,
+                Bound::Excluded(min) => score > *min,
+                Bound::Unbounded => true,
+            },
+            |score, _| match max {
+                Bound::Included(max) => score <= *max,
+                Bound::Excluded(max) => score < *max,
+                Bound::Unbounded => true,
+            },
+        ),
+        ZRangeBy::Lex(min, max) => zset.range_ranks(
+            |_, member| match min {
+                LexBound::NegInf => true,
+                LexBound::PosInf => false,
+                LexBound::Included(min) => member >= min.as_str(),
+                LexBound::Excluded(min) => member > min.as_str(),
+            },
+            |_, member| match max {
+                LexBound::NegInf => false,
+                LexBound::PosInf => true,
+                LexBound::Included(max) => member <= max.as_str(),
+                LexBound::Excluded(max) => member < max.as_str(),
+            },
+        ),
+    };
+
+    match ranks {
+        Some((first, last)) if rev => (zset.len() - 1 - last, last - first + 1),
+        Some((first, last)) => (first, last - first + 1),
+        None => (0, 0),
+    }
+}
+
+// Add a member or update its score, an increment is added to its current score.
+fn add(
+    zset: &mut SortedSet,
+    member: String,
+    score: f64,
+    incr: bool,
+    opt: ZAddOpt,
+) -> Result<Added, BackendErr> {
+    let old = zset.score(&member);
+    if (opt.nx && old.is_some()) || (opt.xx && old.is_none()) {
+        return Ok(Added::Skipped);
+    }
+
+    let score = match incr {
+        true => old.unwrap_or(0.0) + score,
+        false => score,
+    };
+    if score.is_nan() {
+        return Err(BackendErr::ScoreNaN);
+    }
+
+    match old {
+        None => {
+            zset.insert(member, score);
+            Ok(Added::New(score))
+        }
+        Some(old) if (opt.gt && score <= old) || (opt.lt && score >= old) => Ok(Added::Skipped),
+        Some(old) if old == score => Ok(Added::Unchanged(score)),
+        Some(_) => {
+            zset.insert(member, score);
+            Ok(Added::Changed(score))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(pairs: &[(f64, &str)]) -> Vec<(f64, String)> {
+        pairs.iter().map(|(s, m)| (*s, m.to_string())).collect()
+    }
+
+    #[test]
+    fn test_zadd_opt() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let key = || "z".to_string();
+
+        assert_eq!(
+            backend.zadd(key(), pairs(&[(1.0, "a"), (2.0, "b")]), ZAddOpt::default())?,
+            2
+        );
+
+        let xx = ZAddOpt {
+            xx: true,
+            ch: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            backend.zadd(key(), pairs(&[(3.0, "a"), (1.0, "c")]), xx)?,
+            1
+        );
+        assert_eq!(backend.zscore("z", "a")?, Some(3.0));
+        assert_eq!(backend.zscore("z", "c")?, None);
+
+        let nx = ZAddOpt {
+            nx: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            backend.zadd(key(), pairs(&[(9.0, "a"), (1.0, "c")]), nx)?,
+            1
+        );
+        assert_eq!(backend.zscore("z", "a")?, Some(3.0));
+
+        let gt = ZAddOpt {
+            gt: true,
+            ch: true,
+            ..Default::default()
+        };
+        let ret = backend.zadd(key(), pairs(&[(2.0, "a"), (5.0, "b"), (0.0, "d")]), gt)?;
+        assert_eq!(ret, 2);
+        assert_eq!(
+            backend.zmscore("z", &["a".to_string(), "b".to_string()])?,
+            [Some(3.0), Some(5.0)]
+        );
+
+        // xx on a missing key does not create it
+        assert_eq!(
+            backend.zadd("missing".to_string(), pairs(&[(1.0, "a")]), xx)?,
+            0
+        );
+        assert_eq!(backend.exists(&["missing".to_string()]), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zincr_by() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let opt = ZAddOpt::default();
+
+        let ret = backend.zincr_by("z".to_string(), "a".to_string(), 1.5, opt)?;
+        assert_eq!(ret, Some(1.5));
+        let ret = backend.zincr_by("z".to_string(), "a".to_string(), f64::INFINITY, opt)?;
+        assert_eq!(ret, Some(f64::INFINITY));
+        let ret = backend.zincr_by("z".to_string(), "a".to_string(), f64::NEG_INFINITY, opt);
+        assert_eq!(ret, Err(BackendErr::ScoreNaN));
+
+        let lt = ZAddOpt {
+            lt: true,
+            ..Default::default()
+        };
+        let ret = backend.zincr_by("z".to_string(), "a".to_string(), 1.0, lt)?;
+        assert_eq!(ret, None);
+
+        Ok(())
+    }
+
+    fn members(ret: Vec<(String, f64)>) -> Vec<String> {
+        ret.into_iter().map(|(member, _)| member).collect()
+    }
+
+    #[test]
+    fn test_zrange() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let members_in = pairs(&[(1.0, "a"), (2.0, "b"), (3.0, "c"), (4.0, "d"), (5.0, "e")]);
+        backend.zadd("z".to_string(), members_in, ZAddOpt::default())?;
+        let asc = ZRangeOpt::default();
+        let rev = ZRangeOpt {
+            rev: true,
+            ..Default::default()
+        };
+
+        let ret = backend.zrange("z", &ZRangeBy::Rank(1, -2), asc)?;
+        assert_eq!(members(ret), ["b", "c", "d"]);
+        let ret = backend.zrange("z", &ZRangeBy::Rank(0, 1), rev)?;
+        assert_eq!(ret, [("e".to_string(), 5.0), ("d".to_string(), 4.0)]);
+        assert!(backend.zrange("z", &ZRangeBy::Rank(3, 1), asc)?.is_empty());
+        assert!(backend.zrange("z", &ZRangeBy::Rank(5, 10), asc)?.is_empty());
+        assert_eq!(
+            backend.zrange("z", &ZRangeBy::Rank(-100, 100), asc)?.len(),
+            5
+        );
+
+        let range = ZRangeBy::Score(Bound::Excluded(1.0), Bound::Included(4.0));
+        assert_eq!(members(backend.zrange("z", &range, asc)?), ["b", "c", "d"]);
+        assert_eq!(members(backend.zrange("z", &range, rev)?), ["d", "c", "b"]);
+        let paged = ZRangeOpt {
+            rev: true,
+            offset: 1,
+            count: Some(1),
+        };
+        assert_eq!(members(backend.zrange("z", &range, paged)?), ["c"]);
+        let range = ZRangeBy::Score(
+            Bound::Included(f64::NEG_INFINITY),
+            Bound::Excluded(f64::INFINITY),
+        );
+        assert_eq!(backend.zcount("z", &range)?, 5);
+        let range = ZRangeBy::Score(Bound::Included(4.0), Bound::Included(2.0));
+        assert_eq!(backend.zcount("z", &range)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_by_lex() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let members_in = pairs(&[(0.0, "a"), (0.0, "b"), (0.0, "c"), (0.0, "d")]);
+        backend.zadd("z".to_string(), members_in, ZAddOpt::default())?;
+        let asc = ZRangeOpt::default();
+
+        let range = ZRangeBy::Lex(LexBound::NegInf, LexBound::Excluded("c".to_string()));
+        assert_eq!(members(backend.zrange("z", &range, asc)?), ["a", "b"]);
+        let range = ZRangeBy::Lex(LexBound::Included("b".to_string()), LexBound::PosInf);
+        assert_eq!(backend.zcount("z", &range)?, 3);
+        let range = ZRangeBy::Lex(LexBound::PosInf, LexBound::PosInf);
+        assert_eq!(backend.zcount("z", &range)?, 0);
+
+        let range = ZRangeBy::Lex(
+            LexBound::Excluded("a".to_string()),
+            LexBound::Included("c".to_string()),
+        );
+        assert_eq!(backend.zremrange("z", &range)?, 2);
+        assert_eq!(
+            members(backend.zrange("z", &ZRangeBy::Rank(0, -1), asc)?),
+            ["a", "d"]
+        );
+        assert_eq!(backend.zremrange("z", &ZRangeBy::Rank(0, -1))?, 2);
+        assert_eq!(backend.exists(&["z".to_string()]), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_store() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let members_in = pairs(&[(1.0, "a"), (2.0, "b"), (3.0, "c")]);
+        backend.zadd("z".to_string(), members_in, ZAddOpt::default())?;
+        backend.set("dest".to_string(), crate::BulkString::from("v"));
+
+        let range = ZRangeBy::Score(Bound::Included(2.0), Bound::Unbounded);
+        let len = backend.zrange_store("dest".to_string(), "z", &range, ZRangeOpt::default())?;
+        assert_eq!(len, 2);
+        assert_eq!(backend.zscore("dest", "c")?, Some(3.0));
+        assert_eq!(backend.zrank("dest", "b", false)?, Some((0, 2.0)));
+
+        let len =
+            backend.zrange_store("dest".to_string(), "missing", &range, ZRangeOpt::default())?;
+        assert_eq!(len, 0);
+        assert_eq!(backend.exists(&["dest".to_string()]), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrem_zrank() -> Result<(), BackendErr> {
+        let backend = Backend::new();
+        let members = pairs(&[(3.0, "c"), (1.0, "a"), (2.0, "b")]);
+        backend.zadd("z".to_string(), members, ZAddOpt::default())?;
+
+        assert_eq!(backend.zcard("z")?, 3);
+        assert_eq!(backend.zrank("z", "a", false)?, Some((0, 1.0)));
+        assert_eq!(backend.zrank("z", "a", true)?, Some((2, 1.0)));
+        assert_eq!(backend.zrank("z", "x", false)?, None);
+        assert_eq!(backend.zrank("missing", "a", false)?, None);
+
+        let members = ["a".to_string(), "x".to_string()];
+        assert_eq!(backend.zrem("z", &members)?, 1);
+        assert_eq!(backend.zrank("z", "c", false)?, Some((1, 3.0)));
+        assert_eq!(backend.zrem("z", &["b".to_string(), "c".to_string()])?, 2);
+        assert_eq!(backend.exists(&["z".to_string()]), 0);
+
+        backend.set("string".to_string(), crate::BulkString::from("v"));
+        assert_eq!(backend.zcard("string"), Err(BackendErr::WrongType));
+
+        Ok(())
+    }
+}
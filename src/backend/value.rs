@@ -0,0 +1,234 @@
+use std::collections::{HashSet, VecDeque};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use dashmap::DashMap;
+
+use crate::{BackendErr, BulkString, RespFrame, SortedSet};
+
+// Every key in the keyspace holds exactly one typed value
+// together with its absolute expire time in unix milliseconds.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub(crate) value: Value,
+    pub(crate) expire_at: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(BulkString),
+    Hash(HashValue),
+    List(VecDeque<RespFrame>),
+    Set(HashSet<String>),
+    ZSet(SortedSet),
+}
+
+// Fields of a hash, each one may have its own expire time.
+#[derive(Debug)]
+pub struct HashValue {
+    fields: DashMap<String, HashField>,
+    // Earliest expire time among the fields, i64::MAX when none has one.
+    // It may be earlier than the real one but never later, so no expired field is missed.
+    next_expire: AtomicI64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HashField {
+    pub(crate) value: RespFrame,
+    pub(crate) expire_at: Option<i64>,
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Hash(_) => "hash",
+            Value::List(_) => "list",
+            Value::Set(_) => "set",
+            Value::ZSet(_) => "zset",
+        }
+    }
+
+    // Whether the value is a collection without elements, strings are never empty collections.
+    pub(crate) fn is_empty_collection(&self) -> bool {
+        match self {
+            Value::String(_) => false,
+            Value::Hash(v) => v.is_empty(),
+            Value::List(v) => v.is_empty(),
+            Value::Set(v) => v.is_empty(),
+            Value::ZSet(v) => v.is_empty(),
+        }
+    }
+
+    pub(crate) fn as_string(&self) -> Result<&BulkString, BackendErr> {
+        match self {
+            Value::String(v) => Ok(v),
+            _ => Err(BackendErr::WrongType),
+        }
+    }
+
+    pub(crate) fn as_string_mut(&mut self) -> Result<&mut BulkString, BackendErr> {
+        match self {
+            Value::String(v) => Ok(v),
+            _ => Err(BackendErr::WrongType),
+        }
+    }
+
+    // Reap the expired fields of a hash, returns whether the hash has no field left.
+    pub(crate) fn reap_fields(&self, now: i64) -> bool {
+        match self {
+            Value::Hash(v) => v.reap(now) && v.is_empty(),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn as_hash(&self) -> Result<&HashValue, BackendErr> {
+        match self {
+            Value::Hash(v) => Ok(v),
+            _ => Err(BackendErr::WrongType),
+        }
+    }
+
+    pub(crate) fn as_list(&self) -> Result<&VecDeque<RespFrame>, BackendErr> {
+        match self {
+            Value::List(v) => Ok(v),
+            _ => Err(BackendErr::WrongType),
+        }
+    }
+
+    pub(crate) fn as_list_mut(&mut self) -> Result<&mut VecDeque<RespFrame>, BackendErr> {
+        match self {
+            Value::List(v) => Ok(v),
+            _ => Err(BackendErr::WrongType),
+        }
+    }
+
+    pub(crate) fn as_set(&self) -> Result<&HashSet<String>, BackendErr> {
+        match self {
+            Value::Set(v) => Ok(v),
+            _ => Err(BackendErr::WrongType),
+        }
+    }
+
+    pub(crate) fn as_set_mut(&mut self) -> Result<&mut HashSet<String>, BackendErr> {
+        match self {
+            Value::Set(v) => Ok(v),
+            _ => Err(BackendErr::WrongType),
+        }
+    }
+
+    pub(crate) fn as_zset(&self) -> Result<&SortedSet, BackendErr> {
+        match self {
+            Value::ZSet(v) => Ok(v),
+            _ => Err(BackendErr::WrongType),
+        }
+    }
+
+    pub(crate) fn as_zset_mut(&mut self) -> Result<&mut SortedSet, BackendErr> {
+        match self {
+            Value::ZSet(v) => Ok(v),
+            _ => Err(BackendErr::WrongType),
+        }
+    }
+}
+
+impl Entry {
+    pub fn new(value: Value) -> Self {
+        Entry {
+            value,
+            expire_at: None,
+        }
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expire_at, Some(at) if at <= now)
+    }
+}
+
+impl HashValue {
+    pub fn new() -> Self {
+        HashValue {
+            fields: DashMap::new(),
+            next_expire: AtomicI64::new(i64::MAX),
+        }
+    }
+
+    pub(crate) fn next_expire(&self) -> Option<i64> {
+        Some(self.next_expire.load(Ordering::Relaxed)).filter(|at| *at != i64::MAX)
+    }
+
+    // Record that a field expires at the given time, the caller must hold the key exclusively.
+    pub(crate) fn track_expire(&self, at: i64) {
+        self.next_expire.fetch_min(at, Ordering::Relaxed);
+    }
+
+    // Remove the expired fields once the earliest expire time has passed,
+    // returns whether any field was looked at.
+    pub(crate) fn reap(&self, now: i64) -> bool {
+        if self.next_expire.load(Ordering::Relaxed) > now {
+            return false;
+        }
+
+        let mut next = i64::MAX;
+        self.fields.retain(|_, field| match field.expire_at {
+            Some(at) if at <= now => false,
+            Some(at) => {
+                next = next.min(at);
+                true
+            }
+            None => true,
+        });
+        self.next_expire.store(next, Ordering::Relaxed);
+        true
+    }
+}
+
+impl HashField {
+    pub fn new(value: RespFrame) -> Self {
+        HashField {
+            value,
+            expire_at: None,
+        }
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expire_at, Some(at) if at <= now)
+    }
+}
+
+impl Default for HashValue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for HashValue {
+    fn clone(&self) -> Self {
+        HashValue {
+            fields: self.fields.clone(),
+            next_expire: AtomicI64::new(self.next_expire.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Deref for HashValue {
+    type Target = DashMap<String, HashField>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.fields
+    }
+}
+
+impl Deref for Entry {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl DerefMut for Entry {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
@@ -0,0 +1,277 @@
+// bitfield cmd
+use crate::{
+    cmd::{
+        extract_args, parse_int, parse_opt, validate_cmd_min, BitField, BitFieldRo, CmdErr,
+        CmdExecutor,
+    },
+    Array, Backend, BackendErr, BitFieldOp, BitFieldType, Null, Overflow, RespFrame,
+    MAX_STRING_LEN,
+};
+
+// cmd bitfield
+impl CmdExecutor for BitField {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        bitfield_reply(backend.bitfield(self.key, &self.ops))
+    }
+}
+
+impl TryFrom<Array> for BitField {
+    type Error = CmdErr;
+
+    // BITFIELD key [GET encoding offset | [OVERFLOW <WRAP | SAT | FAIL>]
+    //   <SET encoding offset value | INCRBY encoding offset increment> ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["bitfield"], 1)?;
+
+        let (key, ops) = parse_bitfield_args(value, false)?;
+        Ok(BitField { key, ops })
+    }
+}
+
+// cmd bitfield_ro
+impl CmdExecutor for BitFieldRo {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        bitfield_reply(backend.bitfield_ro(&self.key, &self.ops))
+    }
+}
+
+impl TryFrom<Array> for BitFieldRo {
+    type Error = CmdErr;
+
+    // BITFIELD_RO key [GET encoding offset ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["bitfield_ro"], 1)?;
+
+        let (key, ops) = parse_bitfield_args(value, true)?;
+        Ok(BitFieldRo { key, ops })
+    }
+}
+
+fn bitfield_reply(ret: Result<Vec<Option<i64>>, BackendErr>) -> RespFrame {
+    match ret {
+        Ok(values) => Array::new(
+            values
+                .into_iter()
+                .map(|value| value.map_or(RespFrame::Null(Null), RespFrame::Integer))
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into(),
+        Err(e) => e.into(),
+    }
+}
+
+fn parse_bitfield_args(value: Array, read_only: bool) -> Result<(String, Vec<BitFieldOp>), CmdErr> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+        _ => return Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+    };
+
+    let mut ops = Vec::new();
+    let mut overflow = Overflow::Wrap;
+    while let Some(arg) = args.next() {
+        let sub = parse_opt(arg)?;
+        if read_only && sub != "GET" {
+            return Err(CmdErr::Other(
+                "BITFIELD_RO only supports the GET subcommand".to_string(),
+            ));
+        }
+
+        if sub == "OVERFLOW" {
+            overflow = match parse_opt(args.next().ok_or(CmdErr::SyntaxErr)?)?.as_str() {
+                "WRAP" => Overflow::Wrap,
+                "SAT" => Overflow::Sat,
+                "FAIL" => Overflow::Fail,
+                _ => return Err(CmdErr::Other("Invalid OVERFLOW type specified".to_string())),
+            };
+            continue;
+        }
+
+        let (ty, offset) = match (args.next(), args.next()) {
+            (Some(ty), Some(offset)) => {
+                let ty = parse_bitfield_type(ty)?;
+                (ty, parse_bitfield_offset(offset, ty)?)
+            }
+            _ => return Err(CmdErr::SyntaxErr),
+        };
+        let op = match sub.as_str() {
+            "GET" => BitFieldOp::Get { ty, offset },
+            "SET" => BitFieldOp::Set {
+                ty,
+                offset,
+                value: parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?,
+                overflow,
+            },
+            "INCRBY" => BitFieldOp::IncrBy {
+                ty,
+                offset,
+                increment: parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?,
+                overflow,
+            },
+            _ => return Err(CmdErr::SyntaxErr),
+        };
+        ops.push(op);
+    }
+
+    Ok((key, ops))
+}
+
+// Signed types go up to 64 bits, unsigned ones up to 63 so every value fits in a reply integer.
+fn parse_bitfield_type(frame: RespFrame) -> Result<BitFieldType, CmdErr> {
+    let ty = parse_opt(frame)?;
+    let (signed, max) = match ty.as_bytes().first() {
+        Some(b'I') => (true, 64),
+        Some(b'U') => (false, 63),
+        _ => (false, 0),
+    };
+
+    match ty.get(1..).unwrap_or_default().parse::<u32>() {
+        Ok(bits) if (1..=max).contains(&bits) => Ok(BitFieldType { signed, bits }),
+        _ => Err(CmdErr::Other(format!(
+            "Invalid bitfield type. Use something like i16 u8. {}",
+            "Note that u64 is not supported but i64 is."
+        ))),
+    }
+}
+
+// Offsets prefixed with `#` are multiplied by the width of the type.
+fn parse_bitfield_offset(frame: RespFrame, ty: BitFieldType) -> Result<usize, CmdErr> {
+    let err = || CmdErr::Other("bit offset is not an integer or out of range".to_string());
+
+    let offset = match frame {
+        RespFrame::BulkString(s) if s.first() == Some(&b'#') => {
+            let index = parse_int(RespFrame::BulkString(s[1..].into())).map_err(|_| err())?;
+            index.checked_mul(ty.bits as i64).ok_or_else(err)?
+        }
+        frame => parse_int(frame).map_err(|_| err())?,
+    };
+
+    if offset < 0 || offset as usize + ty.bits as usize > MAX_STRING_LEN * 8 {
+        return Err(err());
+    }
+    Ok(offset as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespDecode};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_bitfield_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*11\r\n$8\r\nbitfield\r\n$3\r\nkey\r\n$3\r\nget\r\n$2\r\nu4\r\n$1\r\n0\r\n\
+            $8\r\noverflow\r\n$3\r\nsat\r\n$6\r\nincrby\r\n$2\r\ni5\r\n$2\r\n#2\r\n$2\r\n-1\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: BitField = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+
+        let u4 = BitFieldType {
+            signed: false,
+            bits: 4,
+        };
+        let i5 = BitFieldType {
+            signed: true,
+            bits: 5,
+        };
+        assert_eq!(
+            cmd.ops,
+            vec![
+                BitFieldOp::Get { ty: u4, offset: 0 },
+                BitFieldOp::IncrBy {
+                    ty: i5,
+                    offset: 10,
+                    increment: -1,
+                    overflow: Overflow::Sat,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitfield_invalid_args() -> Result<()> {
+        let parse = |args: &[&str]| {
+            let mut frames = vec![RespFrame::from(BulkString::from("bitfield"))];
+            frames.extend(args.iter().map(|arg| BulkString::from(*arg).into()));
+            BitField::try_from(Array::new(frames))
+        };
+
+        assert!(parse(&["key"]).is_ok());
+        assert!(parse(&["key", "get", "u64", "0"]).is_err());
+        assert!(parse(&["key", "get", "i65", "0"]).is_err());
+        assert!(parse(&["key", "get", "i0", "0"]).is_err());
+        assert!(parse(&["key", "get", "x8", "0"]).is_err());
+        assert!(parse(&["key", "get", "i8", "-1"]).is_err());
+        assert!(parse(&["key", "set", "i8", "0"]).is_err());
+        assert!(parse(&["key", "overflow", "none"]).is_err());
+        assert!(parse(&["key", "unknown", "i8", "0"]).is_err());
+
+        let frames = ["bitfield_ro", "key", "set", "i8", "0", "1"]
+            .iter()
+            .map(|arg| BulkString::from(*arg).into())
+            .collect::<Vec<RespFrame>>();
+        assert!(BitFieldRo::try_from(Array::new(frames)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitfield_cmd() -> Result<()> {
+        let backend = Backend::new();
+        let parse = |args: &[&str]| -> Result<BitField, CmdErr> {
+            let mut frames = vec![RespFrame::from(BulkString::from("bitfield"))];
+            frames.extend(args.iter().map(|arg| BulkString::from(*arg).into()));
+            BitField::try_from(Array::new(frames))
+        };
+
+        let cmd = parse(&["mykey", "incrby", "i5", "100", "1", "get", "u4", "0"])?;
+        let ret = cmd.exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new([RespFrame::Integer(1), RespFrame::Integer(0)]).into()
+        );
+
+        let cmd = parse(&[
+            "mykey", "incrby", "u2", "102", "1", "overflow", "sat", "incrby", "u2", "110", "1",
+        ])?;
+        for expected in [[1, 1], [2, 2], [3, 3], [0, 3]] {
+            let ret = backend.bitfield("mykey".to_string(), &cmd.ops)?;
+            assert_eq!(ret, expected.map(Some).to_vec());
+        }
+
+        let cmd = parse(&["mykey", "overflow", "fail", "incrby", "u2", "102", "4"])?;
+        assert_eq!(
+            cmd.exec(&backend),
+            Array::new([RespFrame::Null(Null)]).into()
+        );
+
+        // set replies the old value and positional offsets count in type widths
+        let cmd = parse(&["counters", "set", "i8", "#1", "-100", "get", "u8", "8"])?;
+        assert_eq!(
+            cmd.exec(&backend),
+            Array::new([RespFrame::Integer(0), RespFrame::Integer(156)]).into()
+        );
+        assert_eq!(backend.get("counters")?, Some(BulkString::new([0u8, 156])));
+
+        // read only operations never create the key
+        let cmd = parse(&["missing", "get", "i8", "0"])?;
+        assert_eq!(
+            cmd.exec(&backend),
+            Array::new([RespFrame::Integer(0)]).into()
+        );
+        assert_eq!(backend.get("missing")?, None);
+
+        backend.lpush("list".to_string(), vec![BulkString::from("a").into()])?;
+        let cmd = parse(&["list", "get", "i8", "0"])?;
+        assert_eq!(cmd.exec(&backend), BackendErr::WrongType.into());
+
+        Ok(())
+    }
+}
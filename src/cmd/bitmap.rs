@@ -0,0 +1,482 @@
+// bitmap cmd
+use crate::{
+    cmd::{
+        extract_args, parse_int, parse_opt, validate_cmd, validate_cmd_min, BitCount, BitOp,
+        BitPos, CmdErr, CmdExecutor, GetBit, SetBit,
+    },
+    Array, Backend, BitOperator, BitUnit, RespFrame, MAX_STRING_LEN,
+};
+
+// cmd setbit
+impl CmdExecutor for SetBit {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.setbit(self.key, self.offset, self.value) {
+            Ok(old) => RespFrame::Integer(old as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SetBit {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["setbit"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(offset), Some(value)) => Ok(SetBit {
+                key: String::from_utf8(key.0)?,
+                offset: parse_bit_offset(offset)?,
+                value: match parse_int(value) {
+                    Ok(0) => false,
+                    Ok(1) => true,
+                    _ => {
+                        return Err(CmdErr::Other(
+                            "bit is not an integer or out of range".to_string(),
+                        ))
+                    }
+                },
+            }),
+            _ => Err(CmdErr::InvalidArg(
+                "Invalid key, offset or value.".to_string(),
+            )),
+        }
+    }
+}
+
+// cmd getbit
+impl CmdExecutor for GetBit {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.getbit(&self.key, self.offset) {
+            Ok(bit) => RespFrame::Integer(bit as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for GetBit {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["getbit"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(offset)) => Ok(GetBit {
+                key: String::from_utf8(key.0)?,
+                offset: parse_bit_offset(offset)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key or offset.".to_string())),
+        }
+    }
+}
+
+// cmd bitcount
+impl CmdExecutor for BitCount {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.bitcount(&self.key, self.range, self.unit) {
+            Ok(count) => RespFrame::Integer(count as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for BitCount {
+    type Error = CmdErr;
+
+    // BITCOUNT key [start end [BYTE | BIT]]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["bitcount"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            _ => return Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        };
+
+        let mut cmd = BitCount {
+            key,
+            range: None,
+            unit: BitUnit::Byte,
+        };
+        match (args.next(), args.next()) {
+            (Some(start), Some(end)) => cmd.range = Some((parse_int(start)?, parse_int(end)?)),
+            (None, None) => return Ok(cmd),
+            _ => return Err(CmdErr::SyntaxErr),
+        }
+        if let Some(unit) = args.next() {
+            cmd.unit = parse_bit_unit(unit)?;
+        }
+        if args.next().is_some() {
+            return Err(CmdErr::SyntaxErr);
+        }
+
+        Ok(cmd)
+    }
+}
+
+// cmd bitpos
+impl CmdExecutor for BitPos {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.bitpos(&self.key, self.bit, self.start, self.end, self.unit) {
+            Ok(pos) => RespFrame::Integer(pos),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for BitPos {
+    type Error = CmdErr;
+
+    // BITPOS key bit [start [end [BYTE | BIT]]]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["bitpos"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key, bit) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(bit)) => {
+                let bit = match parse_int(bit) {
+                    Ok(0) => false,
+                    Ok(1) => true,
+                    _ => {
+                        return Err(CmdErr::Other(
+                            "The bit argument must be 1 or 0.".to_string(),
+                        ))
+                    }
+                };
+                (String::from_utf8(key.0)?, bit)
+            }
+            _ => return Err(CmdErr::InvalidArg("Invalid key or bit.".to_string())),
+        };
+
+        let mut cmd = BitPos {
+            key,
+            bit,
+            start: None,
+            end: None,
+            unit: BitUnit::Byte,
+        };
+        if let Some(start) = args.next() {
+            cmd.start = Some(parse_int(start)?);
+        }
+        if let Some(end) = args.next() {
+            cmd.end = Some(parse_int(end)?);
+        }
+        if let Some(unit) = args.next() {
+            cmd.unit = parse_bit_unit(unit)?;
+        }
+        if args.next().is_some() {
+            return Err(CmdErr::SyntaxErr);
+        }
+
+        Ok(cmd)
+    }
+}
+
+// cmd bitop
+impl CmdExecutor for BitOp {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.bitop(self.op, self.dest, &self.keys) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for BitOp {
+    type Error = CmdErr;
+
+    // BITOP <AND | OR | XOR | NOT | DIFF | DIFF1 | ANDOR | ONE> destkey key [key ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["bitop"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let name = parse_opt(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+        let op = match name.as_str() {
+            "AND" => BitOperator::And,
+            "OR" => BitOperator::Or,
+            "XOR" => BitOperator::Xor,
+            "NOT" => BitOperator::Not,
+            "DIFF" => BitOperator::Diff,
+            "DIFF1" => BitOperator::Diff1,
+            "ANDOR" => BitOperator::AndOr,
+            "ONE" => BitOperator::One,
+            _ => return Err(CmdErr::SyntaxErr),
+        };
+
+        let mut keys = args
+            .map(|arg| match arg {
+                RespFrame::BulkString(key) => Ok(String::from_utf8(key.0)?),
+                _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+            })
+            .collect::<Result<Vec<String>, CmdErr>>()?;
+        let dest = keys.remove(0);
+
+        match op {
+            BitOperator::Not if keys.len() != 1 => Err(CmdErr::Other(
+                "BITOP NOT must be called with a single source key.".to_string(),
+            )),
+            BitOperator::Diff | BitOperator::Diff1 | BitOperator::AndOr if keys.len() < 2 => {
+                Err(CmdErr::Other(format!(
+                    "BITOP {} must be called with at least two source keys.",
+                    name
+                )))
+            }
+            _ => Ok(BitOp { op, dest, keys }),
+        }
+    }
+}
+
+// Bit offsets are limited to the bits of a string of the maximum allowed size.
+fn parse_bit_offset(frame: RespFrame) -> Result<usize, CmdErr> {
+    match parse_int(frame) {
+        Ok(offset) if offset >= 0 && (offset as usize) < MAX_STRING_LEN * 8 => Ok(offset as usize),
+        _ => Err(CmdErr::Other(
+            "bit offset is not an integer or out of range".to_string(),
+        )),
+    }
+}
+
+fn parse_bit_unit(frame: RespFrame) -> Result<BitUnit, CmdErr> {
+    match parse_opt(frame)?.as_str() {
+        "BYTE" => Ok(BitUnit::Byte),
+        "BIT" => Ok(BitUnit::Bit),
+        _ => Err(CmdErr::SyntaxErr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BackendErr, BulkString, RespDecode};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_setbit_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nsetbit\r\n$3\r\nkey\r\n$2\r\n10\r\n$1\r\n1\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: SetBit = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.offset, 10);
+        assert!(cmd.value);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nsetbit\r\n$3\r\nkey\r\n$2\r\n-1\r\n$1\r\n1\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<SetBit, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nsetbit\r\n$3\r\nkey\r\n$1\r\n0\r\n$1\r\n2\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<SetBit, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitcount_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$8\r\nbitcount\r\n$3\r\nkey\r\n$1\r\n5\r\n$2\r\n30\r\n$3\r\nbit\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: BitCount = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.range, Some((5, 30)));
+        assert_eq!(cmd.unit, BitUnit::Bit);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$8\r\nbitcount\r\n$3\r\nkey\r\n$1\r\n5\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<BitCount, CmdErr> = frame.try_into();
+        assert!(matches!(ret, Err(CmdErr::SyntaxErr)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitop_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nbitop\r\n$3\r\nand\r\n$4\r\ndest\r\n$2\r\nk1\r\n$2\r\nk2\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: BitOp = frame.try_into()?;
+        assert_eq!(cmd.op, BitOperator::And);
+        assert_eq!(cmd.dest, "dest");
+        assert_eq!(cmd.keys, vec!["k1".to_string(), "k2".to_string()]);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nbitop\r\n$3\r\nNOT\r\n$4\r\ndest\r\n$2\r\nk1\r\n$2\r\nk2\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<BitOp, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nbitop\r\n$4\r\ndiff\r\n$4\r\ndest\r\n$2\r\nk1\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<BitOp, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bit_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = SetBit {
+            key: "mykey".to_string(),
+            offset: 7,
+            value: true,
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(0));
+
+        let cmd = SetBit {
+            key: "mykey".to_string(),
+            offset: 7,
+            value: false,
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.get("mykey")?, Some(BulkString::new([0u8])));
+
+        let cmd = GetBit {
+            key: "mykey".to_string(),
+            offset: 100,
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(0));
+
+        backend.set("mykey".to_string(), BulkString::from("foobar"));
+        let count = |range, unit| {
+            BitCount {
+                key: "mykey".to_string(),
+                range,
+                unit,
+            }
+            .exec(&backend)
+        };
+        assert_eq!(count(None, BitUnit::Byte), RespFrame::Integer(26));
+        assert_eq!(count(Some((0, 0)), BitUnit::Byte), RespFrame::Integer(4));
+        assert_eq!(count(Some((1, 1)), BitUnit::Byte), RespFrame::Integer(6));
+        assert_eq!(count(Some((1, 1)), BitUnit::Bit), RespFrame::Integer(1));
+        assert_eq!(count(Some((5, 30)), BitUnit::Bit), RespFrame::Integer(17));
+        assert_eq!(count(Some((0, -1)), BitUnit::Bit), RespFrame::Integer(26));
+        assert_eq!(count(Some((8, 15)), BitUnit::Bit), RespFrame::Integer(6));
+        assert_eq!(count(Some((16, 21)), BitUnit::Bit), RespFrame::Integer(4));
+        assert_eq!(count(Some((-2, -1)), BitUnit::Byte), RespFrame::Integer(7));
+        assert_eq!(count(Some((-1, -2)), BitUnit::Byte), RespFrame::Integer(0));
+
+        backend.set("mykey".to_string(), BulkString::new([0xffu8, 0xf0, 0x00]));
+        let pos = |bit, start, end, unit| {
+            BitPos {
+                key: "mykey".to_string(),
+                bit,
+                start,
+                end,
+                unit,
+            }
+            .exec(&backend)
+        };
+        assert_eq!(
+            pos(false, None, None, BitUnit::Byte),
+            RespFrame::Integer(12)
+        );
+        assert_eq!(
+            pos(true, Some(2), None, BitUnit::Byte),
+            RespFrame::Integer(-1)
+        );
+        assert_eq!(
+            pos(true, Some(7), Some(15), BitUnit::Bit),
+            RespFrame::Integer(7)
+        );
+        assert_eq!(
+            pos(true, Some(12), Some(-1), BitUnit::Bit),
+            RespFrame::Integer(-1)
+        );
+
+        backend.set("mykey".to_string(), BulkString::new([0xffu8, 0xff]));
+        assert_eq!(
+            pos(false, None, None, BitUnit::Byte),
+            RespFrame::Integer(16)
+        );
+        assert_eq!(
+            pos(false, Some(0), Some(-1), BitUnit::Byte),
+            RespFrame::Integer(-1)
+        );
+
+        let pos = BitPos {
+            key: "missing".to_string(),
+            bit: false,
+            start: None,
+            end: None,
+            unit: BitUnit::Byte,
+        };
+        assert_eq!(pos.exec(&backend), RespFrame::Integer(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitop_cmd() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("k1".to_string(), BulkString::new([0b1100u8, 0xff]));
+        backend.set("k2".to_string(), BulkString::new([0b1010u8]));
+        backend.set("k3".to_string(), BulkString::new([0b1001u8]));
+
+        let bitop = |op, keys: &[&str]| {
+            BitOp {
+                op,
+                dest: "dest".to_string(),
+                keys: keys.iter().map(|k| k.to_string()).collect(),
+            }
+            .exec(&backend)
+        };
+
+        let cases = [
+            (BitOperator::And, vec![0b1000u8, 0x00]),
+            (BitOperator::Or, vec![0b1111, 0xff]),
+            (BitOperator::Xor, vec![0b1111, 0xff]),
+            (BitOperator::Diff, vec![0b0100, 0xff]),
+            (BitOperator::Diff1, vec![0b0011, 0x00]),
+            (BitOperator::AndOr, vec![0b1000, 0x00]),
+            (BitOperator::One, vec![0b0111, 0xff]),
+        ];
+        for (op, expected) in cases {
+            assert_eq!(bitop(op, &["k1", "k2", "k3"]), RespFrame::Integer(2));
+            assert_eq!(backend.get("dest")?, Some(BulkString::new(expected)));
+        }
+
+        assert_eq!(bitop(BitOperator::Not, &["k2"]), RespFrame::Integer(1));
+        assert_eq!(backend.get("dest")?, Some(BulkString::new([!0b1010u8])));
+
+        // an empty result deletes the destination
+        assert_eq!(
+            bitop(BitOperator::Or, &["missing1", "missing2"]),
+            RespFrame::Integer(0)
+        );
+        assert_eq!(backend.get("dest")?, None);
+
+        backend.lpush("list".to_string(), vec![BulkString::from("a").into()])?;
+        assert_eq!(
+            bitop(BitOperator::And, &["k1", "list"]),
+            BackendErr::WrongType.into()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+// blocking list cmd
+use std::time::Duration;
+
+use crate::cmd::list::{parse_key, parse_list_end, parse_mpop_count, parse_mpop_keys};
+use crate::cmd::{
+    extract_args, parse_float, parse_opt, validate_cmd, validate_cmd_min, BLMPop, BLMove, BLPop,
+    BRPop, CmdErr, CmdExecutor,
+};
+use crate::{Array, Backend, BulkString, ListEnd, Null, NullArray, RespFrame, ServeFn};
+
+// A command that waits on its keys until one of them can serve it.
+pub(super) trait BlockingCmd {
+    // Keys in the order they are tried, the timeout and how the command is served from a key.
+    fn into_parts(self) -> (Vec<String>, Option<Duration>, ServeFn);
+
+    // Reply when the timeout elapses before the command could be served.
+    fn timeout_reply() -> RespFrame {
+        RespFrame::NullArray(NullArray)
+    }
+}
+
+// Block until the command is served or its timeout elapses.
+pub(super) async fn block<C: BlockingCmd>(cmd: C, backend: &Backend) -> RespFrame {
+    let (keys, timeout, serve) = cmd.into_parts();
+    match backend.block_on(keys, timeout, serve).await {
+        Some(reply) => reply,
+        None => C::timeout_reply(),
+    }
+}
+
+// Without blocking, the command behaves as if its timeout elapsed right away.
+fn serve_now<C: BlockingCmd>(cmd: C, backend: &Backend) -> RespFrame {
+    let (keys, _, mut serve) = cmd.into_parts();
+    match keys.iter().find_map(|key| serve(backend, key)) {
+        Some(reply) => reply,
+        None => C::timeout_reply(),
+    }
+}
+
+// Pop from a list, replied as the key with its value or, given a count, with its values.
+fn pop_serve(end: ListEnd, count: Option<usize>) -> ServeFn {
+    Box::new(move |backend: &Backend, key: &str| {
+        let keys = [key.to_string()];
+        match backend.lmpop(&keys, end, count.unwrap_or(1)) {
+            Ok(Some((key, mut values))) => {
+                let values = match count {
+                    Some(_) => Array::new(values).into(),
+                    None => values.pop()?,
+                };
+                Some(Array::new([BulkString::from(key).into(), values]).into())
+            }
+            Ok(None) => None,
+            Err(e) => Some(e.into()),
+        }
+    })
+}
+
+// cmd blpop
+impl BlockingCmd for BLPop {
+    fn into_parts(self) -> (Vec<String>, Option<Duration>, ServeFn) {
+        (self.keys, self.timeout, pop_serve(ListEnd::Left, None))
+    }
+}
+
+impl CmdExecutor for BLPop {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        serve_now(self, backend)
+    }
+}
+
+impl TryFrom<Array> for BLPop {
+    type Error = CmdErr;
+
+    // BLPOP key [key ...] timeout
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_pop_args(value, "blpop")?;
+        Ok(BLPop { keys, timeout })
+    }
+}
+
+// cmd brpop
+impl BlockingCmd for BRPop {
+    fn into_parts(self) -> (Vec<String>, Option<Duration>, ServeFn) {
+        (self.keys, self.timeout, pop_serve(ListEnd::Right, None))
+    }
+}
+
+impl CmdExecutor for BRPop {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        serve_now(self, backend)
+    }
+}
+
+impl TryFrom<Array> for BRPop {
+    type Error = CmdErr;
+
+    // BRPOP key [key ...] timeout
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_pop_args(value, "brpop")?;
+        Ok(BRPop { keys, timeout })
+    }
+}
+
+// cmd blmove
+impl BlockingCmd for BLMove {
+    fn into_parts(self) -> (Vec<String>, Option<Duration>, ServeFn) {
+        let (dest, from, to) = (self.dest, self.from, self.to);
+        let serve = Box::new(move |backend: &Backend, key: &str| {
+            match backend.lmove(key, &dest, from, to) {
+                Ok(value) => value,
+                Err(e) => Some(e.into()),
+            }
+        });
+        (vec![self.src], self.timeout, serve)
+    }
+
+    // a single moved element, so the same null as LMOVE and RPOPLPUSH on an empty list
+    fn timeout_reply() -> RespFrame {
+        RespFrame::Null(Null)
+    }
+}
+
+impl CmdExecutor for BLMove {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        serve_now(self, backend)
+    }
+}
+
+impl TryFrom<Array> for BLMove {
+    type Error = CmdErr;
+
+    // BLMOVE source destination <LEFT | RIGHT> <LEFT | RIGHT> timeout
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["blmove"], 5)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let src = parse_key(args.next())?;
+        let dest = parse_key(args.next())?;
+        let from = parse_list_end(args.next())?;
+        let to = parse_list_end(args.next())?;
+        let timeout = parse_timeout(args.next())?;
+        Ok(BLMove {
+            src,
+            dest,
+            from,
+            to,
+            timeout,
+        })
+    }
+}
+
+// cmd blmpop
+impl BlockingCmd for BLMPop {
+    fn into_parts(self) -> (Vec<String>, Option<Duration>, ServeFn) {
+        (
+            self.keys,
+            self.timeout,
+            pop_serve(self.end, Some(self.count)),
+        )
+    }
+}
+
+impl CmdExecutor for BLMPop {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        serve_now(self, backend)
+    }
+}
+
+impl TryFrom<Array> for BLMPop {
+    type Error = CmdErr;
+
+    // BLMPOP timeout numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["blmpop"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let timeout = parse_timeout(args.next())?;
+        let (keys, end) = parse_mpop_keys(&mut args)?;
+        let count = match args.next().map(parse_opt).transpose()?.as_deref() {
+            None => 1,
+            Some("COUNT") => parse_mpop_count(args.next())?,
+            _ => return Err(CmdErr::SyntaxErr),
+        };
+        if args.next().is_some() {
+            return Err(CmdErr::SyntaxErr);
+        }
+
+        Ok(BLMPop {
+            keys,
+            end,
+            count,
+            timeout,
+        })
+    }
+}
+
+// key [key ...] timeout
+fn parse_pop_args(
+    value: Array,
+    name: &'static str,
+) -> Result<(Vec<String>, Option<Duration>), CmdErr> {
+    validate_cmd_min(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?;
+    let timeout = parse_timeout(args.pop())?;
+    let keys = args
+        .into_iter()
+        .map(|key| parse_key(Some(key)))
+        .collect::<Result<_, _>>()?;
+    Ok((keys, timeout))
+}
+
+// Timeout in seconds with a fractional part, 0 blocks forever.
+fn parse_timeout(frame: Option<RespFrame>) -> Result<Option<Duration>, CmdErr> {
+    let not_float = || CmdErr::Other("timeout is not a float or out of range".to_string());
+
+    let timeout = parse_float(frame.ok_or(CmdErr::SyntaxErr)?).map_err(|_| not_float())?;
+    if timeout < 0.0 {
+        return Err(CmdErr::Other("timeout is negative".to_string()));
+    }
+    if timeout == 0.0 {
+        return Ok(None);
+    }
+
+    Duration::try_from_secs_f64(timeout)
+        .map(Some)
+        .map_err(|_| not_float())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::LMove;
+    use crate::BackendErr;
+    use anyhow::Result;
+
+    fn array(args: &[&str]) -> Array {
+        Array::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    fn frames(values: &[&str]) -> RespFrame {
+        Array::new(
+            values
+                .iter()
+                .map(|v| BulkString::from(*v).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_blocking_from_array() -> Result<()> {
+        let cmd = BLPop::try_from(array(&["blpop", "a", "b", "0.5"]))?;
+        assert_eq!(cmd.keys, ["a", "b"]);
+        assert_eq!(cmd.timeout, Some(Duration::from_millis(500)));
+
+        let cmd = BRPop::try_from(array(&["brpop", "a", "0"]))?;
+        assert_eq!(cmd.timeout, None);
+
+        let cmd = BLMPop::try_from(array(&[
+            "blmpop", "1", "2", "a", "b", "right", "count", "2",
+        ]))?;
+        assert_eq!(cmd.keys, ["a", "b"]);
+        assert_eq!((cmd.end, cmd.count), (ListEnd::Right, 2));
+        assert_eq!(cmd.timeout, Some(Duration::from_secs(1)));
+
+        let ret = BLPop::try_from(array(&["blpop", "a", "-1"]));
+        assert_eq!(ret.unwrap_err().to_string(), "ERR timeout is negative");
+        let ret = BLMove::try_from(array(&["blmove", "a", "b", "left", "right", "x"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR timeout is not a float or out of range"
+        );
+        assert!(BLPop::try_from(array(&["blpop", "1"])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking_cmd_without_waiting() -> Result<()> {
+        let backend = Backend::new();
+        backend.rpush("b".to_string(), vec![BulkString::from("v").into()])?;
+
+        let ret = BLPop::try_from(array(&["blpop", "a", "b", "1"]))?.exec(&backend);
+        assert_eq!(ret, frames(&["b", "v"]));
+        let ret = BLPop::try_from(array(&["blpop", "a", "b", "1"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::NullArray(NullArray));
+
+        backend.set("string".to_string(), BulkString::from("v"));
+        let ret = BRPop::try_from(array(&["brpop", "string", "1"]))?.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blocking_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = BLPop::try_from(array(&["blpop", "queue", "0.01"]))?;
+        assert_eq!(block(cmd, &backend).await, RespFrame::NullArray(NullArray));
+        // BLMOVE times out with the same null LMOVE replies on an empty list
+        let cmd = LMove::try_from(array(&["lmove", "queue", "dest", "left", "left"]))?;
+        let lmove = cmd.exec(&backend);
+        let cmd = BLMove::try_from(array(&["blmove", "queue", "dest", "left", "left", "0.01"]))?;
+        assert_eq!(block(cmd, &backend).await, lmove);
+        assert_eq!(lmove, RespFrame::Null(Null));
+
+        let (client, args) = (
+            backend.clone(),
+            ["blmove", "queue", "processing", "left", "right", "0"],
+        );
+        let cmd = BLMove::try_from(array(&args))?;
+        let blmove = tokio::spawn(async move { block(cmd, &client).await });
+        let (client, args) = (
+            backend.clone(),
+            ["blmpop", "0", "1", "processing", "left", "count", "5"],
+        );
+        let cmd = BLMPop::try_from(array(&args))?;
+        let blmpop = tokio::spawn(async move { block(cmd, &client).await });
+        tokio::task::yield_now().await;
+
+        backend.rpush("queue".to_string(), vec![BulkString::from("job").into()])?;
+        assert_eq!(blmove.await?, BulkString::from("job").into());
+        assert_eq!(
+            blmpop.await?,
+            Array::new([BulkString::from("processing").into(), frames(&["job"])]).into()
+        );
+        assert_eq!(
+            backend.exists(&["queue".to_string(), "processing".to_string()]),
+            0
+        );
+
+        // a list renamed onto the key wakes BLPOP like a push does
+        let (client, cmd) = (
+            backend.clone(),
+            BLPop::try_from(array(&["blpop", "queue", "0"]))?,
+        );
+        let blpop = tokio::spawn(async move { block(cmd, &client).await });
+        tokio::task::yield_now().await;
+        backend.rpush("staging".to_string(), vec![BulkString::from("job").into()])?;
+        backend.rename("staging", "queue".to_string())?;
+        assert_eq!(blpop.await?, frames(&["queue", "job"]));
+
+        Ok(())
+    }
+}
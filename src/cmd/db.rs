@@ -0,0 +1,178 @@
+// db cmd
+use crate::{
+    cmd::{
+        extract_args, parse_db_index, validate_cmd, CmdErr, CmdExecutor, Move, Select, SwapDb,
+        RESP_OK,
+    },
+    Array, Backend, RespFrame,
+};
+
+// cmd select
+impl CmdExecutor for Select {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.select(self.db) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for Select {
+    type Error = CmdErr;
+
+    // SELECT index
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["select"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let db = parse_db_index(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+        Ok(Select { db })
+    }
+}
+
+// cmd swapdb
+impl CmdExecutor for SwapDb {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.swap_db(self.db1, self.db2) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SwapDb {
+    type Error = CmdErr;
+
+    // SWAPDB index1 index2
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["swapdb"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut parse = |nth: &str| match parse_db_index(args.next().ok_or(CmdErr::SyntaxErr)?) {
+            Err(CmdErr::NotInteger) => Err(CmdErr::Other(format!("invalid {} DB index", nth))),
+            ret => ret,
+        };
+        let db1 = parse("first")?;
+        let db2 = parse("second")?;
+
+        Ok(SwapDb { db1, db2 })
+    }
+}
+
+// cmd move
+impl CmdExecutor for Move {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.move_key(&self.key, self.db) {
+            Ok(moved) => RespFrame::Integer(moved as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for Move {
+    type Error = CmdErr;
+
+    // MOVE key db
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["move"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            _ => return Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        };
+        let db = parse_db_index(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+
+        Ok(Move { key, db })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BackendErr, BulkString, RespDecode, SimpleError};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_select_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$6\r\nselect\r\n$1\r\n3\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: Select = frame.try_into()?;
+        assert_eq!(cmd.db, 3);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$6\r\nselect\r\n$2\r\n-1\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<Select, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_swapdb_from_array() -> Result<()> {
+        let parse = |args: &[&str]| {
+            let mut frames = vec![RespFrame::from(BulkString::from("swapdb"))];
+            frames.extend(args.iter().map(|arg| BulkString::from(*arg).into()));
+            SwapDb::try_from(Array::new(frames))
+        };
+
+        let cmd = parse(&["0", "1"])?;
+        assert_eq!((cmd.db1, cmd.db2), (0, 1));
+
+        let err = |ret: Result<SwapDb, CmdErr>| RespFrame::from(ret.unwrap_err());
+        assert_eq!(
+            err(parse(&["a", "1"])),
+            SimpleError::new("ERR invalid first DB index").into()
+        );
+        assert_eq!(
+            err(parse(&["0", "b"])),
+            SimpleError::new("ERR invalid second DB index").into()
+        );
+        assert_eq!(
+            err(parse(&["0", "-1"])),
+            SimpleError::new("ERR DB index is out of range").into()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_db_cmd() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::from("1"));
+
+        assert_eq!(
+            Select { db: 16 }.exec(&backend),
+            BackendErr::DbIndexOutOfRange.into()
+        );
+        assert_eq!(Select { db: 1 }.exec(&backend), RESP_OK.clone());
+        assert_eq!(backend.get("a")?, None);
+
+        let move_key = |db| {
+            Move {
+                key: "a".to_string(),
+                db,
+            }
+            .exec(&backend)
+        };
+        assert_eq!(Select { db: 0 }.exec(&backend), RESP_OK.clone());
+        assert_eq!(move_key(0), BackendErr::SameObject.into());
+        assert_eq!(move_key(1), RespFrame::Integer(1));
+        assert_eq!(move_key(1), RespFrame::Integer(0));
+        assert_eq!(backend.with_db(1)?.get("a")?, Some(BulkString::from("1")));
+
+        assert_eq!(SwapDb { db1: 1, db2: 0 }.exec(&backend), RESP_OK.clone());
+        assert_eq!(backend.get("a")?, Some(BulkString::from("1")));
+        assert_eq!(
+            SwapDb { db1: 0, db2: 16 }.exec(&backend),
+            BackendErr::DbIndexOutOfRange.into()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,417 @@
+// expire cmd
+use crate::cmd::{
+    extract_args, parse_int, parse_opt, validate_cmd, validate_cmd_min, CmdErr, CmdExecutor,
+    Expire, ExpireAt, ExpireTime, PExpire, PExpireAt, PExpireTime, PTtl, Persist, Ttl,
+};
+use crate::{now_ms, Array, Backend, ExpireOpt, RespFrame};
+
+// cmd expire
+impl CmdExecutor for Expire {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let at = self
+            .seconds
+            .checked_mul(1000)
+            .and_then(|ms| ms.checked_add(now_ms()));
+        expire_generic(backend, &self.key, at, self.opt, "expire")
+    }
+}
+
+impl TryFrom<Array> for Expire {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, seconds, opt) = parse_expire_args(value, "expire")?;
+        Ok(Expire { key, seconds, opt })
+    }
+}
+
+// cmd pexpire
+impl CmdExecutor for PExpire {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let at = self.milliseconds.checked_add(now_ms());
+        expire_generic(backend, &self.key, at, self.opt, "pexpire")
+    }
+}
+
+impl TryFrom<Array> for PExpire {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, milliseconds, opt) = parse_expire_args(value, "pexpire")?;
+        Ok(PExpire {
+            key,
+            milliseconds,
+            opt,
+        })
+    }
+}
+
+// cmd expireat
+impl CmdExecutor for ExpireAt {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let at = self.timestamp.checked_mul(1000);
+        expire_generic(backend, &self.key, at, self.opt, "expireat")
+    }
+}
+
+impl TryFrom<Array> for ExpireAt {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, timestamp, opt) = parse_expire_args(value, "expireat")?;
+        Ok(ExpireAt {
+            key,
+            timestamp,
+            opt,
+        })
+    }
+}
+
+// cmd pexpireat
+impl CmdExecutor for PExpireAt {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        expire_generic(
+            backend,
+            &self.key,
+            Some(self.timestamp),
+            self.opt,
+            "pexpireat",
+        )
+    }
+}
+
+impl TryFrom<Array> for PExpireAt {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, timestamp, opt) = parse_expire_args(value, "pexpireat")?;
+        Ok(PExpireAt {
+            key,
+            timestamp,
+            opt,
+        })
+    }
+}
+
+// cmd ttl
+impl CmdExecutor for Ttl {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        ttl_generic(backend, &self.key, false)
+    }
+}
+
+impl TryFrom<Array> for Ttl {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value, "ttl")?;
+        Ok(Ttl { key })
+    }
+}
+
+// cmd pttl
+impl CmdExecutor for PTtl {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        ttl_generic(backend, &self.key, true)
+    }
+}
+
+impl TryFrom<Array> for PTtl {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value, "pttl")?;
+        Ok(PTtl { key })
+    }
+}
+
+// cmd expiretime
+impl CmdExecutor for ExpireTime {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.expire_time(&self.key) {
+            Some(Some(at)) => RespFrame::Integer(at / 1000),
+            Some(None) => RespFrame::Integer(-1),
+            None => RespFrame::Integer(-2),
+        }
+    }
+}
+
+impl TryFrom<Array> for ExpireTime {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value, "expiretime")?;
+        Ok(ExpireTime { key })
+    }
+}
+
+// cmd pexpiretime
+impl CmdExecutor for PExpireTime {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.expire_time(&self.key) {
+            Some(Some(at)) => RespFrame::Integer(at),
+            Some(None) => RespFrame::Integer(-1),
+            None => RespFrame::Integer(-2),
+        }
+    }
+}
+
+impl TryFrom<Array> for PExpireTime {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value, "pexpiretime")?;
+        Ok(PExpireTime { key })
+    }
+}
+
+// cmd persist
+impl CmdExecutor for Persist {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.persist(&self.key) as i64)
+    }
+}
+
+impl TryFrom<Array> for Persist {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let key = parse_key_arg(value, "persist")?;
+        Ok(Persist { key })
+    }
+}
+
+fn expire_generic(
+    backend: &Backend,
+    key: &str,
+    at: Option<i64>,
+    opt: ExpireOpt,
+    name: &'static str,
+) -> RespFrame {
+    match at {
+        Some(at) => RespFrame::Integer(backend.expire_at(key, at, opt) as i64),
+        None => CmdErr::Other(format!("invalid expire time in '{}' command", name)).into(),
+    }
+}
+
+fn ttl_generic(backend: &Backend, key: &str, in_ms: bool) -> RespFrame {
+    match backend.expire_time(key) {
+        Some(Some(at)) => {
+            let ttl = (at - now_ms()).max(0);
+            RespFrame::Integer(if in_ms { ttl } else { (ttl + 500) / 1000 })
+        }
+        Some(None) => RespFrame::Integer(-1),
+        None => RespFrame::Integer(-2),
+    }
+}
+
+fn parse_key_arg(value: Array, name: &'static str) -> Result<String, CmdErr> {
+    validate_cmd(&value, &[name], 1)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    match args.next() {
+        Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.0)?),
+        _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+    }
+}
+
+fn parse_expire_args(value: Array, name: &'static str) -> Result<(String, i64, ExpireOpt), CmdErr> {
+    validate_cmd_min(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let (key, time) = match (args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(time)) => {
+            (String::from_utf8(key.0)?, parse_int(time)?)
+        }
+        _ => return Err(CmdErr::InvalidArg("Invalid key or time.".to_string())),
+    };
+
+    Ok((key, time, parse_expire_opt(args)?))
+}
+
+pub(crate) fn parse_expire_opt(args: impl Iterator<Item = RespFrame>) -> Result<ExpireOpt, CmdErr> {
+    let mut opt = ExpireOpt::default();
+    for arg in args {
+        match parse_opt(arg)?.as_str() {
+            "NX" => opt.nx = true,
+            "XX" => opt.xx = true,
+            "GT" => opt.gt = true,
+            "LT" => opt.lt = true,
+            other => return Err(CmdErr::Other(format!("Unsupported option {}", other))),
+        }
+    }
+
+    if opt.nx && (opt.xx || opt.gt || opt.lt) {
+        return Err(CmdErr::Other(
+            "NX and XX, GT or LT options at the same time are not compatible".to_string(),
+        ));
+    }
+    if opt.gt && opt.lt {
+        return Err(CmdErr::Other(
+            "GT and LT options at the same time are not compatible".to_string(),
+        ));
+    }
+
+    Ok(opt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespDecode, SimpleError};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_expire_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n$2\r\nxx\r\n$2\r\ngt\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+
+        let cmd: Expire = frame.try_into()?;
+        assert_eq!(cmd.key, "hello");
+        assert_eq!(cmd.seconds, 10);
+        assert_eq!(
+            cmd.opt,
+            ExpireOpt {
+                xx: true,
+                gt: true,
+                ..Default::default()
+            }
+        );
+
+        buf.extend_from_slice(
+            b"*5\r\n$7\r\npexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n$2\r\nNX\r\n$2\r\nGT\r\n",
+        );
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<PExpire, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        buf.extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$3\r\nten\r\n");
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<Expire, CmdErr> = frame.try_into();
+        assert!(matches!(ret, Err(CmdErr::NotInteger)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ttl_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$3\r\nttl\r\n$5\r\nhello\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+
+        let cmd: Ttl = frame.try_into()?;
+        assert_eq!(cmd.key, "hello");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: 100,
+            opt: ExpireOpt::default(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+
+        backend.set("hello".to_string(), BulkString::from("world"));
+
+        let cmd = Ttl {
+            key: "hello".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(-1));
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: 100,
+            opt: ExpireOpt::default(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+
+        let cmd = Ttl {
+            key: "hello".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(100));
+
+        let cmd = PTtl {
+            key: "hello".to_string(),
+        };
+        let RespFrame::Integer(ttl) = cmd.exec(&backend) else {
+            panic!("pttl must reply an integer");
+        };
+        assert!(ttl > 99_000 && ttl <= 100_000);
+
+        let cmd = Persist {
+            key: "hello".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+
+        let cmd = PExpireAt {
+            key: "hello".to_string(),
+            timestamp: 4_102_444_800_000,
+            opt: ExpireOpt::default(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+
+        let cmd = ExpireTime {
+            key: "hello".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(4_102_444_800));
+
+        let cmd = PExpireTime {
+            key: "hello".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(4_102_444_800_000));
+
+        // the expire time in seconds is truncated, not rounded
+        backend.expire_at("hello", 4_102_444_800_999, ExpireOpt::default());
+        let cmd = ExpireTime {
+            key: "hello".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(4_102_444_800));
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: i64::MAX,
+            opt: ExpireOpt::default(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(
+            ret,
+            SimpleError::new("ERR invalid expire time in 'expire' command").into()
+        );
+
+        let cmd = ExpireAt {
+            key: "hello".to_string(),
+            timestamp: 1,
+            opt: ExpireOpt::default(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+
+        let cmd = Ttl {
+            key: "hello".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(-2));
+
+        Ok(())
+    }
+}
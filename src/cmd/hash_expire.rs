@@ -0,0 +1,502 @@
+// hash field expire cmd
+use crate::cmd::{
+    extract_args, parse_int, parse_opt, validate_cmd_min, CmdErr, CmdExecutor, HExpire, HGetEx,
+    HPExpire, HPersist, HSetEx, HTtl, SetExpire,
+};
+use crate::{now_ms, Array, Backend, ExpireOpt, FieldCond, Null, RespFrame};
+
+// cmd hexpire
+impl CmdExecutor for HExpire {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let at = self
+            .seconds
+            .checked_mul(1000)
+            .and_then(|ms| ms.checked_add(now_ms()));
+        hexpire_generic(backend, &self.key, &self.fields, at, self.opt, "hexpire")
+    }
+}
+
+impl TryFrom<Array> for HExpire {
+    type Error = CmdErr;
+
+    // HEXPIRE key seconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, seconds, opt, fields) = parse_hexpire_args(value, "hexpire")?;
+        Ok(HExpire {
+            key,
+            seconds,
+            opt,
+            fields,
+        })
+    }
+}
+
+// cmd hpexpire
+impl CmdExecutor for HPExpire {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let at = self.milliseconds.checked_add(now_ms());
+        hexpire_generic(backend, &self.key, &self.fields, at, self.opt, "hpexpire")
+    }
+}
+
+impl TryFrom<Array> for HPExpire {
+    type Error = CmdErr;
+
+    // HPEXPIRE key milliseconds [NX | XX | GT | LT] FIELDS numfields field [field ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, milliseconds, opt, fields) = parse_hexpire_args(value, "hpexpire")?;
+        Ok(HPExpire {
+            key,
+            milliseconds,
+            opt,
+            fields,
+        })
+    }
+}
+
+// cmd httl
+impl CmdExecutor for HTtl {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let now = now_ms();
+        field_reply(
+            backend.hexpire_time(&self.key, &self.fields),
+            |at| match at {
+                Some(Some(at)) => ((at - now).max(0) + 999) / 1000,
+                Some(None) => -1,
+                None => -2,
+            },
+        )
+    }
+}
+
+impl TryFrom<Array> for HTtl {
+    type Error = CmdErr;
+
+    // HTTL key FIELDS numfields field [field ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields_args(value, "httl")?;
+        Ok(HTtl { key, fields })
+    }
+}
+
+// cmd hpersist
+impl CmdExecutor for HPersist {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        field_reply(
+            backend.hpersist(&self.key, &self.fields),
+            |persisted| match persisted {
+                Some(true) => 1,
+                Some(false) => -1,
+                None => -2,
+            },
+        )
+    }
+}
+
+impl TryFrom<Array> for HPersist {
+    type Error = CmdErr;
+
+    // HPERSIST key FIELDS numfields field [field ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields_args(value, "hpersist")?;
+        Ok(HPersist { key, fields })
+    }
+}
+
+// cmd hgetex
+impl CmdExecutor for HGetEx {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let ttl = match self.expire.map(|expire| expire.resolve(now_ms())) {
+            Some(Some(at)) => Some(Some(at)),
+            Some(None) => {
+                return CmdErr::Other("invalid expire time in 'hgetex' command".to_string()).into()
+            }
+            None if self.persist => Some(None),
+            None => None,
+        };
+
+        match backend.hgetex(&self.key, &self.fields, ttl) {
+            Ok(values) => Array::new(
+                values
+                    .into_iter()
+                    .map(|value| value.unwrap_or(RespFrame::Null(Null)))
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HGetEx {
+    type Error = CmdErr;
+
+    // HGETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+    //   PXAT unix-time-milliseconds | PERSIST] FIELDS numfields field [field ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["hgetex"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+
+        let (mut expire, mut persist) = (None, false);
+        loop {
+            match parse_opt(args.next().ok_or_else(missing_fields)?)?.as_str() {
+                "FIELDS" => break,
+                "PERSIST" if expire.is_none() && !persist => persist = true,
+                name @ ("EX" | "PX" | "EXAT" | "PXAT") if expire.is_none() && !persist => {
+                    let time = args.next().ok_or(CmdErr::SyntaxErr)?;
+                    expire = Some(SetExpire::parse(name, time, "hgetex")?);
+                }
+                _ => return Err(missing_fields()),
+            }
+        }
+
+        Ok(HGetEx {
+            key,
+            expire,
+            persist,
+            fields: parse_fields(args)?,
+        })
+    }
+}
+
+// cmd hsetex
+impl CmdExecutor for HSetEx {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let ttl = match self.expire.map(|expire| expire.resolve(now_ms())) {
+            Some(Some(at)) => Some(Some(at)),
+            Some(None) => {
+                return CmdErr::Other("invalid expire time in 'hsetex' command".to_string()).into()
+            }
+            None if self.keep_ttl => None,
+            None => Some(None),
+        };
+
+        match backend.hsetex(self.key, self.pairs, self.cond, ttl) {
+            Ok(set) => RespFrame::Integer(set as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HSetEx {
+    type Error = CmdErr;
+
+    // HSETEX key [FNX | FXX] [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+    //   PXAT unix-time-milliseconds | KEEPTTL] FIELDS numfields field value [field value ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["hsetex"], 5)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+
+        let (mut cond, mut expire, mut keep_ttl) = (FieldCond::Any, None, false);
+        loop {
+            match parse_opt(args.next().ok_or_else(missing_fields)?)?.as_str() {
+                "FIELDS" => break,
+                "FNX" if cond == FieldCond::Any => cond = FieldCond::Fnx,
+                "FXX" if cond == FieldCond::Any => cond = FieldCond::Fxx,
+                "KEEPTTL" if expire.is_none() && !keep_ttl => keep_ttl = true,
+                name @ ("EX" | "PX" | "EXAT" | "PXAT") if expire.is_none() && !keep_ttl => {
+                    let time = args.next().ok_or(CmdErr::SyntaxErr)?;
+                    expire = Some(SetExpire::parse(name, time, "hsetex")?);
+                }
+                _ => return Err(missing_fields()),
+            }
+        }
+
+        let num = parse_numfields(args.next())?;
+        if args.len() != num.saturating_mul(2) {
+            return Err(numfields_mismatch());
+        }
+        let mut pairs = Vec::with_capacity(num);
+        while let (Some(field), Some(value)) = (args.next(), args.next()) {
+            pairs.push((parse_field(field)?, value));
+        }
+
+        Ok(HSetEx {
+            key,
+            cond,
+            expire,
+            keep_ttl,
+            pairs,
+        })
+    }
+}
+
+fn hexpire_generic(
+    backend: &Backend,
+    key: &str,
+    fields: &[String],
+    at: Option<i64>,
+    opt: ExpireOpt,
+    name: &'static str,
+) -> RespFrame {
+    let Some(at) = at else {
+        return CmdErr::Other(format!("invalid expire time in '{}' command", name)).into();
+    };
+
+    field_reply(backend.hexpire_at(key, fields, at, opt), |status| {
+        status as i64
+    })
+}
+
+// Reply an integer status for every field, or the error of the whole command.
+fn field_reply<T, E: Into<RespFrame>>(
+    ret: Result<Vec<T>, E>,
+    status: impl Fn(T) -> i64,
+) -> RespFrame {
+    match ret {
+        Ok(items) => Array::new(
+            items
+                .into_iter()
+                .map(|item| RespFrame::Integer(status(item)))
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into(),
+        Err(e) => e.into(),
+    }
+}
+
+fn parse_hexpire_args(
+    value: Array,
+    name: &'static str,
+) -> Result<(String, i64, ExpireOpt, Vec<String>), CmdErr> {
+    validate_cmd_min(&value, &[name], 5)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = parse_key(args.next())?;
+    let time = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+    if time < 0 {
+        return Err(CmdErr::Other(
+            "invalid expire time, must be >= 0".to_string(),
+        ));
+    }
+
+    let mut opt = ExpireOpt::default();
+    let mut token = parse_opt(args.next().ok_or_else(missing_fields)?)?;
+    let cond = match token.as_str() {
+        "NX" => Some(&mut opt.nx),
+        "XX" => Some(&mut opt.xx),
+        "GT" => Some(&mut opt.gt),
+        "LT" => Some(&mut opt.lt),
+        _ => None,
+    };
+    if let Some(cond) = cond {
+        *cond = true;
+        token = parse_opt(args.next().ok_or_else(missing_fields)?)?;
+    }
+    if token != "FIELDS" {
+        return Err(missing_fields());
+    }
+
+    Ok((key, time, opt, parse_fields(args)?))
+}
+
+fn parse_key_fields_args(
+    value: Array,
+    name: &'static str,
+) -> Result<(String, Vec<String>), CmdErr> {
+    validate_cmd_min(&value, &[name], 4)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = parse_key(args.next())?;
+    if parse_opt(args.next().ok_or_else(missing_fields)?)? != "FIELDS" {
+        return Err(missing_fields());
+    }
+
+    Ok((key, parse_fields(args)?))
+}
+
+// Parse `numfields field [field ...]` ending the arguments.
+fn parse_fields(mut args: impl ExactSizeIterator<Item = RespFrame>) -> Result<Vec<String>, CmdErr> {
+    let num = parse_numfields(args.next())?;
+    if args.len() != num {
+        return Err(numfields_mismatch());
+    }
+
+    args.map(parse_field).collect()
+}
+
+fn parse_numfields(frame: Option<RespFrame>) -> Result<usize, CmdErr> {
+    match frame.map(parse_int) {
+        Some(Ok(num)) if num > 0 => Ok(num as usize),
+        _ => Err(CmdErr::Other(
+            "Parameter `numFields` should be greater than 0".to_string(),
+        )),
+    }
+}
+
+fn parse_key(frame: Option<RespFrame>) -> Result<String, CmdErr> {
+    match frame {
+        Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.0)?),
+        _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+    }
+}
+
+fn parse_field(frame: RespFrame) -> Result<String, CmdErr> {
+    match frame {
+        RespFrame::BulkString(field) => Ok(String::from_utf8(field.0)?),
+        _ => Err(CmdErr::InvalidArg("Invalid field.".to_string())),
+    }
+}
+
+fn missing_fields() -> CmdErr {
+    CmdErr::Other("Mandatory argument FIELDS is missing or not at the right position".to_string())
+}
+
+fn numfields_mismatch() -> CmdErr {
+    CmdErr::Other("The `numfields` parameter must match the number of arguments".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BackendErr, BulkString, RespDecode};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn frames(name: &str, args: &[&str]) -> Array {
+        let mut frames = vec![RespFrame::from(BulkString::from(name))];
+        frames.extend(args.iter().map(|arg| BulkString::from(*arg).into()));
+        Array::new(frames)
+    }
+
+    #[test]
+    fn test_hexpire_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$7\r\nhexpire\r\n$4\r\nuser\r\n$2\r\n60\r\n$2\r\nnx\r\n\
+            $6\r\nfields\r\n$1\r\n1\r\n$4\r\nflag\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: HExpire = frame.try_into()?;
+        assert_eq!(cmd.key, "user");
+        assert_eq!(cmd.seconds, 60);
+        assert!(cmd.opt.nx);
+        assert_eq!(cmd.fields, ["flag"]);
+
+        let parse = |args: &[&str]| HPExpire::try_from(frames("hpexpire", args));
+        assert!(parse(&["user", "100", "fields", "2", "a", "b"]).is_ok());
+        assert!(parse(&["user", "100", "fields", "2", "a"]).is_err());
+        assert!(parse(&["user", "100", "fields", "0", "a"]).is_err());
+        assert!(parse(&["user", "100", "nx", "xx", "fields", "1", "a"]).is_err());
+        assert!(parse(&["user", "-1", "fields", "1", "a"]).is_err());
+        assert!(parse(&["user", "100", "a", "b", "c"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hsetex_from_array() -> Result<()> {
+        let parse = |args: &[&str]| HSetEx::try_from(frames("hsetex", args));
+
+        let cmd = parse(&[
+            "user", "fnx", "px", "100", "fields", "2", "a", "1", "b", "2",
+        ])?;
+        assert_eq!(cmd.cond, FieldCond::Fnx);
+        assert_eq!(cmd.expire, Some(SetExpire::Px(100)));
+        assert_eq!(cmd.pairs.len(), 2);
+
+        assert!(parse(&["user", "keepttl", "fields", "1", "a", "1"])?.keep_ttl);
+        assert!(parse(&["user", "fields", "2", "a", "1"]).is_err());
+        assert!(parse(&["user", "keepttl", "ex", "1", "fields", "1", "a", "1"]).is_err());
+        assert!(parse(&["user", "fnx", "fxx", "fields", "1", "a", "1"]).is_err());
+
+        let cmd = HGetEx::try_from(frames("hgetex", &["user", "persist", "fields", "1", "a"]))?;
+        assert!(cmd.persist);
+        assert!(
+            HGetEx::try_from(frames("hgetex", &["user", "ex", "0", "fields", "1", "a"])).is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_field_expire_cmd() -> Result<()> {
+        let backend = Backend::new();
+        let fields = |fields: &[&str]| fields.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        let ints = |ints: &[i64]| {
+            Array::new(
+                ints.iter()
+                    .map(|i| RespFrame::Integer(*i))
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+
+        let cmd = HSetEx {
+            key: "user".to_string(),
+            cond: FieldCond::Any,
+            expire: None,
+            keep_ttl: false,
+            pairs: vec![
+                ("a".to_string(), BulkString::from("1").into()),
+                ("b".to_string(), BulkString::from("2").into()),
+            ],
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(1));
+
+        let cmd = HExpire {
+            key: "user".to_string(),
+            seconds: 100,
+            opt: ExpireOpt::default(),
+            fields: fields(&["a", "missing"]),
+        };
+        assert_eq!(cmd.exec(&backend), ints(&[1, -2]));
+
+        let cmd = HTtl {
+            key: "user".to_string(),
+            fields: fields(&["a", "b", "missing"]),
+        };
+        assert_eq!(cmd.exec(&backend), ints(&[100, -1, -2]));
+
+        let cmd = HPersist {
+            key: "user".to_string(),
+            fields: fields(&["a", "b", "missing"]),
+        };
+        assert_eq!(cmd.exec(&backend), ints(&[1, -1, -2]));
+
+        let cmd = HPExpire {
+            key: "user".to_string(),
+            milliseconds: 0,
+            opt: ExpireOpt::default(),
+            fields: fields(&["a"]),
+        };
+        assert_eq!(cmd.exec(&backend), ints(&[2]));
+
+        let cmd = HGetEx {
+            key: "user".to_string(),
+            expire: Some(SetExpire::PxAt(1)),
+            persist: false,
+            fields: fields(&["a", "b"]),
+        };
+        assert_eq!(
+            cmd.exec(&backend),
+            Array::new([RespFrame::Null(Null), BulkString::from("2").into()]).into()
+        );
+        assert_eq!(backend.exists(&fields(&["user"])), 0);
+
+        let cmd = HExpire {
+            key: "user".to_string(),
+            seconds: i64::MAX,
+            opt: ExpireOpt::default(),
+            fields: fields(&["a"]),
+        };
+        assert_eq!(
+            cmd.exec(&backend),
+            CmdErr::Other("invalid expire time in 'hexpire' command".to_string()).into()
+        );
+
+        backend.set("string".to_string(), BulkString::from("v"));
+        let cmd = HTtl {
+            key: "string".to_string(),
+            fields: fields(&["a"]),
+        };
+        assert_eq!(cmd.exec(&backend), BackendErr::WrongType.into());
+
+        Ok(())
+    }
+}
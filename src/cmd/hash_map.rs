@@ -1,44 +1,106 @@
 // hash map cmd
 
-use crate::cmd::{extract_args, validate_cmd, CmdErr, CmdExecutor, HGet, HGetAll, HSet, RESP_OK};
+use crate::cmd::{
+    extract_args, map_reply, parse_float, parse_int, parse_opt, parse_random_count, validate_cmd,
+    validate_cmd_min, CmdErr, CmdExecutor, HDel, HExists, HGet, HGetAll, HIncrBy, HIncrByFloat,
+    HKeys, HLen, HMGet, HRandField, HSet, HSetNx, HStrLen, HVals,
+};
 use crate::{Array, Backend, BulkString, Null, RespFrame};
 
 // cmd hset
 impl CmdExecutor for HSet {
     fn exec(self, backend: &Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value);
-        RESP_OK.clone()
+        match backend.hset(self.key, self.pairs) {
+            Ok(added) => RespFrame::Integer(added as i64),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl TryFrom<Array> for HSet {
     type Error = CmdErr;
 
+    // HSET key field value [field value ...]
     fn try_from(value: Array) -> Result<Self, Self::Error> {
-        validate_cmd(&value, &["hset"], 3)?;
+        validate_cmd_min(&value, &["hset"], 3)?;
+        if !value.len().is_multiple_of(2) {
+            return Err(CmdErr::Other(
+                "wrong number of arguments for 'hset' command".to_string(),
+            ));
+        }
 
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
-                Ok(HSet {
-                    key: String::from_utf8(key.0)?,
-                    field: String::from_utf8(field.0)?,
-                    value,
-                })
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            _ => return Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        };
+
+        let mut pairs = Vec::with_capacity(args.len() / 2);
+        while let (Some(field), Some(value)) = (args.next(), args.next()) {
+            match field {
+                RespFrame::BulkString(field) => pairs.push((String::from_utf8(field.0)?, value)),
+                _ => return Err(CmdErr::InvalidArg("Invalid field.".to_string())),
             }
-            _ => Err(CmdErr::InvalidArg(
-                "Invalid key, field or value.".to_string(),
-            )),
+        }
+
+        Ok(HSet { key, pairs })
+    }
+}
+
+// cmd hincrby
+impl CmdExecutor for HIncrBy {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hincr_by(self.key, self.field, self.increment) {
+            Ok(value) => RespFrame::Integer(value),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HIncrBy {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, field, increment) = parse_incr_args(value, "hincrby")?;
+        Ok(HIncrBy {
+            key,
+            field,
+            increment: parse_int(increment)?,
+        })
+    }
+}
+
+// cmd hincrbyfloat
+impl CmdExecutor for HIncrByFloat {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hincr_by_float(self.key, self.field, self.increment) {
+            Ok(value) => value.into(),
+            Err(e) => e.into(),
         }
     }
 }
 
+impl TryFrom<Array> for HIncrByFloat {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, field, increment) = parse_incr_args(value, "hincrbyfloat")?;
+        Ok(HIncrByFloat {
+            key,
+            field,
+            increment: parse_float(increment)?,
+        })
+    }
+}
+
 // cmd hget
 impl CmdExecutor for HGet {
     fn exec(self, backend: &Backend) -> RespFrame {
-        backend
-            .hget(&self.key, &self.field)
-            .unwrap_or(RespFrame::Null(Null))
+        match backend.hget(&self.key, &self.field) {
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
     }
 }
 
@@ -62,28 +124,16 @@ impl TryFrom<Array> for HGet {
 // cmd hgetall
 impl CmdExecutor for HGetAll {
     fn exec(self, backend: &Backend) -> RespFrame {
-        let map = backend.hash_map.get(&self.key);
-
-        match map {
-            Some(map) => {
-                let mut data = Vec::with_capacity(map.len());
-                for item in map.iter() {
-                    let key = item.key().to_owned();
-                    data.push((key, item.value().clone()));
-                }
-
+        match backend.hgetall(&self.key) {
+            // sort only orders RESP2 arrays, RESP3 maps are always replied ordered by field
+            Ok(Some(mut data)) => {
                 if self.sort {
                     data.sort_by(|a, b| a.0.cmp(&b.0));
                 }
-
-                let ret = data
-                    .into_iter()
-                    .flat_map(|(k, v)| vec![BulkString::from(k).into(), v])
-                    .collect::<Vec<RespFrame>>();
-
-                Array::new(ret).into()
+                map_reply(backend, data)
             }
-            None => Array::new([]).into(),
+            Ok(None) => map_reply(backend, Vec::new()),
+            Err(e) => e.into(),
         }
     }
 }
@@ -91,6 +141,7 @@ impl CmdExecutor for HGetAll {
 impl TryFrom<Array> for HGetAll {
     type Error = CmdErr;
 
+    // HGETALL key
     fn try_from(value: Array) -> Result<Self, Self::Error> {
         validate_cmd(&value, &["hgetall"], 1)?;
 
@@ -105,12 +156,323 @@ impl TryFrom<Array> for HGetAll {
     }
 }
 
+// cmd hsetnx
+impl CmdExecutor for HSetNx {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hsetnx(self.key, self.field, self.value) {
+            Ok(set) => RespFrame::Integer(set as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HSetNx {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["hsetnx"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
+                Ok(HSetNx {
+                    key: String::from_utf8(key.0)?,
+                    field: String::from_utf8(field.0)?,
+                    value,
+                })
+            }
+            _ => Err(CmdErr::InvalidArg(
+                "Invalid key, field or value.".to_string(),
+            )),
+        }
+    }
+}
+
+// cmd hmget
+impl CmdExecutor for HMGet {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hmget(&self.key, &self.fields) {
+            Ok(values) => Array::new(
+                values
+                    .into_iter()
+                    .map(|value| value.unwrap_or(RespFrame::Null(Null)))
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HMGet {
+    type Error = CmdErr;
+
+    // HMGET key field [field ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields_args(value, "hmget")?;
+        Ok(HMGet { key, fields })
+    }
+}
+
+// cmd hdel
+impl CmdExecutor for HDel {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hdel(&self.key, &self.fields) {
+            Ok(deleted) => RespFrame::Integer(deleted as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HDel {
+    type Error = CmdErr;
+
+    // HDEL key field [field ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, fields) = parse_key_fields_args(value, "hdel")?;
+        Ok(HDel { key, fields })
+    }
+}
+
+// cmd hexists
+impl CmdExecutor for HExists {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hexists(&self.key, &self.field) {
+            Ok(exists) => RespFrame::Integer(exists as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HExists {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, field) = parse_key_field_args(value, "hexists")?;
+        Ok(HExists { key, field })
+    }
+}
+
+// cmd hlen
+impl CmdExecutor for HLen {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HLen {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(HLen {
+            key: parse_key_arg(value, "hlen")?,
+        })
+    }
+}
+
+// cmd hkeys
+impl CmdExecutor for HKeys {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hkeys(&self.key) {
+            Ok(fields) => Array::new(
+                fields
+                    .into_iter()
+                    .map(|field| BulkString::from(field).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HKeys {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(HKeys {
+            key: parse_key_arg(value, "hkeys")?,
+        })
+    }
+}
+
+// cmd hvals
+impl CmdExecutor for HVals {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hvals(&self.key) {
+            Ok(values) => Array::new(values).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HVals {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(HVals {
+            key: parse_key_arg(value, "hvals")?,
+        })
+    }
+}
+
+// cmd hstrlen
+impl CmdExecutor for HStrLen {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hstrlen(&self.key, &self.field) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HStrLen {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, field) = parse_key_field_args(value, "hstrlen")?;
+        Ok(HStrLen { key, field })
+    }
+}
+
+// cmd hrandfield
+impl CmdExecutor for HRandField {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let fields = match backend.hrandfield(&self.key, self.count.unwrap_or(1)) {
+            Ok(fields) => fields,
+            Err(e) => return e.into(),
+        };
+
+        if self.count.is_none() {
+            return match fields.into_iter().next() {
+                Some((field, _)) => BulkString::from(field).into(),
+                None => RespFrame::Null(Null),
+            };
+        }
+
+        // a map cannot hold the repeated fields of a negative count, those are kept as pairs
+        let distinct = self.count.is_some_and(|count| count >= 0);
+        if self.with_values && distinct {
+            return map_reply(backend, fields);
+        }
+        if self.with_values && backend.protocol() >= 3 {
+            let pairs = fields
+                .into_iter()
+                .map(|(field, value)| Array::new([BulkString::from(field).into(), value]).into())
+                .collect::<Vec<RespFrame>>();
+            return Array::new(pairs).into();
+        }
+
+        let mut items = Vec::with_capacity(fields.len() * 2);
+        for (field, value) in fields {
+            items.push(BulkString::from(field).into());
+            if self.with_values {
+                items.push(value);
+            }
+        }
+        Array::new(items).into()
+    }
+}
+
+impl TryFrom<Array> for HRandField {
+    type Error = CmdErr;
+
+    // HRANDFIELD key [count [WITHVALUES]]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["hrandfield"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            _ => return Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        };
+        let count = args.next().map(parse_random_count).transpose()?;
+        let with_values = match args.next().map(parse_opt).transpose()?.as_deref() {
+            None => false,
+            Some("WITHVALUES") => true,
+            _ => return Err(CmdErr::SyntaxErr),
+        };
+        if args.next().is_some() {
+            return Err(CmdErr::SyntaxErr);
+        }
+
+        Ok(HRandField {
+            key,
+            count,
+            with_values,
+        })
+    }
+}
+
+fn parse_incr_args(
+    value: Array,
+    name: &'static str,
+) -> Result<(String, String, RespFrame), CmdErr> {
+    validate_cmd(&value, &[name], 3)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(increment)) => {
+            Ok((
+                String::from_utf8(key.0)?,
+                String::from_utf8(field.0)?,
+                increment,
+            ))
+        }
+        _ => Err(CmdErr::InvalidArg(
+            "Invalid key, field or increment.".to_string(),
+        )),
+    }
+}
+
+fn parse_key_arg(value: Array, name: &'static str) -> Result<String, CmdErr> {
+    validate_cmd(&value, &[name], 1)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    match args.next() {
+        Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.0)?),
+        _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+    }
+}
+
+fn parse_key_field_args(value: Array, name: &'static str) -> Result<(String, String), CmdErr> {
+    validate_cmd(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field))) => {
+            Ok((String::from_utf8(key.0)?, String::from_utf8(field.0)?))
+        }
+        _ => Err(CmdErr::InvalidArg("Invalid key or field.".to_string())),
+    }
+}
+
+fn parse_key_fields_args(
+    value: Array,
+    name: &'static str,
+) -> Result<(String, Vec<String>), CmdErr> {
+    validate_cmd_min(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter().map(|arg| match arg {
+        RespFrame::BulkString(s) => Ok(String::from_utf8(s.0)?),
+        _ => Err(CmdErr::InvalidArg("Invalid key or field.".to_string())),
+    });
+    let key = args.next().ok_or(CmdErr::SyntaxErr)??;
+    let fields = args.collect::<Result<Vec<_>, _>>()?;
+
+    Ok((key, fields))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::RespDecode;
+    use crate::{BackendErr, Map, RespDecode, RespEncode};
     use anyhow::Result;
     use bytes::BytesMut;
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_hset_from_array() -> Result<()> {
@@ -123,8 +485,19 @@ mod tests {
 
         let cmd: HSet = frame.try_into()?;
         assert_eq!(cmd.key, "jrmarcco");
-        assert_eq!(cmd.field, "hello");
-        assert_eq!(cmd.value, RespFrame::BulkString("world".into()));
+        assert_eq!(
+            cmd.pairs,
+            [("hello".to_string(), RespFrame::BulkString("world".into()))]
+        );
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nhset\r\n$8\r\njrmarcco\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<HSet, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
 
         Ok(())
     }
@@ -154,6 +527,72 @@ mod tests {
         assert_eq!(cmd.key, "jrmarcco");
         assert!(!cmd.sort);
 
+        // HGETALL takes the key alone
+        buf.extend_from_slice(b"*3\r\n$7\r\nhgetall\r\n$8\r\njrmarcco\r\n$4\r\nsort\r\n");
+        let ret: Result<HGetAll, CmdErr> = Array::decode(&mut buf)?.try_into();
+        assert!(ret.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_map_cmd_resp3() -> Result<()> {
+        let backend = Backend::new();
+        backend.set_protocol(3);
+        let pairs = [("b", "2"), ("a", "1")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), RespFrame::from(BulkString::from(*v))))
+            .collect::<Vec<_>>();
+        backend.hset("jrmarcco".to_string(), pairs.clone())?;
+
+        let expected = Map::from(pairs.clone().into_iter().collect::<BTreeMap<_, _>>());
+        let cmd = HGetAll {
+            key: "jrmarcco".to_string(),
+            sort: false,
+        };
+        assert_eq!(cmd.exec(&backend), expected.clone().into());
+
+        let cmd = HGetAll {
+            key: "missing".to_string(),
+            sort: false,
+        };
+        assert_eq!(cmd.exec(&backend), Map::new().into());
+
+        let cmd = HRandField {
+            key: "jrmarcco".to_string(),
+            count: Some(5),
+            with_values: true,
+        };
+        assert_eq!(cmd.exec(&backend), expected.into());
+
+        // repeated fields of a negative count are replied as field and value pairs
+        let cmd = HRandField {
+            key: "jrmarcco".to_string(),
+            count: Some(-3),
+            with_values: true,
+        };
+        let RespFrame::Array(items) = cmd.exec(&backend) else {
+            panic!("expected an array reply");
+        };
+        assert_eq!(items.len(), 3);
+        let candidates = pairs
+            .into_iter()
+            .map(|(k, v)| Array::new([BulkString::from(k).into(), v]).into())
+            .collect::<Vec<RespFrame>>();
+        assert!(items.iter().all(|item| candidates.contains(item)));
+
+        // field names are binary safe, a CRLF in one can not break the reply apart
+        let field = ("f\r\n+OK".to_string(), BulkString::from("v").into());
+        backend.hset("crlf".to_string(), vec![field])?;
+        let cmd = HGetAll {
+            key: "crlf".to_string(),
+            sort: false,
+        };
+        assert_eq!(
+            cmd.exec(&backend).encode(),
+            b"%1\r\n$6\r\nf\r\n+OK\r\n$1\r\nv\r\n"
+        );
+
         Ok(())
     }
 
@@ -163,12 +602,11 @@ mod tests {
 
         let cmd = HSet {
             key: "jrmarcco".to_string(),
-            field: "hello".to_string(),
-            value: RespFrame::BulkString("world".into()),
+            pairs: vec![("hello".to_string(), RespFrame::BulkString("world".into()))],
         };
 
         let ret = cmd.exec(&backend);
-        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(ret, RespFrame::Integer(1));
 
         let cmd = HGet {
             key: "jrmarcco".to_string(),
@@ -180,12 +618,11 @@ mod tests {
 
         let cmd = HSet {
             key: "jrmarcco".to_string(),
-            field: "foo".to_string(),
-            value: RespFrame::BulkString("bar".into()),
+            pairs: vec![("foo".to_string(), RespFrame::BulkString("bar".into()))],
         };
 
         let ret = cmd.exec(&backend);
-        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(ret, RespFrame::Integer(1));
 
         let cmd = HGet {
             key: "jrmarcco".to_string(),
@@ -214,4 +651,249 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_hash_map_cmd_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("jrmarcco".to_string(), BulkString::from("hello"));
+
+        let cmd = HSet {
+            key: "jrmarcco".to_string(),
+            pairs: vec![("hello".to_string(), RespFrame::BulkString("world".into()))],
+        };
+
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        let cmd = HGet {
+            key: "jrmarcco".to_string(),
+            field: "hello".to_string(),
+        };
+
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        let cmd = HGetAll {
+            key: "jrmarcco".to_string(),
+            sort: false,
+        };
+
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hmget_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nhmget\r\n$8\r\njrmarcco\r\n$1\r\na\r\n$1\r\nb\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+
+        let cmd: HMGet = frame.try_into()?;
+        assert_eq!(cmd.key, "jrmarcco");
+        assert_eq!(cmd.fields, ["a", "b"]);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\nhdel\r\n$8\r\njrmarcco\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<HDel, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield_from_array() -> Result<()> {
+        let parse = |args: &[&str]| {
+            let mut frames = vec![RespFrame::from(BulkString::from("hrandfield"))];
+            frames.extend(args.iter().map(|arg| BulkString::from(*arg).into()));
+            HRandField::try_from(Array::new(frames))
+        };
+
+        let cmd = parse(&["jrmarcco"])?;
+        assert_eq!((cmd.count, cmd.with_values), (None, false));
+
+        let cmd = parse(&["jrmarcco", "-5", "withvalues"])?;
+        assert_eq!((cmd.count, cmd.with_values), (Some(-5), true));
+
+        assert!(parse(&["jrmarcco", "a"]).is_err());
+        assert!(parse(&["jrmarcco", "1", "withscores"]).is_err());
+        assert!(parse(&["jrmarcco", "-9223372036854775807", "withvalues"]).is_err());
+        // a negative count is bounded as its repeated fields are allocated upfront
+        let ret = parse(&["jrmarcco", "-9223372036854775807"]);
+        assert_eq!(ret.unwrap_err().to_string(), "ERR value is out of range");
+        assert!(parse(&["jrmarcco", "9223372036854775807"]).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_fields_cmd() -> Result<()> {
+        let backend = Backend::new();
+        let key = || "jrmarcco".to_string();
+        let fields = |fields: &[&str]| fields.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+
+        let hsetnx = |field: &str, value: &str| {
+            HSetNx {
+                key: key(),
+                field: field.to_string(),
+                value: BulkString::from(value).into(),
+            }
+            .exec(&backend)
+        };
+        assert_eq!(hsetnx("name", "alice"), RespFrame::Integer(1));
+        assert_eq!(hsetnx("name", "bob"), RespFrame::Integer(0));
+        assert_eq!(hsetnx("age", "30"), RespFrame::Integer(1));
+
+        let cmd = HMGet {
+            key: key(),
+            fields: fields(&["name", "missing"]),
+        };
+        assert_eq!(
+            cmd.exec(&backend),
+            Array::new([BulkString::from("alice").into(), RespFrame::Null(Null)]).into()
+        );
+
+        let cmd = HExists {
+            key: key(),
+            field: "age".to_string(),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(1));
+        assert_eq!(HLen { key: key() }.exec(&backend), RespFrame::Integer(2));
+
+        let cmd = HStrLen {
+            key: key(),
+            field: "name".to_string(),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(5));
+
+        let RespFrame::Array(keys) = HKeys { key: key() }.exec(&backend) else {
+            panic!("hkeys must reply an array");
+        };
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&BulkString::from("age").into()));
+
+        let RespFrame::Array(values) = HVals { key: key() }.exec(&backend) else {
+            panic!("hvals must reply an array");
+        };
+        assert!(values.contains(&BulkString::from("30").into()));
+
+        let cmd = HRandField {
+            key: key(),
+            count: Some(-4),
+            with_values: true,
+        };
+        let RespFrame::Array(items) = cmd.exec(&backend) else {
+            panic!("hrandfield with a count must reply an array");
+        };
+        assert_eq!(items.len(), 8);
+
+        let cmd = HDel {
+            key: key(),
+            fields: fields(&["name", "age", "missing"]),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(2));
+        assert_eq!(backend.exists(&[key()]), 0);
+
+        let cmd = HRandField {
+            key: key(),
+            count: None,
+            with_values: false,
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Null(Null));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hset_multiple_cmd() -> Result<()> {
+        let backend = Backend::new();
+        let pairs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(f, v)| (f.to_string(), BulkString::from(*v).into()))
+                .collect::<Vec<_>>()
+        };
+
+        let cmd = HSet {
+            key: "jrmarcco".to_string(),
+            pairs: pairs(&[("a", "1"), ("b", "2")]),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(2));
+
+        // updated fields are not counted, a field given twice keeps the last value
+        let cmd = HSet {
+            key: "jrmarcco".to_string(),
+            pairs: pairs(&[("b", "3"), ("c", "4"), ("c", "5")]),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(1));
+        assert_eq!(
+            backend.hget("jrmarcco", "c")?,
+            Some(BulkString::from("5").into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincr_cmd() -> Result<()> {
+        let backend = Backend::new();
+        let hincr_by = |field: &str, increment| {
+            HIncrBy {
+                key: "jrmarcco".to_string(),
+                field: field.to_string(),
+                increment,
+            }
+            .exec(&backend)
+        };
+        let hincr_by_float = |field: &str, increment| {
+            HIncrByFloat {
+                key: "jrmarcco".to_string(),
+                field: field.to_string(),
+                increment,
+            }
+            .exec(&backend)
+        };
+
+        assert_eq!(hincr_by("count", 5), RespFrame::Integer(5));
+        assert_eq!(hincr_by("count", -7), RespFrame::Integer(-2));
+        assert_eq!(hincr_by("count", i64::MIN), BackendErr::Overflow.into());
+
+        assert_eq!(
+            hincr_by_float("count", 0.5),
+            BulkString::from("-1.5").into()
+        );
+        assert_eq!(
+            hincr_by_float("price", 10.5),
+            BulkString::from("10.5").into()
+        );
+        assert_eq!(
+            hincr_by_float("price", 1e-1),
+            BulkString::from("10.6").into()
+        );
+        assert_eq!(
+            hincr_by_float("price", f64::INFINITY),
+            BackendErr::NanOrInfinity.into()
+        );
+
+        // values produced by incrbyfloat are no integers anymore
+        assert_eq!(hincr_by("price", 1), BackendErr::HashNotInteger.into());
+
+        backend.hset(
+            "jrmarcco".to_string(),
+            vec![("name".to_string(), BulkString::from("alice").into())],
+        )?;
+        assert_eq!(hincr_by_float("name", 1.0), BackendErr::HashNotFloat.into());
+
+        let frames = ["hincrby", "jrmarcco", "count", "1.5"]
+            .iter()
+            .map(|arg| BulkString::from(*arg).into())
+            .collect::<Vec<RespFrame>>();
+        assert!(HIncrBy::try_from(Array::new(frames)).is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,514 @@
+// keyspace cmd
+use crate::{
+    cmd::{
+        extract_args, parse_db_index, parse_opt, validate_cmd, validate_cmd_min, CmdErr,
+        CmdExecutor, CopyKey, DbSize, Del, Exists, FlushAll, FlushDb, RandomKey, Rename, RenameNx,
+        Touch, Type, Unlink, RESP_OK,
+    },
+    Array, Backend, BulkString, Null, RespFrame, SimpleString,
+};
+
+// cmd del
+impl CmdExecutor for Del {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.del(&self.keys) as i64)
+    }
+}
+
+impl TryFrom<Array> for Del {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(Del {
+            keys: parse_keys_arg(value, "del")?,
+        })
+    }
+}
+
+// cmd unlink
+impl CmdExecutor for Unlink {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.unlink(&self.keys) as i64)
+    }
+}
+
+impl TryFrom<Array> for Unlink {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(Unlink {
+            keys: parse_keys_arg(value, "unlink")?,
+        })
+    }
+}
+
+// cmd exists
+impl CmdExecutor for Exists {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.exists(&self.keys) as i64)
+    }
+}
+
+impl TryFrom<Array> for Exists {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(Exists {
+            keys: parse_keys_arg(value, "exists")?,
+        })
+    }
+}
+
+// cmd type
+impl CmdExecutor for Type {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        SimpleString::new(backend.key_type(&self.key).unwrap_or("none")).into()
+    }
+}
+
+impl TryFrom<Array> for Type {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["type"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Type {
+                key: String::from_utf8(key.0)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        }
+    }
+}
+
+// cmd rename
+impl CmdExecutor for Rename {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, self.new_key) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for Rename {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, new_key) = parse_rename_args(value, "rename")?;
+        Ok(Rename { key, new_key })
+    }
+}
+
+// cmd renamenx
+impl CmdExecutor for RenameNx {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.rename_nx(&self.key, self.new_key) {
+            Ok(renamed) => RespFrame::Integer(renamed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for RenameNx {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, new_key) = parse_rename_args(value, "renamenx")?;
+        Ok(RenameNx { key, new_key })
+    }
+}
+
+// cmd copy
+impl CmdExecutor for CopyKey {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.copy(&self.source, self.destination, self.db, self.replace) {
+            Ok(copied) => RespFrame::Integer(copied as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for CopyKey {
+    type Error = CmdErr;
+
+    // COPY source destination [DB destination-db] [REPLACE]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["copy"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (source, destination) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(source)), Some(RespFrame::BulkString(destination))) => (
+                String::from_utf8(source.0)?,
+                String::from_utf8(destination.0)?,
+            ),
+            _ => return Err(CmdErr::InvalidArg("Invalid keys.".to_string())),
+        };
+
+        let mut cmd = CopyKey {
+            source,
+            destination,
+            db: None,
+            replace: false,
+        };
+        while let Some(arg) = args.next() {
+            match parse_opt(arg)?.as_str() {
+                "REPLACE" => cmd.replace = true,
+                "DB" => cmd.db = Some(parse_db_index(args.next().ok_or(CmdErr::SyntaxErr)?)?),
+                _ => return Err(CmdErr::SyntaxErr),
+            }
+        }
+
+        Ok(cmd)
+    }
+}
+
+// cmd touch
+impl CmdExecutor for Touch {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.exists(&self.keys) as i64)
+    }
+}
+
+impl TryFrom<Array> for Touch {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(Touch {
+            keys: parse_keys_arg(value, "touch")?,
+        })
+    }
+}
+
+// cmd randomkey
+impl CmdExecutor for RandomKey {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        backend
+            .random_key()
+            .map_or(RespFrame::Null(Null), |key| BulkString::from(key).into())
+    }
+}
+
+impl TryFrom<Array> for RandomKey {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["randomkey"], 0)?;
+        Ok(RandomKey)
+    }
+}
+
+// cmd dbsize
+impl CmdExecutor for DbSize {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.db_size() as i64)
+    }
+}
+
+impl TryFrom<Array> for DbSize {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["dbsize"], 0)?;
+        Ok(DbSize)
+    }
+}
+
+// cmd flushdb
+impl CmdExecutor for FlushDb {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        backend.flush_db(self.lazy);
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<Array> for FlushDb {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(FlushDb {
+            lazy: parse_flush_args(value, "flushdb")?,
+        })
+    }
+}
+
+// cmd flushall
+impl CmdExecutor for FlushAll {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        backend.flush_all(self.lazy);
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<Array> for FlushAll {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(FlushAll {
+            lazy: parse_flush_args(value, "flushall")?,
+        })
+    }
+}
+
+// Whether a flush is asked to be lazy, flushes are synchronous by default.
+fn parse_flush_args(value: Array, name: &'static str) -> Result<bool, CmdErr> {
+    validate_cmd_min(&value, &[name], 0)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let lazy = match args.next().map(parse_opt).transpose()?.as_deref() {
+        None | Some("SYNC") => false,
+        Some("ASYNC") => true,
+        _ => return Err(CmdErr::SyntaxErr),
+    };
+    if args.next().is_some() {
+        return Err(CmdErr::SyntaxErr);
+    }
+
+    Ok(lazy)
+}
+
+fn parse_keys_arg(value: Array, name: &'static str) -> Result<Vec<String>, CmdErr> {
+    validate_cmd_min(&value, &[name], 1)?;
+
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(key) => Ok(String::from_utf8(key.0)?),
+            _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        })
+        .collect()
+}
+
+fn parse_rename_args(value: Array, name: &'static str) -> Result<(String, String), CmdErr> {
+    validate_cmd(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next()) {
+        (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(new_key))) => {
+            Ok((String::from_utf8(key.0)?, String::from_utf8(new_key.0)?))
+        }
+        _ => Err(CmdErr::InvalidArg("Invalid key or newkey.".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{now_ms, BackendErr, RespDecode, SimpleError};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn test_del_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\ndel\r\n$2\r\nk1\r\n$2\r\nk2\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: Del = frame.try_into()?;
+        assert_eq!(cmd.keys, keys(&["k1", "k2"]));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$3\r\ndel\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<Del, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\ncopy\r\n$1\r\na\r\n$1\r\nb\r\n$2\r\ndb\r\n$1\r\n0\r\n$7\r\nreplace\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: CopyKey = frame.try_into()?;
+        assert_eq!(cmd.source, "a");
+        assert_eq!(cmd.destination, "b");
+        assert_eq!(cmd.db, Some(0));
+        assert!(cmd.replace);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\ncopy\r\n$1\r\na\r\n$1\r\nb\r\n$2\r\ndb\r\n$2\r\n-1\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<CopyKey, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyspace_cmd() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("s".to_string(), BulkString::from("v"));
+        backend.lpush("l".to_string(), vec![BulkString::from("v").into()])?;
+
+        let cmd = Exists {
+            keys: keys(&["s", "s", "l", "missing"]),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(3));
+
+        let cmd = Type {
+            key: "l".to_string(),
+        };
+        assert_eq!(cmd.exec(&backend), SimpleString::new("list").into());
+
+        let cmd = Type {
+            key: "missing".to_string(),
+        };
+        assert_eq!(cmd.exec(&backend), SimpleString::new("none").into());
+
+        assert_eq!(DbSize.exec(&backend), RespFrame::Integer(2));
+
+        let cmd = Touch {
+            keys: keys(&["s", "missing"]),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(1));
+
+        let cmd = Del {
+            keys: keys(&["s", "s", "missing"]),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(1));
+
+        let cmd = Unlink { keys: keys(&["l"]) };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(1));
+        assert_eq!(DbSize.exec(&backend), RespFrame::Integer(0));
+        assert_eq!(RandomKey.exec(&backend), RespFrame::Null(Null));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flush_from_array() -> Result<()> {
+        let parse = |args: &[&str]| {
+            let frames = args
+                .iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<RespFrame>>();
+            FlushDb::try_from(Array::new(frames))
+        };
+
+        assert!(!parse(&["flushdb"])?.lazy);
+        assert!(!parse(&["flushdb", "sync"])?.lazy);
+        assert!(parse(&["FLUSHDB", "ASYNC"])?.lazy);
+        assert!(parse(&["flushdb", "lazy"]).is_err());
+        assert!(parse(&["flushdb", "async", "sync"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flush_cmd() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::from("1"));
+        backend.set("b".to_string(), BulkString::from("2"));
+
+        assert_eq!(FlushDb { lazy: true }.exec(&backend), RESP_OK.clone());
+        assert_eq!(DbSize.exec(&backend), RespFrame::Integer(0));
+
+        backend.set("a".to_string(), BulkString::from("1"));
+        assert_eq!(FlushAll { lazy: false }.exec(&backend), RESP_OK.clone());
+        assert_eq!(DbSize.exec(&backend), RespFrame::Integer(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_cmd() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::from("1"));
+        backend.expire_at("a", now_ms() + 10_000, Default::default());
+
+        let cmd = Rename {
+            key: "a".to_string(),
+            new_key: "b".to_string(),
+        };
+        assert_eq!(cmd.exec(&backend), RESP_OK.clone());
+        assert!(backend.expire_time("b").flatten().is_some());
+
+        let cmd = Rename {
+            key: "a".to_string(),
+            new_key: "b".to_string(),
+        };
+        assert_eq!(
+            cmd.exec(&backend),
+            SimpleError::new("ERR no such key").into()
+        );
+
+        let cmd = RenameNx {
+            key: "b".to_string(),
+            new_key: "b".to_string(),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(0));
+
+        let cmd = RenameNx {
+            key: "b".to_string(),
+            new_key: "c".to_string(),
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_cmd() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), BulkString::from("1"));
+        backend.set("b".to_string(), BulkString::from("2"));
+
+        let copy = |replace| {
+            CopyKey {
+                source: "a".to_string(),
+                destination: "b".to_string(),
+                db: None,
+                replace,
+            }
+            .exec(&backend)
+        };
+        assert_eq!(copy(false), RespFrame::Integer(0));
+        assert_eq!(backend.get("b")?, Some(BulkString::from("2")));
+        assert_eq!(copy(true), RespFrame::Integer(1));
+        assert_eq!(backend.get("b")?, Some(BulkString::from("1")));
+
+        // the copy is independent of the source
+        backend.append("a".to_string(), b"1")?;
+        assert_eq!(backend.get("b")?, Some(BulkString::from("1")));
+
+        backend.lpush("l".to_string(), vec![BulkString::from("v").into()])?;
+        let cmd = CopyKey {
+            source: "l".to_string(),
+            destination: "l2".to_string(),
+            db: None,
+            replace: false,
+        };
+        assert_eq!(cmd.exec(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.get("l2"), Err(BackendErr::WrongType));
+
+        let copy_to = |destination: &str, db| {
+            CopyKey {
+                source: "a".to_string(),
+                destination: destination.to_string(),
+                db: Some(db),
+                replace: false,
+            }
+            .exec(&backend)
+        };
+        assert_eq!(copy_to("a", 0), BackendErr::SameObject.into());
+        assert_eq!(copy_to("a", 16), BackendErr::DbIndexOutOfRange.into());
+        assert_eq!(copy_to("a", 1), RespFrame::Integer(1));
+        assert_eq!(backend.with_db(1)?.get("a")?, Some(BulkString::from("11")));
+
+        Ok(())
+    }
+}
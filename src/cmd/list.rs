@@ -1,105 +1,213 @@
 use crate::cmd::{
-    extract_args, validate_cmd, CmdErr, CmdExecutor, LLen, LPop, LPush, RPop, RPush, RESP_OK,
+    extract_args, parse_int, parse_opt, validate_cmd, validate_cmd_min, CmdErr, CmdExecutor,
+    LIndex, LInsert, LLen, LMPop, LMove, LPop, LPos, LPush, LPushX, LRange, LRem, LSet, LTrim,
+    RPop, RPopLPush, RPush, RPushX, RESP_OK,
 };
-use crate::{Array, Backend, Null, RespFrame};
+use crate::{Array, Backend, BackendErr, BulkString, LPosOpt, ListEnd, Null, NullArray, RespFrame};
 
 impl CmdExecutor for LPush {
     fn exec(self, backend: &Backend) -> RespFrame {
-        backend.lpush(self.key, self.value);
-        RESP_OK.clone()
+        match backend.lpush(self.key, self.values) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl TryFrom<Array> for LPush {
     type Error = CmdErr;
 
+    // LPUSH key element [element ...]
     fn try_from(value: Array) -> Result<Self, Self::Error> {
-        validate_cmd(&value, &["lpush"], 2)?;
+        let (key, values) = parse_push_args(value, "lpush")?;
+        Ok(LPush { key, values })
+    }
+}
 
-        let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(LPush {
-                key: String::from_utf8(key.0)?,
-                value,
-            }),
-            _ => Err(CmdErr::InvalidArg("Invalid key or value".to_string())),
+impl CmdExecutor for LPushX {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.lpushx(&self.key, self.values) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
         }
     }
 }
 
+impl TryFrom<Array> for LPushX {
+    type Error = CmdErr;
+
+    // LPUSHX key element [element ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push_args(value, "lpushx")?;
+        Ok(LPushX { key, values })
+    }
+}
+
 impl CmdExecutor for LPop {
     fn exec(self, backend: &Backend) -> RespFrame {
-        backend.lpop(&self.key).unwrap_or(RespFrame::Null(Null))
+        pop_reply(backend.lpop(&self.key, self.count.unwrap_or(1)), self.count)
     }
 }
 
 impl TryFrom<Array> for LPop {
     type Error = CmdErr;
 
+    // LPOP key [count]
     fn try_from(value: Array) -> Result<Self, Self::Error> {
-        validate_cmd(&value, &["lpop"], 1)?;
-
-        let mut args = extract_args(value, 1)?.into_iter();
-        match args.next() {
-            Some(RespFrame::BulkString(key)) => Ok(LPop {
-                key: String::from_utf8(key.0)?,
-            }),
-            _ => Err(CmdErr::InvalidArg("Invalid key".to_string())),
-        }
+        let (key, count) = parse_pop_args(value, "lpop")?;
+        Ok(LPop { key, count })
     }
 }
 
 impl CmdExecutor for RPush {
     fn exec(self, backend: &Backend) -> RespFrame {
-        backend.rpush(self.key, self.value);
-        RESP_OK.clone()
+        match backend.rpush(self.key, self.values) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
     }
 }
 
 impl TryFrom<Array> for RPush {
     type Error = CmdErr;
 
+    // RPUSH key element [element ...]
     fn try_from(value: Array) -> Result<Self, Self::Error> {
-        validate_cmd(&value, &["rpush"], 2)?;
+        let (key, values) = parse_push_args(value, "rpush")?;
+        Ok(RPush { key, values })
+    }
+}
 
-        let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(RPush {
-                key: String::from_utf8(key.0)?,
-                value,
-            }),
-            _ => Err(CmdErr::InvalidArg("Invalid key or value".to_string())),
+impl CmdExecutor for RPushX {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.rpushx(&self.key, self.values) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
         }
     }
 }
 
+impl TryFrom<Array> for RPushX {
+    type Error = CmdErr;
+
+    // RPUSHX key element [element ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, values) = parse_push_args(value, "rpushx")?;
+        Ok(RPushX { key, values })
+    }
+}
+
 impl CmdExecutor for RPop {
     fn exec(self, backend: &Backend) -> RespFrame {
-        backend.rpop(&self.key).unwrap_or(RespFrame::Null(Null))
+        pop_reply(backend.rpop(&self.key, self.count.unwrap_or(1)), self.count)
     }
 }
 
 impl TryFrom<Array> for RPop {
     type Error = CmdErr;
 
+    // RPOP key [count]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, count) = parse_pop_args(value, "rpop")?;
+        Ok(RPop { key, count })
+    }
+}
+
+impl CmdExecutor for LMove {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.lmove(&self.src, &self.dest, self.from, self.to) {
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for LMove {
+    type Error = CmdErr;
+
+    // LMOVE source destination <LEFT | RIGHT> <LEFT | RIGHT>
     fn try_from(value: Array) -> Result<Self, Self::Error> {
-        validate_cmd(&value, &["rpop"], 1)?;
+        validate_cmd(&value, &["lmove"], 4)?;
 
         let mut args = extract_args(value, 1)?.into_iter();
-        match args.next() {
-            Some(RespFrame::BulkString(key)) => Ok(RPop {
-                key: String::from_utf8(key.0)?,
-            }),
-            _ => Err(CmdErr::InvalidArg("Invalid key".to_string())),
+        let src = parse_key(args.next())?;
+        let dest = parse_key(args.next())?;
+        let from = parse_list_end(args.next())?;
+        let to = parse_list_end(args.next())?;
+        Ok(LMove {
+            src,
+            dest,
+            from,
+            to,
+        })
+    }
+}
+
+impl CmdExecutor for RPopLPush {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.lmove(&self.src, &self.dest, ListEnd::Right, ListEnd::Left) {
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for RPopLPush {
+    type Error = CmdErr;
+
+    // RPOPLPUSH source destination
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["rpoplpush"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let src = parse_key(args.next())?;
+        let dest = parse_key(args.next())?;
+        Ok(RPopLPush { src, dest })
+    }
+}
+
+impl CmdExecutor for LMPop {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.lmpop(&self.keys, self.end, self.count) {
+            Ok(Some((key, values))) => {
+                Array::new([BulkString::from(key).into(), Array::new(values).into()]).into()
+            }
+            Ok(None) => RespFrame::NullArray(NullArray),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for LMPop {
+    type Error = CmdErr;
+
+    // LMPOP numkeys key [key ...] <LEFT | RIGHT> [COUNT count]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["lmpop"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (keys, end) = parse_mpop_keys(&mut args)?;
+        let count = match args.next().map(parse_opt).transpose()?.as_deref() {
+            None => 1,
+            Some("COUNT") => parse_mpop_count(args.next())?,
+            _ => return Err(CmdErr::SyntaxErr),
+        };
+        if args.next().is_some() {
+            return Err(CmdErr::SyntaxErr);
         }
+
+        Ok(LMPop { keys, end, count })
     }
 }
 
 impl CmdExecutor for LLen {
     fn exec(self, backend: &Backend) -> RespFrame {
         match backend.llen(&self.key) {
-            Some(len) => RespFrame::Integer(len as i64),
-            _ => RespFrame::Integer(0),
+            Ok(Some(len)) => RespFrame::Integer(len as i64),
+            Ok(None) => RespFrame::Integer(0),
+            Err(e) => e.into(),
         }
     }
 }
@@ -120,12 +228,336 @@ impl TryFrom<Array> for LLen {
     }
 }
 
+impl CmdExecutor for LRange {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.lrange(&self.key, self.start, self.stop) {
+            Ok(values) => Array::new(values).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for LRange {
+    type Error = CmdErr;
+
+    // LRANGE key start stop
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["lrange"], 3)?;
+
+        let (key, start, stop) = parse_range_args(value)?;
+        Ok(LRange { key, start, stop })
+    }
+}
+
+impl CmdExecutor for LIndex {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.lindex(&self.key, self.index) {
+            Ok(Some(value)) => value,
+            Ok(None) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for LIndex {
+    type Error = CmdErr;
+
+    // LINDEX key index
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["lindex"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let index = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+        Ok(LIndex { key, index })
+    }
+}
+
+impl CmdExecutor for LSet {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.lset(&self.key, self.index, self.value) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for LSet {
+    type Error = CmdErr;
+
+    // LSET key index element
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["lset"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let index = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+        let value = args.next().ok_or(CmdErr::SyntaxErr)?;
+        Ok(LSet { key, index, value })
+    }
+}
+
+impl CmdExecutor for LInsert {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.linsert(&self.key, self.before, &self.pivot, self.value) {
+            Ok(Some(len)) => RespFrame::Integer(len as i64),
+            Ok(None) => RespFrame::Integer(-1),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for LInsert {
+    type Error = CmdErr;
+
+    // LINSERT key <BEFORE | AFTER> pivot element
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["linsert"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let before = match parse_opt(args.next().ok_or(CmdErr::SyntaxErr)?)?.as_str() {
+            "BEFORE" => true,
+            "AFTER" => false,
+            _ => return Err(CmdErr::SyntaxErr),
+        };
+        match (args.next(), args.next()) {
+            (Some(pivot), Some(value)) => Ok(LInsert {
+                key,
+                before,
+                pivot,
+                value,
+            }),
+            _ => Err(CmdErr::SyntaxErr),
+        }
+    }
+}
+
+impl CmdExecutor for LRem {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.lrem(&self.key, self.count, &self.value) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for LRem {
+    type Error = CmdErr;
+
+    // LREM key count element
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["lrem"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let count = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+        let value = args.next().ok_or(CmdErr::SyntaxErr)?;
+        Ok(LRem { key, count, value })
+    }
+}
+
+impl CmdExecutor for LTrim {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.ltrim(&self.key, self.start, self.stop) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for LTrim {
+    type Error = CmdErr;
+
+    // LTRIM key start stop
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["ltrim"], 3)?;
+
+        let (key, start, stop) = parse_range_args(value)?;
+        Ok(LTrim { key, start, stop })
+    }
+}
+
+impl CmdExecutor for LPos {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let opt = LPosOpt {
+            rank: self.rank,
+            count: self.count.unwrap_or(1),
+            max_len: self.max_len,
+        };
+        let indexes = match backend.lpos(&self.key, &self.value, opt) {
+            Ok(indexes) => indexes,
+            Err(e) => return e.into(),
+        };
+
+        if self.count.is_some() {
+            let indexes = indexes
+                .into_iter()
+                .map(|index| RespFrame::Integer(index as i64))
+                .collect::<Vec<_>>();
+            return Array::new(indexes).into();
+        }
+        match indexes.first() {
+            Some(index) => RespFrame::Integer(*index as i64),
+            None => RespFrame::Null(Null),
+        }
+    }
+}
+
+impl TryFrom<Array> for LPos {
+    type Error = CmdErr;
+
+    // LPOS key element [RANK rank] [COUNT num-matches] [MAXLEN len]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["lpos"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let value = args.next().ok_or(CmdErr::SyntaxErr)?;
+
+        let mut cmd = LPos {
+            key,
+            value,
+            rank: 1,
+            count: None,
+            max_len: 0,
+        };
+        while let Some(opt) = args.next() {
+            let opt = parse_opt(opt)?;
+            let arg = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+            match opt.as_str() {
+                "RANK" if arg == 0 => {
+                    return Err(CmdErr::Other(
+                        "RANK can't be zero: use 1 to start from the first match, \
+                        2 from the second ... or use negative to start from the end of the list"
+                            .to_string(),
+                    ))
+                }
+                // the rank is negated to search from the tail, so it must be negatable
+                "RANK" if arg == i64::MIN => {
+                    return Err(CmdErr::Other("value is out of range".to_string()))
+                }
+                "RANK" => cmd.rank = arg,
+                "COUNT" => {
+                    let count = usize::try_from(arg)
+                        .map_err(|_| CmdErr::Other("COUNT can't be negative".to_string()))?;
+                    cmd.count = Some(count);
+                }
+                "MAXLEN" => {
+                    cmd.max_len = usize::try_from(arg)
+                        .map_err(|_| CmdErr::Other("MAXLEN can't be negative".to_string()))?;
+                }
+                _ => return Err(CmdErr::SyntaxErr),
+            }
+        }
+
+        Ok(cmd)
+    }
+}
+
+// key element [element ...]
+fn parse_push_args(value: Array, name: &'static str) -> Result<(String, Vec<RespFrame>), CmdErr> {
+    validate_cmd_min(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = parse_key(args.next())?;
+    Ok((key, args.collect()))
+}
+
+// key [count]
+fn parse_pop_args(value: Array, name: &'static str) -> Result<(String, Option<usize>), CmdErr> {
+    validate_cmd_min(&value, &[name], 1)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = parse_key(args.next())?;
+    let count = args
+        .next()
+        .map(|count| {
+            usize::try_from(parse_int(count)?)
+                .map_err(|_| CmdErr::Other("value is out of range, must be positive".to_string()))
+        })
+        .transpose()?;
+    if args.next().is_some() {
+        return Err(CmdErr::SyntaxErr);
+    }
+
+    Ok((key, count))
+}
+
+// A single value is replied without count and an array of them with it,
+// a missing key is a null of the same shape.
+fn pop_reply(ret: Result<Option<Vec<RespFrame>>, BackendErr>, count: Option<usize>) -> RespFrame {
+    match (ret, count) {
+        (Ok(Some(values)), Some(_)) => Array::new(values).into(),
+        (Ok(None), Some(_)) => RespFrame::NullArray(NullArray),
+        (Ok(values), None) => match values.into_iter().flatten().next() {
+            Some(value) => value,
+            None => RespFrame::Null(Null),
+        },
+        (Err(e), _) => e.into(),
+    }
+}
+
+// numkeys key [key ...] <LEFT | RIGHT>
+pub(super) fn parse_mpop_keys(
+    args: &mut impl Iterator<Item = RespFrame>,
+) -> Result<(Vec<String>, ListEnd), CmdErr> {
+    let numkeys = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+    if numkeys <= 0 {
+        return Err(CmdErr::Other(
+            "numkeys should be greater than 0".to_string(),
+        ));
+    }
+
+    let keys = args
+        .take(numkeys as usize)
+        .map(|key| parse_key(Some(key)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if keys.len() != numkeys as usize {
+        return Err(CmdErr::SyntaxErr);
+    }
+
+    let end = parse_list_end(args.next())?;
+    Ok((keys, end))
+}
+
+pub(super) fn parse_mpop_count(frame: Option<RespFrame>) -> Result<usize, CmdErr> {
+    match parse_int(frame.ok_or(CmdErr::SyntaxErr)?)? {
+        count if count > 0 => Ok(count as usize),
+        _ => Err(CmdErr::Other("count should be greater than 0".to_string())),
+    }
+}
+
+pub(super) fn parse_list_end(frame: Option<RespFrame>) -> Result<ListEnd, CmdErr> {
+    match parse_opt(frame.ok_or(CmdErr::SyntaxErr)?)?.as_str() {
+        "LEFT" => Ok(ListEnd::Left),
+        "RIGHT" => Ok(ListEnd::Right),
+        _ => Err(CmdErr::SyntaxErr),
+    }
+}
+
+pub(super) fn parse_key(frame: Option<RespFrame>) -> Result<String, CmdErr> {
+    match frame {
+        Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.0)?),
+        _ => Err(CmdErr::InvalidArg("Invalid key".to_string())),
+    }
+}
+
+// key start stop
+fn parse_range_args(value: Array) -> Result<(String, i64, i64), CmdErr> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = parse_key(args.next())?;
+    let start = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+    let stop = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+    Ok((key, start, stop))
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
     use bytes::BytesMut;
 
-    use crate::RespDecode;
+    use crate::{BackendErr, BulkString, RespDecode};
 
     use super::*;
 
@@ -138,7 +570,7 @@ mod tests {
 
         let cmd: LPush = frame.try_into()?;
         assert_eq!(cmd.key, "jrmarcco");
-        assert_eq!(cmd.value, RespFrame::BulkString("hello".into()));
+        assert_eq!(cmd.values, [RespFrame::BulkString("hello".into())]);
 
         Ok(())
     }
@@ -165,7 +597,7 @@ mod tests {
 
         let cmd: RPush = frame.try_into()?;
         assert_eq!(cmd.key, "jrmarcco");
-        assert_eq!(cmd.value, RespFrame::BulkString("hello".into()));
+        assert_eq!(cmd.values, [RespFrame::BulkString("hello".into())]);
 
         Ok(())
     }
@@ -207,22 +639,23 @@ mod tests {
 
         let cmd = LPush {
             key: "jrmarcco".to_string(),
-            value: RespFrame::BulkString("hello".into()),
+            values: vec![RespFrame::BulkString("hello".into())],
         };
 
         let ret = cmd.exec(&backend);
-        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(ret, RespFrame::Integer(1));
 
         let cmd = LPush {
             key: "jrmarcco".to_string(),
-            value: RespFrame::BulkString("world".into()),
+            values: vec![RespFrame::BulkString("world".into())],
         };
 
         let ret = cmd.exec(&backend);
-        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(ret, RespFrame::Integer(2));
 
         let cmd = LPop {
             key: "jrmarcco".to_string(),
+            count: None,
         };
 
         let ret = cmd.exec(&backend);
@@ -230,20 +663,21 @@ mod tests {
 
         let cmd = RPush {
             key: "jrmarcco".to_string(),
-            value: RespFrame::Double(1.23),
+            values: vec![RespFrame::Double(1.23)],
         };
         let ret = cmd.exec(&backend);
-        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(ret, RespFrame::Integer(2));
 
         let cmd = RPush {
             key: "jrmarcco".to_string(),
-            value: RespFrame::Double(4.56),
+            values: vec![RespFrame::Double(4.56)],
         };
         let ret = cmd.exec(&backend);
-        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(ret, RespFrame::Integer(3));
 
         let cmd = RPop {
             key: "jrmarcco".to_string(),
+            count: None,
         };
         let ret = cmd.exec(&backend);
         assert_eq!(ret, RespFrame::Double(4.56));
@@ -256,4 +690,257 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_list_cmd_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset(
+            "jrmarcco".to_string(),
+            vec![("hello".to_string(), RespFrame::BulkString("world".into()))],
+        )?;
+
+        let cmd = LPush {
+            key: "jrmarcco".to_string(),
+            values: vec![RespFrame::BulkString("hello".into())],
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        let cmd = RPop {
+            key: "jrmarcco".to_string(),
+            count: None,
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        let cmd = LLen {
+            key: "jrmarcco".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        Ok(())
+    }
+
+    fn array(args: &[&str]) -> Array {
+        Array::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    fn frames(values: &[&str]) -> RespFrame {
+        Array::new(
+            values
+                .iter()
+                .map(|v| BulkString::from(*v).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_linsert_from_array() -> Result<()> {
+        let cmd = LInsert::try_from(array(&["linsert", "jrmarcco", "after", "a", "b"]))?;
+        assert_eq!(cmd.key, "jrmarcco");
+        assert!(!cmd.before);
+        assert_eq!(cmd.pivot, BulkString::from("a").into());
+        assert_eq!(cmd.value, BulkString::from("b").into());
+
+        assert!(LInsert::try_from(array(&["linsert", "jrmarcco", "middle", "a", "b"])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpos_from_array() -> Result<()> {
+        let cmd = LPos::try_from(array(&["lpos", "jrmarcco", "a"]))?;
+        assert_eq!((cmd.rank, cmd.count, cmd.max_len), (1, None, 0));
+
+        let args = [
+            "lpos", "jrmarcco", "a", "rank", "-2", "count", "0", "maxlen", "5",
+        ];
+        let cmd = LPos::try_from(array(&args))?;
+        assert_eq!((cmd.rank, cmd.count, cmd.max_len), (-2, Some(0), 5));
+
+        let ret = LPos::try_from(array(&["lpos", "jrmarcco", "a", "rank", "0"]));
+        assert!(ret
+            .unwrap_err()
+            .to_string()
+            .starts_with("ERR RANK can't be zero"));
+        let ret = LPos::try_from(array(&["lpos", "jrmarcco", "a", "count", "-1"]));
+        assert_eq!(ret.unwrap_err().to_string(), "ERR COUNT can't be negative");
+        assert!(LPos::try_from(array(&["lpos", "jrmarcco", "a", "maxlen"])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_index_cmd() -> Result<()> {
+        let backend = Backend::new();
+        for value in ["a", "b", "c", "b"] {
+            backend.rpush("jrmarcco".to_string(), vec![BulkString::from(value).into()])?;
+        }
+
+        let ret = LRange::try_from(array(&["lrange", "jrmarcco", "1", "-2"]))?.exec(&backend);
+        assert_eq!(ret, frames(&["b", "c"]));
+        let ret = LRange::try_from(array(&["lrange", "missing", "0", "-1"]))?.exec(&backend);
+        assert_eq!(ret, frames(&[]));
+
+        let ret = LIndex::try_from(array(&["lindex", "jrmarcco", "-1"]))?.exec(&backend);
+        assert_eq!(ret, BulkString::from("b").into());
+        let ret = LIndex::try_from(array(&["lindex", "jrmarcco", "4"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+
+        let ret = LSet::try_from(array(&["lset", "jrmarcco", "-4", "x"]))?.exec(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+        let ret = LSet::try_from(array(&["lset", "jrmarcco", "-5", "x"]))?.exec(&backend);
+        assert_eq!(ret, BackendErr::IndexOutOfRange.into());
+        let ret = LSet::try_from(array(&["lset", "missing", "0", "x"]))?.exec(&backend);
+        assert_eq!(ret, BackendErr::NoSuchKey.into());
+
+        let args = ["linsert", "jrmarcco", "before", "b", "y"];
+        let ret = LInsert::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(5));
+        let args = ["linsert", "jrmarcco", "after", "missing", "y"];
+        let ret = LInsert::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(-1));
+        let args = ["linsert", "missing", "after", "b", "y"];
+        let ret = LInsert::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+
+        let ret = LRange::try_from(array(&["lrange", "jrmarcco", "0", "-1"]))?.exec(&backend);
+        assert_eq!(ret, frames(&["x", "y", "b", "c", "b"]));
+
+        let ret = LPos::try_from(array(&["lpos", "jrmarcco", "b"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        let ret = LPos::try_from(array(&[
+            "lpos", "jrmarcco", "b", "rank", "-1", "count", "0",
+        ]))?
+        .exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new([RespFrame::Integer(4), RespFrame::Integer(2)]).into()
+        );
+        let ret = LPos::try_from(array(&["lpos", "jrmarcco", "z"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+
+        let ret = LRem::try_from(array(&["lrem", "jrmarcco", "0", "b"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+
+        let ret = LTrim::try_from(array(&["ltrim", "jrmarcco", "1", "-1"]))?.exec(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+        let ret = LRange::try_from(array(&["lrange", "jrmarcco", "0", "-1"]))?.exec(&backend);
+        assert_eq!(ret, frames(&["y", "c"]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_count_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let ret = LPushX::try_from(array(&["lpushx", "jrmarcco", "a"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+        let ret = RPush::try_from(array(&["rpush", "jrmarcco", "a", "b", "c"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(3));
+        let ret = LPushX::try_from(array(&["lpushx", "jrmarcco", "y", "z"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(5));
+        let ret = RPushX::try_from(array(&["rpushx", "jrmarcco", "d"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(6));
+
+        let ret = LPop::try_from(array(&["lpop", "jrmarcco", "2"]))?.exec(&backend);
+        assert_eq!(ret, frames(&["z", "y"]));
+        let ret = RPop::try_from(array(&["rpop", "jrmarcco", "10"]))?.exec(&backend);
+        assert_eq!(ret, frames(&["d", "c", "b", "a"]));
+        assert_eq!(backend.exists(&["jrmarcco".to_string()]), 0);
+
+        let ret = LPop::try_from(array(&["lpop", "jrmarcco", "2"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::NullArray(NullArray));
+        let ret = RPop::try_from(array(&["rpop", "jrmarcco"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+
+        let ret = LPop::try_from(array(&["lpop", "jrmarcco", "-1"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR value is out of range, must be positive"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmpop_from_array() -> Result<()> {
+        let cmd = LMPop::try_from(array(&["lmpop", "2", "a", "b", "right", "count", "3"]))?;
+        assert_eq!(cmd.keys, ["a", "b"]);
+        assert_eq!((cmd.end, cmd.count), (ListEnd::Right, 3));
+
+        let ret = LMPop::try_from(array(&["lmpop", "0", "a", "left"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR numkeys should be greater than 0"
+        );
+        let ret = LMPop::try_from(array(&["lmpop", "1", "a", "left", "count", "0"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR count should be greater than 0"
+        );
+        assert!(LMPop::try_from(array(&["lmpop", "3", "a", "b", "left"])).is_err());
+        assert!(LMPop::try_from(array(&["lmpop", "1", "a", "middle"])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_move_cmd() -> Result<()> {
+        let backend = Backend::new();
+        RPush::try_from(array(&["rpush", "pending", "a", "b", "c"]))?.exec(&backend);
+
+        let args = ["lmove", "pending", "processing", "left", "right"];
+        let ret = LMove::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, BulkString::from("a").into());
+        let ret =
+            RPopLPush::try_from(array(&["rpoplpush", "pending", "processing"]))?.exec(&backend);
+        assert_eq!(ret, BulkString::from("c").into());
+        let ret = LRange::try_from(array(&["lrange", "processing", "0", "-1"]))?.exec(&backend);
+        assert_eq!(ret, frames(&["c", "a"]));
+
+        // the same key rotates the list
+        let ret =
+            RPopLPush::try_from(array(&["rpoplpush", "processing", "processing"]))?.exec(&backend);
+        assert_eq!(ret, BulkString::from("a").into());
+        let ret = LRange::try_from(array(&["lrange", "processing", "0", "-1"]))?.exec(&backend);
+        assert_eq!(ret, frames(&["a", "c"]));
+
+        backend.set("string".to_string(), BulkString::from("v"));
+        let args = ["lmove", "pending", "string", "left", "left"];
+        let ret = LMove::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+        let args = ["lmove", "missing", "string", "left", "left"];
+        let ret = LMove::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+
+        // the last element moved away removes the source
+        let ret =
+            RPopLPush::try_from(array(&["rpoplpush", "pending", "processing"]))?.exec(&backend);
+        assert_eq!(ret, BulkString::from("b").into());
+        assert_eq!(backend.exists(&["pending".to_string()]), 0);
+
+        let args = ["lmpop", "2", "pending", "processing", "left", "count", "2"];
+        let ret = LMPop::try_from(array(&args))?.exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new([BulkString::from("processing").into(), frames(&["b", "a"])]).into()
+        );
+        let ret = LMPop::try_from(array(&["lmpop", "1", "processing", "left"]))?.exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new([BulkString::from("processing").into(), frames(&["c"])]).into()
+        );
+        let ret = LMPop::try_from(array(&["lmpop", "1", "processing", "left"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::NullArray(NullArray));
+
+        Ok(())
+    }
 }
@@ -1,61 +1,778 @@
 // map cmd
 use crate::{
-    cmd::{extract_args, validate_cmd, CmdErr, CmdExecutor, Get, Set, RESP_OK},
-    Array, Backend, Null, RespFrame,
+    cmd::{
+        extract_args, parse_float, parse_int, parse_opt, validate_cmd, validate_cmd_min, Append,
+        CmdErr, CmdExecutor, Decr, DecrBy, Get, GetDel, GetEx, GetRange, GetSet, Incr, IncrBy,
+        IncrByFloat, Lcs, MGet, MSet, MSetNx, Set, SetExpire, SetRange, StrLen, RESP_OK,
+    },
+    now_ms, Array, Backend, BackendErr, BulkString, Null, RespFrame, SetOpt, MAX_STRING_LEN,
 };
 
 // cmd set
 impl CmdExecutor for Set {
     fn exec(self, backend: &Backend) -> RespFrame {
-        backend.set(self.key, self.value);
+        let expire_at = match self.expire.map(|expire| expire.resolve(now_ms())) {
+            Some(Some(at)) => Some(at),
+            Some(None) => {
+                return CmdErr::Other("invalid expire time in 'set' command".to_string()).into()
+            }
+            None => None,
+        };
+
+        let opt = SetOpt {
+            expire_at,
+            ..self.opt
+        };
+        match backend.set_opt(self.key, self.value, opt) {
+            Ok((_, old)) if opt.get => old.map_or(RespFrame::Null(Null), RespFrame::from),
+            Ok((true, _)) => RESP_OK.clone(),
+            Ok((false, _)) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for Set {
+    type Error = CmdErr;
+
+    // SET key value [NX | XX] [GET] [EX seconds | PX milliseconds |
+    //   EXAT unix-time-seconds | PXAT unix-time-milliseconds | KEEPTTL]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["set"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key, value) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(value))) => {
+                (String::from_utf8(key.0)?, value)
+            }
+            _ => return Err(CmdErr::InvalidArg("Invalid key or value.".to_string())),
+        };
+
+        let mut opt = SetOpt::default();
+        let mut expire = None;
+        while let Some(arg) = args.next() {
+            match parse_opt(arg)?.as_str() {
+                "NX" if !opt.xx => opt.nx = true,
+                "XX" if !opt.nx => opt.xx = true,
+                "GET" => opt.get = true,
+                "KEEPTTL" if expire.is_none() => opt.keep_ttl = true,
+                name @ ("EX" | "PX" | "EXAT" | "PXAT") if expire.is_none() && !opt.keep_ttl => {
+                    let time = args.next().ok_or(CmdErr::SyntaxErr)?;
+                    expire = Some(SetExpire::parse(name, time, "set")?);
+                }
+                _ => return Err(CmdErr::SyntaxErr),
+            }
+        }
+
+        Ok(Set {
+            key,
+            value,
+            opt,
+            expire,
+        })
+    }
+}
+
+impl SetExpire {
+    pub(crate) fn parse(name: &str, time: RespFrame, cmd: &'static str) -> Result<Self, CmdErr> {
+        let time = parse_int(time)?;
+        if time <= 0 {
+            return Err(CmdErr::Other(format!(
+                "invalid expire time in '{}' command",
+                cmd
+            )));
+        }
+
+        match name {
+            "EX" => Ok(SetExpire::Ex(time)),
+            "PX" => Ok(SetExpire::Px(time)),
+            "EXAT" => Ok(SetExpire::ExAt(time)),
+            "PXAT" => Ok(SetExpire::PxAt(time)),
+            _ => Err(CmdErr::SyntaxErr),
+        }
+    }
+
+    // Absolute expire time in unix milliseconds, None if it overflows.
+    pub(crate) fn resolve(self, now: i64) -> Option<i64> {
+        match self {
+            SetExpire::Ex(seconds) => seconds.checked_mul(1000).and_then(|ms| ms.checked_add(now)),
+            SetExpire::Px(ms) => ms.checked_add(now),
+            SetExpire::ExAt(seconds) => seconds.checked_mul(1000),
+            SetExpire::PxAt(ms) => Some(ms),
+        }
+    }
+}
+
+// cmd get
+impl CmdExecutor for Get {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.get(&self.key) {
+            Ok(Some(value)) => value.into(),
+            Ok(None) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for Get {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["get"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Get {
+                key: String::from_utf8(key.0)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        }
+    }
+}
+
+// cmd incr
+impl CmdExecutor for Incr {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        incr_generic(backend, self.key, 1)
+    }
+}
+
+impl TryFrom<Array> for Incr {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["incr"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Incr {
+                key: String::from_utf8(key.0)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        }
+    }
+}
+
+// cmd decr
+impl CmdExecutor for Decr {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        incr_generic(backend, self.key, -1)
+    }
+}
+
+impl TryFrom<Array> for Decr {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["decr"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Decr {
+                key: String::from_utf8(key.0)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        }
+    }
+}
+
+// cmd incrby
+impl CmdExecutor for IncrBy {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        incr_generic(backend, self.key, self.increment)
+    }
+}
+
+impl TryFrom<Array> for IncrBy {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["incrby"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(increment)) => Ok(IncrBy {
+                key: String::from_utf8(key.0)?,
+                increment: parse_int(increment)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key or increment.".to_string())),
+        }
+    }
+}
+
+// cmd decrby
+impl CmdExecutor for DecrBy {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match self.decrement.checked_neg() {
+            Some(delta) => incr_generic(backend, self.key, delta),
+            None => CmdErr::Other("decrement would overflow".to_string()).into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for DecrBy {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["decrby"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(decrement)) => Ok(DecrBy {
+                key: String::from_utf8(key.0)?,
+                decrement: parse_int(decrement)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key or decrement.".to_string())),
+        }
+    }
+}
+
+// cmd incrbyfloat
+impl CmdExecutor for IncrByFloat {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.incr_by_float(self.key, self.increment) {
+            Ok(value) => value.into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for IncrByFloat {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["incrbyfloat"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(increment)) => Ok(IncrByFloat {
+                key: String::from_utf8(key.0)?,
+                increment: parse_float(increment)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key or increment.".to_string())),
+        }
+    }
+}
+
+// cmd mset
+impl CmdExecutor for MSet {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        backend.mset(self.pairs);
         RESP_OK.clone()
     }
 }
 
-impl TryFrom<Array> for Set {
+impl TryFrom<Array> for MSet {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(MSet {
+            pairs: parse_pairs(value, "mset")?,
+        })
+    }
+}
+
+// cmd msetnx
+impl CmdExecutor for MSetNx {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.mset_nx(self.pairs) as i64)
+    }
+}
+
+impl TryFrom<Array> for MSetNx {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        Ok(MSetNx {
+            pairs: parse_pairs(value, "msetnx")?,
+        })
+    }
+}
+
+// cmd mget
+impl CmdExecutor for MGet {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let ret = backend
+            .mget(&self.keys)
+            .into_iter()
+            .map(|value| value.map_or(RespFrame::Null(Null), RespFrame::from))
+            .collect::<Vec<RespFrame>>();
+
+        Array::new(ret).into()
+    }
+}
+
+impl TryFrom<Array> for MGet {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["mget"], 1)?;
+
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| match arg {
+                RespFrame::BulkString(key) => Ok(String::from_utf8(key.0)?),
+                _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+            })
+            .collect::<Result<Vec<String>, CmdErr>>()?;
+
+        Ok(MGet { keys })
+    }
+}
+
+// cmd getdel
+impl CmdExecutor for GetDel {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.get_del(&self.key) {
+            Ok(Some(value)) => value.into(),
+            Ok(None) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for GetDel {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["getdel"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(GetDel {
+                key: String::from_utf8(key.0)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        }
+    }
+}
+
+// cmd getex
+impl CmdExecutor for GetEx {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let ttl = match self.expire.map(|expire| expire.resolve(now_ms())) {
+            Some(Some(at)) => Some(Some(at)),
+            Some(None) => {
+                return CmdErr::Other("invalid expire time in 'getex' command".to_string()).into()
+            }
+            None if self.persist => Some(None),
+            None => None,
+        };
+
+        match backend.get_ex(&self.key, ttl) {
+            Ok(Some(value)) => value.into(),
+            Ok(None) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for GetEx {
+    type Error = CmdErr;
+
+    // GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+    //   PXAT unix-time-milliseconds | PERSIST]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["getex"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            _ => return Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        };
+
+        let (mut expire, mut persist) = (None, false);
+        while let Some(arg) = args.next() {
+            match parse_opt(arg)?.as_str() {
+                "PERSIST" if expire.is_none() => persist = true,
+                name @ ("EX" | "PX" | "EXAT" | "PXAT") if expire.is_none() && !persist => {
+                    let time = args.next().ok_or(CmdErr::SyntaxErr)?;
+                    expire = Some(SetExpire::parse(name, time, "getex")?);
+                }
+                _ => return Err(CmdErr::SyntaxErr),
+            }
+        }
+
+        Ok(GetEx {
+            key,
+            expire,
+            persist,
+        })
+    }
+}
+
+// cmd getset
+impl CmdExecutor for GetSet {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let opt = SetOpt {
+            get: true,
+            ..Default::default()
+        };
+
+        match backend.set_opt(self.key, self.value, opt) {
+            Ok((_, Some(old))) => old.into(),
+            Ok((_, None)) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for GetSet {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["getset"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(value))) => Ok(GetSet {
+                key: String::from_utf8(key.0)?,
+                value,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key or value.".to_string())),
+        }
+    }
+}
+
+// cmd append
+impl CmdExecutor for Append {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.append(self.key, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for Append {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["append"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(value))) => Ok(Append {
+                key: String::from_utf8(key.0)?,
+                value,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key or value.".to_string())),
+        }
+    }
+}
+
+// cmd strlen
+impl CmdExecutor for StrLen {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.strlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for StrLen {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["strlen"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(StrLen {
+                key: String::from_utf8(key.0)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        }
+    }
+}
+
+// cmd getrange
+impl CmdExecutor for GetRange {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.get_range(&self.key, self.start, self.end) {
+            Ok(value) => value.into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for GetRange {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["getrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(start), Some(end)) => Ok(GetRange {
+                key: String::from_utf8(key.0)?,
+                start: parse_int(start)?,
+                end: parse_int(end)?,
+            }),
+            _ => Err(CmdErr::InvalidArg("Invalid key, start or end.".to_string())),
+        }
+    }
+}
+
+// cmd setrange
+impl CmdExecutor for SetRange {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.set_range(self.key, self.offset, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SetRange {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["setrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(offset),
+                Some(RespFrame::BulkString(value)),
+            ) => {
+                let offset = parse_int(offset)?;
+                if offset < 0 {
+                    return Err(CmdErr::Other("offset is out of range".to_string()));
+                }
+
+                Ok(SetRange {
+                    key: String::from_utf8(key.0)?,
+                    offset: offset as usize,
+                    value,
+                })
+            }
+            _ => Err(CmdErr::InvalidArg(
+                "Invalid key, offset or value.".to_string(),
+            )),
+        }
+    }
+}
+
+// cmd lcs
+impl CmdExecutor for Lcs {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let (a, b) = match backend.get_strings(&[&self.key1, &self.key2]) {
+            Ok(values) => (values[0].clone(), values[1].clone()),
+            Err(BackendErr::WrongType) => {
+                return CmdErr::Other("The specified keys must contain string values".to_string())
+                    .into()
+            }
+            Err(e) => return e.into(),
+        };
+
+        // the dp table is transient memory that must not exceed proto-max-bulk-len
+        let table_size = (a.len() + 1)
+            .checked_mul(b.len() + 1)
+            .and_then(|size| size.checked_mul(size_of::<u32>()));
+        if table_size.is_none_or(|size| size > MAX_STRING_LEN) {
+            return CmdErr::Other(
+                "Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len"
+                    .to_string(),
+            )
+            .into();
+        }
+
+        let (lcs, matches) = lcs(&a, &b, self.idx, self.min_match_len);
+        if self.len {
+            return RespFrame::Integer(lcs.len() as i64);
+        }
+        if !self.idx {
+            return BulkString::new(lcs).into();
+        }
+
+        let matches = matches
+            .into_iter()
+            .map(|m| {
+                let mut item: Vec<RespFrame> = vec![
+                    Array::new(vec![(m.a.0 as i64).into(), (m.a.1 as i64).into()]).into(),
+                    Array::new(vec![(m.b.0 as i64).into(), (m.b.1 as i64).into()]).into(),
+                ];
+                if self.with_match_len {
+                    item.push(((m.a.1 - m.a.0 + 1) as i64).into());
+                }
+                Array::new(item).into()
+            })
+            .collect::<Vec<RespFrame>>();
+
+        Array::new(vec![
+            BulkString::from("matches").into(),
+            Array::new(matches).into(),
+            BulkString::from("len").into(),
+            RespFrame::Integer(lcs.len() as i64),
+        ])
+        .into()
+    }
+}
+
+impl TryFrom<Array> for Lcs {
     type Error = CmdErr;
 
+    // LCS key1 key2 [LEN] [IDX] [MINMATCHLEN min-match-len] [WITHMATCHLEN]
     fn try_from(value: Array) -> Result<Self, Self::Error> {
-        validate_cmd(&value, &["set"], 2)?;
+        validate_cmd_min(&value, &["lcs"], 2)?;
 
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(Set {
-                key: String::from_utf8(key.0)?,
-                value,
-            }),
-            _ => Err(CmdErr::InvalidArg("Invalid key or value.".to_string())),
+        let (key1, key2) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key1)), Some(RespFrame::BulkString(key2))) => {
+                (String::from_utf8(key1.0)?, String::from_utf8(key2.0)?)
+            }
+            _ => return Err(CmdErr::InvalidArg("Invalid keys.".to_string())),
+        };
+
+        let mut cmd = Lcs {
+            key1,
+            key2,
+            len: false,
+            idx: false,
+            min_match_len: 0,
+            with_match_len: false,
+        };
+        while let Some(arg) = args.next() {
+            match parse_opt(arg)?.as_str() {
+                "LEN" => cmd.len = true,
+                "IDX" => cmd.idx = true,
+                "WITHMATCHLEN" => cmd.with_match_len = true,
+                "MINMATCHLEN" => {
+                    let len = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+                    cmd.min_match_len = len.max(0) as usize;
+                }
+                _ => return Err(CmdErr::SyntaxErr),
+            }
+        }
+
+        if cmd.len && cmd.idx {
+            return Err(CmdErr::Other(
+                "If you want both the length and indexes, please just use IDX.".to_string(),
+            ));
         }
+
+        Ok(cmd)
     }
 }
 
-// cmd get
-impl CmdExecutor for Get {
-    fn exec(self, backend: &Backend) -> RespFrame {
-        backend.get(&self.key).unwrap_or(RespFrame::Null(Null))
-    }
+// A pair of ranges matched in both strings, offsets are inclusive.
+#[derive(Debug, PartialEq)]
+struct LcsMatch {
+    a: (usize, usize),
+    b: (usize, usize),
 }
 
-impl TryFrom<Array> for Get {
-    type Error = CmdErr;
+// Compute the longest common subsequence with dynamic programming,
+// then backtrack from the end collecting matched ranges the same order redis replies them.
+fn lcs(a: &[u8], b: &[u8], with_matches: bool, min_match_len: usize) -> (Vec<u8>, Vec<LcsMatch>) {
+    let (alen, blen) = (a.len(), b.len());
+    let width = blen + 1;
 
-    fn try_from(value: Array) -> Result<Self, Self::Error> {
-        validate_cmd(&value, &["get"], 1)?;
+    let mut dp = vec![0u32; (alen + 1) * width];
+    for i in 1..=alen {
+        for j in 1..=blen {
+            dp[i * width + j] = if a[i - 1] == b[j - 1] {
+                dp[(i - 1) * width + j - 1] + 1
+            } else {
+                dp[(i - 1) * width + j].max(dp[i * width + j - 1])
+            };
+        }
+    }
 
-        let mut args = extract_args(value, 1)?.into_iter();
-        match args.next() {
-            Some(RespFrame::BulkString(key)) => Ok(Get {
-                key: String::from_utf8(key.0)?,
-            }),
-            _ => Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+    let mut idx = dp[alen * width + blen] as usize;
+    let mut lcs = vec![0u8; idx];
+    let mut matches = Vec::new();
+
+    let (mut i, mut j) = (alen, blen);
+    // alen as the start of range means no range is being tracked
+    let (mut arange_start, mut arange_end, mut brange_start, mut brange_end) = (alen, 0, 0, 0);
+    while i > 0 && j > 0 {
+        let mut emit_range = false;
+        if a[i - 1] == b[j - 1] {
+            lcs[idx - 1] = a[i - 1];
+            if arange_start == alen {
+                arange_start = i - 1;
+                arange_end = i - 1;
+                brange_start = j - 1;
+                brange_end = j - 1;
+            } else if arange_start == i && brange_start == j {
+                // extend the range backward since it is contiguous
+                arange_start -= 1;
+                brange_start -= 1;
+            } else {
+                emit_range = true;
+            }
+
+            // emit the range if we matched with the first byte of one of the two strings
+            if arange_start == 0 || brange_start == 0 {
+                emit_range = true;
+            }
+            idx -= 1;
+            i -= 1;
+            j -= 1;
+        } else {
+            if dp[(i - 1) * width + j] > dp[i * width + j - 1] {
+                i -= 1;
+            } else {
+                j -= 1;
+            }
+            if arange_start != alen {
+                emit_range = true;
+            }
+        }
+
+        if emit_range {
+            let match_len = arange_end - arange_start + 1;
+            if with_matches && (min_match_len == 0 || match_len >= min_match_len) {
+                matches.push(LcsMatch {
+                    a: (arange_start, arange_end),
+                    b: (brange_start, brange_end),
+                });
+            }
+            arange_start = alen;
+        }
+    }
+
+    (lcs, matches)
+}
+
+fn parse_pairs(value: Array, name: &'static str) -> Result<Vec<(String, BulkString)>, CmdErr> {
+    validate_cmd_min(&value, &[name], 2)?;
+    if value.len().is_multiple_of(2) {
+        return Err(CmdErr::Other(format!(
+            "wrong number of arguments for '{}' command",
+            name
+        )));
+    }
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let mut pairs = Vec::with_capacity(args.len() / 2);
+    while let (Some(key), Some(value)) = (args.next(), args.next()) {
+        match (key, value) {
+            (RespFrame::BulkString(key), RespFrame::BulkString(value)) => {
+                pairs.push((String::from_utf8(key.0)?, value))
+            }
+            _ => return Err(CmdErr::InvalidArg("Invalid key or value.".to_string())),
         }
     }
+
+    Ok(pairs)
+}
+
+fn incr_generic(backend: &Backend, key: String, delta: i64) -> RespFrame {
+    match backend.incr_by(key, delta) {
+        Ok(value) => RespFrame::Integer(value),
+        Err(e) => e.into(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::RespDecode;
+    use crate::{BackendErr, BulkString, RespDecode, SimpleError};
     use anyhow::Result;
     use bytes::BytesMut;
 
@@ -68,7 +785,45 @@ mod tests {
 
         let cmd: Set = frame.try_into()?;
         assert_eq!(cmd.key, "hello");
-        assert_eq!(cmd.value, RespFrame::BulkString("world".into()));
+        assert_eq!(cmd.value, BulkString::from("world"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_opt_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nnx\r\n$2\r\nPX\r\n$5\r\n30000\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+
+        let cmd: Set = frame.try_into()?;
+        assert_eq!(cmd.key, "hello");
+        assert!(cmd.opt.nx);
+        assert_eq!(cmd.expire, Some(SetExpire::Px(30000)));
+
+        let conflicts: [&[u8]; 5] = [
+            b"*5\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nNX\r\n$2\r\nXX\r\n",
+            b"*7\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nEX\r\n$1\r\n1\r\n$2\r\nPX\r\n$1\r\n1\r\n",
+            b"*6\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nEX\r\n$1\r\n1\r\n$7\r\nKEEPTTL\r\n",
+            b"*4\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nEX\r\n",
+            b"*4\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$3\r\nFOO\r\n",
+        ];
+        for conflict in conflicts {
+            buf.extend_from_slice(conflict);
+            let frame = Array::decode(&mut buf)?;
+            let ret: Result<Set, CmdErr> = frame.try_into();
+            assert!(matches!(ret, Err(CmdErr::SyntaxErr)));
+        }
+
+        buf.extend_from_slice(
+            b"*5\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nEX\r\n$1\r\n0\r\n",
+        );
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<Set, CmdErr> = frame.try_into();
+        assert!(matches!(ret, Err(CmdErr::Other(_))));
 
         Ok(())
     }
@@ -92,7 +847,9 @@ mod tests {
 
         let cmd = Set {
             key: "hello".to_string(),
-            value: RespFrame::BulkString("world".into()),
+            value: BulkString::from("world"),
+            opt: SetOpt::default(),
+            expire: None,
         };
 
         let ret = cmd.exec(&backend);
@@ -107,4 +864,461 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_opt_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = Set {
+            key: "lock".to_string(),
+            value: BulkString::from("owner"),
+            opt: SetOpt {
+                nx: true,
+                ..Default::default()
+            },
+            expire: Some(SetExpire::Px(30000)),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+        assert!(matches!(backend.expire_time("lock"), Some(Some(_))));
+
+        let cmd = Set {
+            key: "lock".to_string(),
+            value: BulkString::from("other"),
+            opt: SetOpt {
+                nx: true,
+                ..Default::default()
+            },
+            expire: Some(SetExpire::Px(30000)),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+
+        let cmd = Set {
+            key: "lock".to_string(),
+            value: BulkString::from("other"),
+            opt: SetOpt {
+                get: true,
+                ..Default::default()
+            },
+            expire: None,
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::BulkString("owner".into()));
+        assert_eq!(backend.expire_time("lock"), Some(None));
+
+        let cmd = Set {
+            key: "missing".to_string(),
+            value: BulkString::from("value"),
+            opt: SetOpt {
+                xx: true,
+                get: true,
+                ..Default::default()
+            },
+            expire: None,
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+
+        let cmd = Set {
+            key: "lock".to_string(),
+            value: BulkString::from("value"),
+            opt: SetOpt::default(),
+            expire: Some(SetExpire::Ex(i64::MAX)),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(
+            ret,
+            CmdErr::Other("invalid expire time in 'set' command".to_string()).into()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_cmd_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.lpush(
+            "jrmarcco".to_string(),
+            vec![RespFrame::BulkString("hello".into())],
+        )?;
+
+        let cmd = Get {
+            key: "jrmarcco".to_string(),
+        };
+
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        // set overwrites the key whatever type it holds
+        let cmd = Set {
+            key: "jrmarcco".to_string(),
+            value: BulkString::from("world"),
+            opt: SetOpt::default(),
+            expire: None,
+        };
+
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+
+        let cmd = Get {
+            key: "jrmarcco".to_string(),
+        };
+
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::BulkString("world".into()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nincrby\r\n$7\r\ncounter\r\n$2\r\n-5\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+
+        let cmd: IncrBy = frame.try_into()?;
+        assert_eq!(cmd.key, "counter");
+        assert_eq!(cmd.increment, -5);
+
+        buf.extend_from_slice(b"*3\r\n$6\r\nincrby\r\n$7\r\ncounter\r\n$3\r\n1.5\r\n");
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<IncrBy, CmdErr> = frame.try_into();
+        assert!(matches!(ret, Err(CmdErr::NotInteger)));
+
+        buf.extend_from_slice(b"*3\r\n$11\r\nincrbyfloat\r\n$7\r\ncounter\r\n$3\r\n1.5\r\n");
+        let frame = Array::decode(&mut buf)?;
+        let cmd: IncrByFloat = frame.try_into()?;
+        assert_eq!(cmd.increment, 1.5);
+
+        buf.extend_from_slice(b"*3\r\n$11\r\nincrbyfloat\r\n$7\r\ncounter\r\n$3\r\nabc\r\n");
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<IncrByFloat, CmdErr> = frame.try_into();
+        assert!(matches!(ret, Err(CmdErr::NotFloat)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = Incr {
+            key: "counter".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+
+        let cmd = IncrBy {
+            key: "counter".to_string(),
+            increment: 10,
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(11));
+
+        let cmd = Decr {
+            key: "counter".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(10));
+
+        let cmd = DecrBy {
+            key: "counter".to_string(),
+            decrement: 20,
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(-10));
+
+        let cmd = DecrBy {
+            key: "counter".to_string(),
+            decrement: i64::MIN,
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, SimpleError::new("ERR decrement would overflow").into());
+
+        let cmd = IncrByFloat {
+            key: "counter".to_string(),
+            increment: 0.5,
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::BulkString("-9.5".into()));
+
+        let cmd = Incr {
+            key: "counter".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BackendErr::NotInteger.into());
+
+        let cmd = Get {
+            key: "counter".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::BulkString("-9.5".into()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nmset\r\n$3\r\nfoo\r\n$1\r\n1\r\n$3\r\nbar\r\n$1\r\n2\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+
+        let cmd: MSet = frame.try_into()?;
+        assert_eq!(
+            cmd.pairs,
+            vec![
+                ("foo".to_string(), BulkString::from("1")),
+                ("bar".to_string(), BulkString::from("2")),
+            ]
+        );
+
+        buf.extend_from_slice(b"*4\r\n$6\r\nmsetnx\r\n$3\r\nfoo\r\n$1\r\n1\r\n$3\r\nbar\r\n");
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<MSetNx, CmdErr> = frame.try_into();
+        assert!(ret.is_err());
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nmget\r\n$3\r\nfoo\r\n$3\r\nbar\r\n");
+        let frame = Array::decode(&mut buf)?;
+        let cmd: MGet = frame.try_into()?;
+        assert_eq!(cmd.keys, vec!["foo".to_string(), "bar".to_string()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\ngetex\r\n$5\r\nhello\r\n$2\r\nex\r\n$2\r\n10\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+
+        let cmd: GetEx = frame.try_into()?;
+        assert_eq!(cmd.key, "hello");
+        assert_eq!(cmd.expire, Some(SetExpire::Ex(10)));
+        assert!(!cmd.persist);
+
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\ngetex\r\n$5\r\nhello\r\n$2\r\nex\r\n$2\r\n10\r\n$7\r\npersist\r\n",
+        );
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<GetEx, CmdErr> = frame.try_into();
+        assert!(matches!(ret, Err(CmdErr::SyntaxErr)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_key_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = MSet {
+            pairs: vec![
+                ("foo".to_string(), BulkString::from("1")),
+                ("bar".to_string(), BulkString::from("2")),
+            ],
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RESP_OK.clone());
+
+        let cmd = MSetNx {
+            pairs: vec![
+                ("bar".to_string(), BulkString::from("3")),
+                ("baz".to_string(), BulkString::from("3")),
+            ],
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+
+        let cmd = MGet {
+            keys: vec!["foo".to_string(), "bar".to_string(), "baz".to_string()],
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new(vec![
+                BulkString::from("1").into(),
+                BulkString::from("2").into(),
+                RespFrame::Null(Null),
+            ])
+            .into()
+        );
+
+        let cmd = GetSet {
+            key: "foo".to_string(),
+            value: BulkString::from("10"),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BulkString::from("1").into());
+
+        let cmd = GetEx {
+            key: "foo".to_string(),
+            expire: Some(SetExpire::Px(10_000)),
+            persist: false,
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BulkString::from("10").into());
+        assert!(matches!(backend.expire_time("foo"), Some(Some(_))));
+
+        let cmd = GetEx {
+            key: "foo".to_string(),
+            expire: None,
+            persist: true,
+        };
+        cmd.exec(&backend);
+        assert_eq!(backend.expire_time("foo"), Some(None));
+
+        let cmd = GetDel {
+            key: "foo".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BulkString::from("10").into());
+
+        let cmd = GetDel {
+            key: "foo".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*7\r\n$3\r\nlcs\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$3\r\nidx\r\n$11\r\nminmatchlen\r\n$1\r\n4\r\n$12\r\nwithmatchlen\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+
+        let cmd: Lcs = frame.try_into()?;
+        assert_eq!(cmd.key1, "key1");
+        assert_eq!(cmd.key2, "key2");
+        assert!(cmd.idx && cmd.with_match_len && !cmd.len);
+        assert_eq!(cmd.min_match_len, 4);
+
+        buf.extend_from_slice(
+            b"*5\r\n$3\r\nlcs\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$3\r\nidx\r\n$3\r\nlen\r\n",
+        );
+        let frame = Array::decode(&mut buf)?;
+        let ret: Result<Lcs, CmdErr> = frame.try_into();
+        assert!(matches!(ret, Err(CmdErr::Other(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_range_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = Append {
+            key: "hello".to_string(),
+            value: BulkString::from("Hello World"),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(11));
+
+        let cmd = SetRange {
+            key: "hello".to_string(),
+            offset: 6,
+            value: BulkString::from("Redis"),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(11));
+
+        let cmd = GetRange {
+            key: "hello".to_string(),
+            start: -5,
+            end: -1,
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, BulkString::from("Redis").into());
+
+        let cmd = StrLen {
+            key: "hello".to_string(),
+        };
+        let ret = cmd.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(11));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_cmd() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key1".to_string(), BulkString::from("ohmytext"));
+        backend.set("key2".to_string(), BulkString::from("mynewtext"));
+
+        let lcs = |key2: &str, len, idx, min_match_len, with_match_len| Lcs {
+            key1: "key1".to_string(),
+            key2: key2.to_string(),
+            len,
+            idx,
+            min_match_len,
+            with_match_len,
+        };
+
+        let ret = lcs("key2", false, false, 0, false).exec(&backend);
+        assert_eq!(ret, BulkString::from("mytext").into());
+
+        let ret = lcs("key2", true, false, 0, false).exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(6));
+
+        let ret = lcs("key2", false, true, 0, false).exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new(vec![
+                BulkString::from("matches").into(),
+                Array::new(vec![
+                    Array::new(vec![
+                        Array::new(vec![4.into(), 7.into()]).into(),
+                        Array::new(vec![5.into(), 8.into()]).into(),
+                    ])
+                    .into(),
+                    Array::new(vec![
+                        Array::new(vec![2.into(), 3.into()]).into(),
+                        Array::new(vec![0.into(), 1.into()]).into(),
+                    ])
+                    .into(),
+                ])
+                .into(),
+                BulkString::from("len").into(),
+                6.into(),
+            ])
+            .into()
+        );
+
+        let ret = lcs("key2", false, true, 4, true).exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new(vec![
+                BulkString::from("matches").into(),
+                Array::new(vec![Array::new(vec![
+                    Array::new(vec![4.into(), 7.into()]).into(),
+                    Array::new(vec![5.into(), 8.into()]).into(),
+                    4.into(),
+                ])
+                .into(),])
+                .into(),
+                BulkString::from("len").into(),
+                6.into(),
+            ])
+            .into()
+        );
+
+        backend.lpush(
+            "list".to_string(),
+            vec![RespFrame::BulkString("foo".into())],
+        )?;
+        let ret = lcs("list", false, false, 0, false).exec(&backend);
+        assert_eq!(
+            ret,
+            SimpleError::new("ERR The specified keys must contain string values").into()
+        );
+
+        Ok(())
+    }
 }
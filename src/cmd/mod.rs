@@ -1,98 +1,1006 @@
+mod bitfield;
+mod bitmap;
+mod blocking;
+mod db;
+mod expire;
+mod hash_expire;
 mod hash_map;
+mod keyspace;
 mod list;
 mod map;
+mod scan;
+mod server;
+mod set;
+mod sorted_set;
 
-use crate::{Array, Backend, RespErr, RespFrame, SimpleString};
-use enum_dispatch::enum_dispatch;
+use crate::{
+    parse_f64, parse_i64, Array, Backend, BitFieldOp, BitOperator, BitUnit, BulkString, ExpireOpt,
+    FieldCond, ListEnd, Map, RespErr, RespFrame, ScanOpt, SetOpt, SimpleError, SimpleString,
+    ZAddOpt, ZRangeBy, ZRangeOpt,
+};
 use lazy_static::lazy_static;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 use thiserror::Error;
 
 lazy_static! {
     static ref RESP_OK: RespFrame = SimpleString::new("Ok").into();
 }
 
-#[enum_dispatch]
 pub trait CmdExecutor {
     fn exec(self, backend: &Backend) -> RespFrame;
 }
 
-#[enum_dispatch(CmdExecutor)]
+type CmdParser = fn(Array) -> Result<Cmd, CmdErr>;
+
+macro_rules! cmd_table {
+    ($($name:literal => $cmd:ident),* $(,)?) => {
+        #[derive(Debug)]
+        pub enum Cmd {
+            $($cmd($cmd),)*
+        }
+
+        impl CmdExecutor for Cmd {
+            fn exec(self, backend: &Backend) -> RespFrame {
+                match self {
+                    $(Cmd::$cmd(cmd) => cmd.exec(backend),)*
+                }
+            }
+        }
+
+        $(impl From<$cmd> for Cmd {
+            fn from(cmd: $cmd) -> Self {
+                Cmd::$cmd(cmd)
+            }
+        })*
+
+        lazy_static! {
+            static ref CMD_TABLE: HashMap<&'static str, CmdParser> = HashMap::from([
+                $(($name, (|value| Ok($cmd::try_from(value)?.into())) as CmdParser),)*
+            ]);
+        }
+    };
+}
+
+// Every command registers here once with its lowercase name, which generates both the `Cmd`
+// variant and its entry in the case-insensitive dispatch table.
+cmd_table! {
+    "set" => Set,
+    "get" => Get,
+    "incr" => Incr,
+    "decr" => Decr,
+    "incrby" => IncrBy,
+    "decrby" => DecrBy,
+    "incrbyfloat" => IncrByFloat,
+    "mset" => MSet,
+    "msetnx" => MSetNx,
+    "mget" => MGet,
+    "getdel" => GetDel,
+    "getex" => GetEx,
+    "getset" => GetSet,
+    "append" => Append,
+    "strlen" => StrLen,
+    "getrange" => GetRange,
+    "setrange" => SetRange,
+    "lcs" => Lcs,
+    "setbit" => SetBit,
+    "getbit" => GetBit,
+    "bitcount" => BitCount,
+    "bitpos" => BitPos,
+    "bitop" => BitOp,
+    "bitfield" => BitField,
+    "bitfield_ro" => BitFieldRo,
+    "hset" => HSet,
+    "hget" => HGet,
+    "hgetall" => HGetAll,
+    "hsetnx" => HSetNx,
+    "hincrby" => HIncrBy,
+    "hincrbyfloat" => HIncrByFloat,
+    "hmget" => HMGet,
+    "hdel" => HDel,
+    "hexists" => HExists,
+    "hlen" => HLen,
+    "hkeys" => HKeys,
+    "hvals" => HVals,
+    "hstrlen" => HStrLen,
+    "hrandfield" => HRandField,
+    "hexpire" => HExpire,
+    "hpexpire" => HPExpire,
+    "httl" => HTtl,
+    "hpersist" => HPersist,
+    "hgetex" => HGetEx,
+    "hsetex" => HSetEx,
+    "lpush" => LPush,
+    "lpushx" => LPushX,
+    "lpop" => LPop,
+    "rpush" => RPush,
+    "rpushx" => RPushX,
+    "rpop" => RPop,
+    "lmove" => LMove,
+    "rpoplpush" => RPopLPush,
+    "lmpop" => LMPop,
+    "blpop" => BLPop,
+    "brpop" => BRPop,
+    "blmove" => BLMove,
+    "blmpop" => BLMPop,
+    "llen" => LLen,
+    "lrange" => LRange,
+    "lindex" => LIndex,
+    "lset" => LSet,
+    "linsert" => LInsert,
+    "lrem" => LRem,
+    "ltrim" => LTrim,
+    "lpos" => LPos,
+    "sadd" => SAdd,
+    "srem" => SRem,
+    "smembers" => SMembers,
+    "sismember" => SIsMember,
+    "smismember" => SMIsMember,
+    "scard" => SCard,
+    "spop" => SPop,
+    "srandmember" => SRandMember,
+    "smove" => SMove,
+    "sinter" => SInter,
+    "sunion" => SUnion,
+    "sdiff" => SDiff,
+    "sinterstore" => SInterStore,
+    "sunionstore" => SUnionStore,
+    "sdiffstore" => SDiffStore,
+    "sintercard" => SInterCard,
+    "zadd" => ZAdd,
+    "zrem" => ZRem,
+    "zscore" => ZScore,
+    "zmscore" => ZMScore,
+    "zincrby" => ZIncrBy,
+    "zcard" => ZCard,
+    "zrank" => ZRank,
+    "zrevrank" => ZRevRank,
+    "zrange" => ZRange,
+    "zrangestore" => ZRangeStore,
+    "zcount" => ZCount,
+    "zlexcount" => ZLexCount,
+    "zremrangebyrank" => ZRemRangeByRank,
+    "zremrangebyscore" => ZRemRangeByScore,
+    "zremrangebylex" => ZRemRangeByLex,
+    "expire" => Expire,
+    "pexpire" => PExpire,
+    "expireat" => ExpireAt,
+    "pexpireat" => PExpireAt,
+    "ttl" => Ttl,
+    "pttl" => PTtl,
+    "expiretime" => ExpireTime,
+    "pexpiretime" => PExpireTime,
+    "persist" => Persist,
+    "del" => Del,
+    "unlink" => Unlink,
+    "exists" => Exists,
+    "type" => Type,
+    "rename" => Rename,
+    "renamenx" => RenameNx,
+    "copy" => CopyKey,
+    "touch" => Touch,
+    "randomkey" => RandomKey,
+    "dbsize" => DbSize,
+    "flushdb" => FlushDb,
+    "flushall" => FlushAll,
+    "select" => Select,
+    "swapdb" => SwapDb,
+    "move" => Move,
+    "keys" => Keys,
+    "scan" => Scan,
+    "hscan" => HScan,
+    "hello" => Hello,
+    "config" => ConfigGet,
+}
+
+#[derive(Debug)]
+pub struct Set {
+    key: String,
+    value: BulkString,
+    opt: SetOpt,
+    expire: Option<SetExpire>,
+}
+
+// Expire time options of the set command, resolved to an absolute time on execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetExpire {
+    Ex(i64),
+    Px(i64),
+    ExAt(i64),
+    PxAt(i64),
+}
+
+#[derive(Debug)]
+pub struct Get {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Incr {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Decr {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct IncrBy {
+    key: String,
+    increment: i64,
+}
+
+#[derive(Debug)]
+pub struct DecrBy {
+    key: String,
+    decrement: i64,
+}
+
+#[derive(Debug)]
+pub struct IncrByFloat {
+    key: String,
+    increment: f64,
+}
+
+#[derive(Debug)]
+pub struct MSet {
+    pairs: Vec<(String, BulkString)>,
+}
+
+#[derive(Debug)]
+pub struct MSetNx {
+    pairs: Vec<(String, BulkString)>,
+}
+
+#[derive(Debug)]
+pub struct MGet {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct GetDel {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct GetEx {
+    key: String,
+    expire: Option<SetExpire>,
+    persist: bool,
+}
+
+#[derive(Debug)]
+pub struct GetSet {
+    key: String,
+    value: BulkString,
+}
+
+#[derive(Debug)]
+pub struct Append {
+    key: String,
+    value: BulkString,
+}
+
+#[derive(Debug)]
+pub struct StrLen {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct GetRange {
+    key: String,
+    start: i64,
+    end: i64,
+}
+
+#[derive(Debug)]
+pub struct SetRange {
+    key: String,
+    offset: usize,
+    value: BulkString,
+}
+
+#[derive(Debug)]
+pub struct Lcs {
+    key1: String,
+    key2: String,
+    len: bool,
+    idx: bool,
+    min_match_len: usize,
+    with_match_len: bool,
+}
+
+#[derive(Debug)]
+pub struct SetBit {
+    key: String,
+    offset: usize,
+    value: bool,
+}
+
+#[derive(Debug)]
+pub struct GetBit {
+    key: String,
+    offset: usize,
+}
+
+#[derive(Debug)]
+pub struct BitCount {
+    key: String,
+    range: Option<(i64, i64)>,
+    unit: BitUnit,
+}
+
+#[derive(Debug)]
+pub struct BitPos {
+    key: String,
+    bit: bool,
+    start: Option<i64>,
+    end: Option<i64>,
+    unit: BitUnit,
+}
+
+#[derive(Debug)]
+pub struct BitOp {
+    op: BitOperator,
+    dest: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct BitField {
+    key: String,
+    ops: Vec<BitFieldOp>,
+}
+
+#[derive(Debug)]
+pub struct BitFieldRo {
+    key: String,
+    ops: Vec<BitFieldOp>,
+}
+
+#[derive(Debug)]
+pub struct HSet {
+    key: String,
+    pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct HIncrBy {
+    key: String,
+    field: String,
+    increment: i64,
+}
+
+#[derive(Debug)]
+pub struct HIncrByFloat {
+    key: String,
+    field: String,
+    increment: f64,
+}
+
+#[derive(Debug)]
+pub struct HGet {
+    key: String,
+    field: String,
+}
+
+#[derive(Debug)]
+pub struct HGetAll {
+    key: String,
+    sort: bool,
+}
+
+#[derive(Debug)]
+pub struct HSetNx {
+    key: String,
+    field: String,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct HMGet {
+    key: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HDel {
+    key: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HExists {
+    key: String,
+    field: String,
+}
+
+#[derive(Debug)]
+pub struct HLen {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HKeys {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HVals {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct HStrLen {
+    key: String,
+    field: String,
+}
+
+// Without a count a single field is replied, a negative count allows repeated fields.
+#[derive(Debug)]
+pub struct HRandField {
+    key: String,
+    count: Option<i64>,
+    with_values: bool,
+}
+
+#[derive(Debug)]
+pub struct HExpire {
+    key: String,
+    seconds: i64,
+    opt: ExpireOpt,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HPExpire {
+    key: String,
+    milliseconds: i64,
+    opt: ExpireOpt,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HTtl {
+    key: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HPersist {
+    key: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HGetEx {
+    key: String,
+    expire: Option<SetExpire>,
+    persist: bool,
+    fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HSetEx {
+    key: String,
+    cond: FieldCond,
+    expire: Option<SetExpire>,
+    keep_ttl: bool,
+    pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct LPush {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct LPushX {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct LPop {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct RPush {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct RPushX {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct RPop {
+    key: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct LMove {
+    src: String,
+    dest: String,
+    from: ListEnd,
+    to: ListEnd,
+}
+
+#[derive(Debug)]
+pub struct RPopLPush {
+    src: String,
+    dest: String,
+}
+
+#[derive(Debug)]
+pub struct LMPop {
+    keys: Vec<String>,
+    end: ListEnd,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct BLPop {
+    keys: Vec<String>,
+    // no timeout blocks forever
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BRPop {
+    keys: Vec<String>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BLMove {
+    src: String,
+    dest: String,
+    from: ListEnd,
+    to: ListEnd,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BLMPop {
+    keys: Vec<String>,
+    end: ListEnd,
+    count: usize,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct LLen {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct LRange {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+#[derive(Debug)]
+pub struct LIndex {
+    key: String,
+    index: i64,
+}
+
+#[derive(Debug)]
+pub struct LSet {
+    key: String,
+    index: i64,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct LInsert {
+    key: String,
+    before: bool,
+    pivot: RespFrame,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct LRem {
+    key: String,
+    count: i64,
+    value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct LTrim {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+#[derive(Debug)]
+pub struct LPos {
+    key: String,
+    value: RespFrame,
+    rank: i64,
+    // a single index is replied without COUNT, an array of them with it
+    count: Option<usize>,
+    max_len: usize,
+}
+
+#[derive(Debug)]
+pub struct SAdd {
+    key: String,
+    members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SRem {
+    key: String,
+    members: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SMembers {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct SIsMember {
+    key: String,
+    member: String,
+}
+
+#[derive(Debug)]
+pub struct SMIsMember {
+    key: String,
+    members: Vec<String>,
+}
+
 #[derive(Debug)]
-pub enum Cmd {
-    Set(Set),
-    Get(Get),
-    HSet(HSet),
-    HGet(HGet),
-    HGetAll(HGetAll),
-    LPush(LPush),
-    LPop(LPop),
-    RPush(RPush),
-    RPop(RPop),
-    LLen(LLen),
+pub struct SCard {
+    key: String,
+}
 
-    // unrecognized command
-    Unrecognized(Unrecognized),
+#[derive(Debug)]
+pub struct SPop {
+    key: String,
+    // a single member is replied without a count, a set of them with it
+    count: Option<usize>,
 }
 
 #[derive(Debug)]
-pub struct Set {
+pub struct SRandMember {
     key: String,
-    value: RespFrame,
+    count: Option<i64>,
 }
 
 #[derive(Debug)]
-pub struct Get {
+pub struct SMove {
+    src: String,
+    dest: String,
+    member: String,
+}
+
+#[derive(Debug)]
+pub struct SInter {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnion {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SDiff {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SInterStore {
+    dest: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnionStore {
+    dest: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SDiffStore {
+    dest: String,
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SInterCard {
+    keys: Vec<String>,
+    // 0 counts the whole intersection
+    limit: usize,
+}
+
+#[derive(Debug)]
+pub struct ZAdd {
     key: String,
+    pairs: Vec<(f64, String)>,
+    opt: ZAddOpt,
+    // the single pair is an increment and the new score is replied
+    incr: bool,
 }
 
 #[derive(Debug)]
-pub struct HSet {
+pub struct ZRem {
     key: String,
-    field: String,
-    value: RespFrame,
+    members: Vec<String>,
 }
 
 #[derive(Debug)]
-pub struct HGet {
+pub struct ZScore {
     key: String,
-    field: String,
+    member: String,
 }
 
 #[derive(Debug)]
-pub struct HGetAll {
+pub struct ZMScore {
     key: String,
-    sort: bool,
+    members: Vec<String>,
 }
 
 #[derive(Debug)]
-pub struct LPush {
+pub struct ZIncrBy {
     key: String,
-    value: RespFrame,
+    increment: f64,
+    member: String,
 }
 
 #[derive(Debug)]
-pub struct LPop {
+pub struct ZCard {
     key: String,
 }
 
 #[derive(Debug)]
-pub struct RPush {
+pub struct ZRank {
     key: String,
-    value: RespFrame,
+    member: String,
+    with_score: bool,
 }
 
 #[derive(Debug)]
-pub struct RPop {
+pub struct ZRevRank {
     key: String,
+    member: String,
+    with_score: bool,
 }
 
 #[derive(Debug)]
-pub struct LLen {
+pub struct ZRange {
+    key: String,
+    range: ZRangeBy,
+    opt: ZRangeOpt,
+    with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRangeStore {
+    dest: String,
+    src: String,
+    range: ZRangeBy,
+    opt: ZRangeOpt,
+}
+
+#[derive(Debug)]
+pub struct ZCount {
+    key: String,
+    range: ZRangeBy,
+}
+
+#[derive(Debug)]
+pub struct ZLexCount {
+    key: String,
+    range: ZRangeBy,
+}
+
+#[derive(Debug)]
+pub struct ZRemRangeByRank {
+    key: String,
+    range: ZRangeBy,
+}
+
+#[derive(Debug)]
+pub struct ZRemRangeByScore {
+    key: String,
+    range: ZRangeBy,
+}
+
+#[derive(Debug)]
+pub struct ZRemRangeByLex {
+    key: String,
+    range: ZRangeBy,
+}
+
+#[derive(Debug)]
+pub struct Expire {
+    key: String,
+    seconds: i64,
+    opt: ExpireOpt,
+}
+
+#[derive(Debug)]
+pub struct PExpire {
+    key: String,
+    milliseconds: i64,
+    opt: ExpireOpt,
+}
+
+#[derive(Debug)]
+pub struct ExpireAt {
+    key: String,
+    timestamp: i64,
+    opt: ExpireOpt,
+}
+
+#[derive(Debug)]
+pub struct PExpireAt {
+    key: String,
+    timestamp: i64,
+    opt: ExpireOpt,
+}
+
+#[derive(Debug)]
+pub struct Ttl {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct PTtl {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct ExpireTime {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct PExpireTime {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Persist {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Del {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Unlink {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Exists {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Type {
+    key: String,
+}
+
+#[derive(Debug)]
+pub struct Rename {
+    key: String,
+    new_key: String,
+}
+
+#[derive(Debug)]
+pub struct RenameNx {
+    key: String,
+    new_key: String,
+}
+
+// cmd copy, named to not shadow the `Copy` trait
+#[derive(Debug)]
+pub struct CopyKey {
+    source: String,
+    destination: String,
+    db: Option<usize>,
+    replace: bool,
+}
+
+#[derive(Debug)]
+pub struct Touch {
+    keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct RandomKey;
+
+#[derive(Debug)]
+pub struct DbSize;
+
+#[derive(Debug)]
+pub struct FlushDb {
+    lazy: bool,
+}
+
+#[derive(Debug)]
+pub struct FlushAll {
+    lazy: bool,
+}
+
+#[derive(Debug)]
+pub struct Select {
+    db: usize,
+}
+
+#[derive(Debug)]
+pub struct SwapDb {
+    db1: usize,
+    db2: usize,
+}
+
+#[derive(Debug)]
+pub struct Move {
+    key: String,
+    db: usize,
+}
+
+#[derive(Debug)]
+pub struct Keys {
+    pattern: BulkString,
+}
+
+#[derive(Debug)]
+pub struct Scan {
+    cursor: u64,
+    opt: ScanOpt,
+}
+
+#[derive(Debug)]
+pub struct HScan {
     key: String,
+    cursor: u64,
+    opt: ScanOpt,
+    no_values: bool,
+}
+
+#[derive(Debug)]
+pub struct Hello {
+    protocol: Option<u8>,
 }
 
 #[derive(Debug)]
-pub struct Unrecognized;
+pub struct ConfigGet {
+    patterns: Vec<Vec<u8>>,
+}
+
+impl Cmd {
+    // Blocking commands park the connection until they are served or time out,
+    // every other command replies right away.
+    pub async fn exec_blocking(self, backend: &Backend) -> RespFrame {
+        match self {
+            Cmd::BLPop(cmd) => blocking::block(cmd, backend).await,
+            Cmd::BRPop(cmd) => blocking::block(cmd, backend).await,
+            Cmd::BLMove(cmd) => blocking::block(cmd, backend).await,
+            Cmd::BLMPop(cmd) => blocking::block(cmd, backend).await,
+            cmd => cmd.exec(backend),
+        }
+    }
+}
 
 impl TryFrom<RespFrame> for Cmd {
     type Error = CmdErr;
@@ -110,15 +1018,13 @@ impl TryFrom<Array> for Cmd {
 
     fn try_from(value: Array) -> Result<Self, Self::Error> {
         match value.first() {
-            Some(RespFrame::BulkString(ref cmd)) => match cmd.as_ref() {
-                b"set" => Ok(Set::try_from(value)?.into()),
-                b"get" => Ok(Get::try_from(value)?.into()),
-                b"hset" => Ok(HSet::try_from(value)?.into()),
-                b"hget" => Ok(HGet::try_from(value)?.into()),
-                b"hgetall" => Ok(HGetAll::try_from(value)?.into()),
-                b"lpush" => Ok(LPush::try_from(value)?.into()),
-                _ => Ok(Unrecognized.into()),
-            },
+            Some(RespFrame::BulkString(ref cmd)) => {
+                let name = String::from_utf8_lossy(cmd.as_ref()).to_ascii_lowercase();
+                match CMD_TABLE.get(name.as_str()) {
+                    Some(parse) => parse(value),
+                    None => Err(CmdErr::unknown_cmd(&value)),
+                }
+            }
             _ => Err(CmdErr::InvalidCmd(
                 "Command must have a BulkString as the first argument.".to_string(),
             )),
@@ -126,26 +1032,72 @@ impl TryFrom<Array> for Cmd {
     }
 }
 
-impl CmdExecutor for Unrecognized {
-    fn exec(self, _backend: &Backend) -> RespFrame {
-        RESP_OK.clone()
-    }
-}
-
+// Error messages are replied to the client as is, so they follow the redis `ERR ...` format.
 #[derive(Debug, Error)]
 pub enum CmdErr {
-    #[error("Invalid command: {0}")]
+    #[error("ERR Invalid command: {0}")]
     InvalidCmd(String),
-    #[error("Invalid arguments: {0}")]
+    #[error("ERR Invalid arguments: {0}")]
     InvalidArg(String),
+    #[error("ERR syntax error")]
+    SyntaxErr,
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR value is not a valid float")]
+    NotFloat,
+    #[error("ERR {0}")]
+    Other(String),
+    #[error("ERR unknown command '{name}', with args beginning with: {args}")]
+    UnknownCmd { name: String, args: String },
+    #[error("ERR unknown subcommand '{sub}'. Try {cmd} HELP.")]
+    UnknownSubcmd { cmd: String, sub: String },
+    #[error("NOPROTO unsupported protocol version")]
+    NoProto,
 
-    #[error("{0}")]
+    #[error("ERR {0}")]
     RespErr(#[from] RespErr),
-    #[error("From utf-8 error: {0}")]
+    #[error("ERR From utf-8 error: {0}")]
     FromUtf8Err(#[from] std::string::FromUtf8Error),
 }
 
+impl CmdErr {
+    // Error of an unknown command, echoing its name and the beginning of its arguments like redis.
+    fn unknown_cmd(value: &Array) -> Self {
+        let mut args = String::new();
+        for arg in value.iter().skip(1) {
+            if args.len() >= MAX_ECHO_LEN {
+                break;
+            }
+            let arg = echo_arg(arg, MAX_ECHO_LEN - args.len());
+            args.push_str(&format!("'{}' ", arg));
+        }
+
+        CmdErr::UnknownCmd {
+            name: value
+                .first()
+                .map_or(String::new(), |name| echo_arg(name, MAX_ECHO_LEN)),
+            args,
+        }
+    }
+
+    fn unknown_subcmd(cmd: &str, sub: &RespFrame) -> Self {
+        CmdErr::UnknownSubcmd {
+            cmd: cmd.to_ascii_uppercase(),
+            sub: echo_arg(sub, MAX_ECHO_LEN),
+        }
+    }
+}
+
+impl From<CmdErr> for RespFrame {
+    fn from(value: CmdErr) -> Self {
+        // client input is echoed in some errors, line breaks would corrupt the simple error
+        SimpleError::new(value.to_string().replace(['\r', '\n'], " ")).into()
+    }
+}
+
+// Names come before the arity, an unknown subcommand is reported whatever its arguments.
 fn validate_cmd(value: &Array, names: &[&'static str], args_num: usize) -> Result<(), CmdErr> {
+    validate_cmd_names(value, names)?;
     if value.len() != args_num + names.len() {
         return Err(CmdErr::InvalidArg(format!(
             "{} command must have exactly {} argument.",
@@ -153,11 +1105,30 @@ fn validate_cmd(value: &Array, names: &[&'static str], args_num: usize) -> Resul
             args_num
         )));
     }
+    Ok(())
+}
+
+fn validate_cmd_min(value: &Array, names: &[&'static str], min_args: usize) -> Result<(), CmdErr> {
+    validate_cmd_names(value, names)?;
+    if value.len() < min_args + names.len() {
+        return Err(CmdErr::InvalidArg(format!(
+            "{} command must have at least {} argument.",
+            names.join(" "),
+            min_args
+        )));
+    }
+    Ok(())
+}
 
-    for (i, name) in names.iter().enumerate() {
+// A name missing from a too short command is left to the arity check.
+fn validate_cmd_names(value: &Array, names: &[&'static str]) -> Result<(), CmdErr> {
+    for (i, name) in names.iter().enumerate().take(value.len()) {
         match value[i] {
             RespFrame::BulkString(ref cmd) => {
                 let cmd_lower = String::from_utf8_lossy(cmd.as_ref()).to_ascii_lowercase();
+                if &cmd_lower != name && i > 0 {
+                    return Err(CmdErr::unknown_subcmd(names[0], &value[i]));
+                }
                 if &cmd_lower != name {
                     return Err(CmdErr::InvalidCmd(format!(
                         "Expected {}, got {}",
@@ -178,10 +1149,90 @@ fn validate_cmd(value: &Array, names: &[&'static str], args_num: usize) -> Resul
     Ok(())
 }
 
+// Client input echoed in error replies is truncated to this many characters.
+const MAX_ECHO_LEN: usize = 128;
+
+fn echo_arg(frame: &RespFrame, max_len: usize) -> String {
+    match frame {
+        RespFrame::BulkString(s) => String::from_utf8_lossy(s).chars().take(max_len).collect(),
+        RespFrame::SimpleString(s) => s.chars().take(max_len).collect(),
+        RespFrame::Integer(i) => i.to_string(),
+        _ => String::new(),
+    }
+}
+
 fn extract_args(value: Array, start: usize) -> Result<Vec<RespFrame>, CmdErr> {
     Ok(value.0.into_iter().skip(start).collect::<Vec<RespFrame>>())
 }
 
+fn parse_int(frame: RespFrame) -> Result<i64, CmdErr> {
+    match frame {
+        RespFrame::BulkString(s) => parse_i64(&s).ok_or(CmdErr::NotInteger),
+        RespFrame::Integer(i) => Ok(i),
+        _ => Err(CmdErr::NotInteger),
+    }
+}
+
+// A negative random count repeats members in a reply built in memory, so it is bounded.
+const MAX_RANDOM_COUNT: u64 = 1 << 24;
+
+// Count of HRANDFIELD and SRANDMEMBER, a negative one asks for possibly repeated members.
+fn parse_random_count(frame: RespFrame) -> Result<i64, CmdErr> {
+    let count = parse_int(frame)?;
+    if count < 0 && count.unsigned_abs() > MAX_RANDOM_COUNT {
+        return Err(CmdErr::Other("value is out of range".to_string()));
+    }
+    Ok(count)
+}
+
+fn parse_float(frame: RespFrame) -> Result<f64, CmdErr> {
+    match frame {
+        RespFrame::BulkString(s) => parse_f64(&s).ok_or(CmdErr::NotFloat),
+        RespFrame::Integer(i) => Ok(i as f64),
+        RespFrame::Double(f) => Ok(f),
+        _ => Err(CmdErr::NotFloat),
+    }
+}
+
+// Index of a database, whether it exists is only known by the backend.
+fn parse_db_index(frame: RespFrame) -> Result<usize, CmdErr> {
+    usize::try_from(parse_int(frame)?)
+        .map_err(|_| CmdErr::Other("DB index is out of range".to_string()))
+}
+
+// Upper-cased option name of a command, options are matched case-insensitively.
+fn parse_opt(frame: RespFrame) -> Result<String, CmdErr> {
+    match frame {
+        RespFrame::BulkString(s) => Ok(String::from_utf8(s.0)?.to_ascii_uppercase()),
+        _ => Err(CmdErr::SyntaxErr),
+    }
+}
+
+// Reply of field and value pairs, a map for RESP3 connections and a flat array for RESP2 ones.
+fn map_reply(backend: &Backend, pairs: Vec<(String, RespFrame)>) -> RespFrame {
+    if backend.protocol() >= 3 {
+        return Map::from(pairs.into_iter().collect::<BTreeMap<_, _>>()).into();
+    }
+
+    let items = pairs
+        .into_iter()
+        .flat_map(|(k, v)| [BulkString::from(k).into(), v])
+        .collect::<Vec<RespFrame>>();
+    Array::new(items).into()
+}
+
+// Reply of set members, a set for RESP3 connections and an array for RESP2 ones.
+fn set_reply(backend: &Backend, members: Vec<String>) -> RespFrame {
+    let items = members
+        .into_iter()
+        .map(|member| BulkString::from(member).into())
+        .collect::<Vec<RespFrame>>();
+    if backend.protocol() >= 3 {
+        return crate::Set::new(items).into();
+    }
+    Array::new(items).into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,4 +1255,117 @@ mod tests {
         assert_eq!(ret, RespFrame::Null(Null));
         Ok(())
     }
+
+    #[test]
+    fn test_cmd_case_insensitive() -> Result<()> {
+        let backend = Backend::new();
+
+        for (name, value) in [("RPUSH", "a"), ("rPush", "b"), ("LPUSH", "c")] {
+            let frame = Array::new([
+                BulkString::from(name).into(),
+                BulkString::from("list").into(),
+                BulkString::from(value).into(),
+            ]);
+            Cmd::try_from(frame)?.exec(&backend);
+        }
+
+        let frame = Array::new([
+            BulkString::from("LLEN").into(),
+            BulkString::from("list").into(),
+        ]);
+        assert_eq!(Cmd::try_from(frame)?.exec(&backend), RespFrame::Integer(3));
+
+        let frame = Array::new([
+            BulkString::from("RPOP").into(),
+            BulkString::from("list").into(),
+        ]);
+        assert_eq!(
+            Cmd::try_from(frame)?.exec(&backend),
+            BulkString::from("b").into()
+        );
+
+        let frame = Array::new([
+            BulkString::from("LpOp").into(),
+            BulkString::from("list").into(),
+        ]);
+        assert_eq!(
+            Cmd::try_from(frame)?.exec(&backend),
+            BulkString::from("c").into()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_table() {
+        // every name is lowercase so the lowercased client input can find it
+        assert!(CMD_TABLE
+            .keys()
+            .all(|name| *name == name.to_ascii_lowercase()));
+        assert!(CMD_TABLE.contains_key("bitfield_ro"));
+    }
+
+    #[test]
+    fn test_unknown_cmd() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nsett\r\n$5\r\nhello\r\n$7\r\nwor\r\nld\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let ret = Cmd::try_from(frame).map_err(RespFrame::from);
+        assert_eq!(
+            ret.unwrap_err(),
+            SimpleError::new(
+                "ERR unknown command 'sett', with args beginning with: 'hello' 'wor  ld' "
+            )
+            .into()
+        );
+
+        let long = "a".repeat(200);
+        let frame = Array::new([
+            BulkString::from("nope").into(),
+            BulkString::from(long.as_str()).into(),
+            BulkString::from("b").into(),
+        ]);
+        let ret = Cmd::try_from(frame);
+        assert!(matches!(
+            ret,
+            Err(CmdErr::UnknownCmd { ref args, .. }) if *args == format!("'{}' ", &long[..128])
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_subcmd() -> Result<()> {
+        let frame = Array::new([
+            BulkString::from("config").into(),
+            BulkString::from("gett").into(),
+            BulkString::from("maxmemory").into(),
+        ]);
+        let ret = validate_cmd(&frame, &["config", "get"], 1);
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR unknown subcommand 'gett'. Try CONFIG HELP."
+        );
+
+        // the subcommand is reported before the number of arguments is checked
+        for args in [&["config", "foo"][..], &["config", "foo", "a", "b"]] {
+            let frame = Array::new(
+                args.iter()
+                    .map(|arg| BulkString::from(*arg).into())
+                    .collect::<Vec<RespFrame>>(),
+            );
+            let ret = validate_cmd_min(&frame, &["config", "get"], 1);
+            assert_eq!(
+                ret.unwrap_err().to_string(),
+                "ERR unknown subcommand 'foo'. Try CONFIG HELP."
+            );
+        }
+
+        let frame = Array::new([BulkString::from("config").into()]);
+        let ret = validate_cmd(&frame, &["config", "get"], 1);
+        assert!(matches!(ret, Err(CmdErr::InvalidArg(_))));
+
+        Ok(())
+    }
 }
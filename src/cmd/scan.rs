@@ -0,0 +1,290 @@
+// scan cmd
+use crate::{
+    cmd::{
+        extract_args, parse_int, parse_opt, validate_cmd, validate_cmd_min, CmdErr, CmdExecutor,
+        HScan, Keys, Scan,
+    },
+    Array, Backend, BulkString, RespFrame, ScanOpt,
+};
+
+// Type names accepted by the type filter of scan, including types easy-redis may not hold.
+const TYPE_NAMES: [&str; 6] = ["string", "list", "set", "zset", "hash", "stream"];
+
+// cmd keys
+impl CmdExecutor for Keys {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let keys = backend
+            .keys(&self.pattern)
+            .into_iter()
+            .map(|key| BulkString::from(key).into())
+            .collect::<Vec<RespFrame>>();
+
+        Array::new(keys).into()
+    }
+}
+
+impl TryFrom<Array> for Keys {
+    type Error = CmdErr;
+
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["keys"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(pattern)) => Ok(Keys { pattern }),
+            _ => Err(CmdErr::InvalidArg("Invalid pattern.".to_string())),
+        }
+    }
+}
+
+// cmd scan
+impl CmdExecutor for Scan {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let (cursor, keys) = backend.scan(self.cursor, &self.opt);
+
+        let keys = keys
+            .into_iter()
+            .map(|key| BulkString::from(key).into())
+            .collect::<Vec<RespFrame>>();
+        scan_reply(cursor, keys)
+    }
+}
+
+impl TryFrom<Array> for Scan {
+    type Error = CmdErr;
+
+    // SCAN cursor [MATCH pattern] [COUNT count] [TYPE type]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["scan"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let cursor = parse_cursor(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+        let (opt, _) = parse_scan_opt(args, false)?;
+
+        Ok(Scan { cursor, opt })
+    }
+}
+
+// cmd hscan
+impl CmdExecutor for HScan {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.hscan(&self.key, self.cursor, &self.opt) {
+            Ok((cursor, fields)) => {
+                let mut items = Vec::with_capacity(fields.len() * 2);
+                for (field, value) in fields {
+                    items.push(BulkString::from(field).into());
+                    if !self.no_values {
+                        items.push(value);
+                    }
+                }
+                scan_reply(cursor, items)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for HScan {
+    type Error = CmdErr;
+
+    // HSCAN key cursor [MATCH pattern] [COUNT count] [NOVALUES]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["hscan"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            _ => return Err(CmdErr::InvalidArg("Invalid key.".to_string())),
+        };
+        let cursor = parse_cursor(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+        let (opt, no_values) = parse_scan_opt(args, true)?;
+
+        Ok(HScan {
+            key,
+            cursor,
+            opt,
+            no_values,
+        })
+    }
+}
+
+fn scan_reply(cursor: u64, items: Vec<RespFrame>) -> RespFrame {
+    Array::new([
+        BulkString::from(cursor.to_string()).into(),
+        Array::new(items).into(),
+    ])
+    .into()
+}
+
+fn parse_cursor(frame: RespFrame) -> Result<u64, CmdErr> {
+    let err = || CmdErr::Other("invalid cursor".to_string());
+    match frame {
+        RespFrame::BulkString(s) => String::from_utf8_lossy(&s).parse().map_err(|_| err()),
+        RespFrame::Integer(i) => u64::try_from(i).map_err(|_| err()),
+        _ => Err(err()),
+    }
+}
+
+// Parse the options shared by the scan family, TYPE only applies to the keyspace
+// and NOVALUES only to hashes.
+fn parse_scan_opt(
+    mut args: impl Iterator<Item = RespFrame>,
+    hash: bool,
+) -> Result<(ScanOpt, bool), CmdErr> {
+    let mut opt = ScanOpt::default();
+    let mut no_values = false;
+
+    while let Some(arg) = args.next() {
+        match parse_opt(arg)?.as_str() {
+            "MATCH" => match args.next() {
+                Some(RespFrame::BulkString(pattern)) => opt.pattern = Some(pattern.0),
+                _ => return Err(CmdErr::SyntaxErr),
+            },
+            "COUNT" => {
+                let count = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+                if count < 1 {
+                    return Err(CmdErr::SyntaxErr);
+                }
+                opt.count = count as usize;
+            }
+            "TYPE" if !hash => {
+                let ty = parse_opt(args.next().ok_or(CmdErr::SyntaxErr)?)?.to_ascii_lowercase();
+                if !TYPE_NAMES.contains(&ty.as_str()) {
+                    return Err(CmdErr::Other(format!("unknown type name '{}'", ty)));
+                }
+                opt.ty = Some(ty);
+            }
+            "NOVALUES" if hash => no_values = true,
+            _ => return Err(CmdErr::SyntaxErr),
+        }
+    }
+
+    Ok((opt, no_values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BackendErr, RespDecode};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_scan_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*8\r\n$4\r\nscan\r\n$2\r\n42\r\n$5\r\nmatch\r\n$5\r\nuser*\r\n\
+            $5\r\ncount\r\n$3\r\n100\r\n$4\r\ntype\r\n$4\r\nHASH\r\n",
+        );
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: Scan = frame.try_into()?;
+        assert_eq!(cmd.cursor, 42);
+        assert_eq!(
+            cmd.opt,
+            ScanOpt {
+                pattern: Some(b"user*".to_vec()),
+                count: 100,
+                ty: Some("hash".to_string()),
+            }
+        );
+
+        let parse = |args: &[&str]| {
+            let mut frames = vec![RespFrame::from(BulkString::from("scan"))];
+            frames.extend(args.iter().map(|arg| BulkString::from(*arg).into()));
+            Scan::try_from(Array::new(frames))
+        };
+        assert!(parse(&["-1"]).is_err());
+        assert!(parse(&["0", "count", "0"]).is_err());
+        assert!(parse(&["0", "type", "unknown"]).is_err());
+        assert!(parse(&["0", "novalues"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_from_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nhscan\r\n$4\r\nuser\r\n$1\r\n0\r\n$8\r\nnovalues\r\n");
+
+        let frame = Array::decode(&mut buf)?;
+        let cmd: HScan = frame.try_into()?;
+        assert_eq!(cmd.key, "user");
+        assert_eq!(cmd.cursor, 0);
+        assert!(cmd.no_values);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keys_cmd() -> Result<()> {
+        let backend = Backend::new();
+        for key in ["firstname", "lastname", "age"] {
+            backend.set(key.to_string(), BulkString::from("v"));
+        }
+
+        let cmd = Keys {
+            pattern: BulkString::from("*name*"),
+        };
+        let RespFrame::Array(keys) = cmd.exec(&backend) else {
+            panic!("keys must reply an array");
+        };
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&BulkString::from("firstname").into()));
+        assert!(keys.contains(&BulkString::from("lastname").into()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_cmd() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset(
+            "user".to_string(),
+            vec![("name".to_string(), BulkString::from("alice").into())],
+        )?;
+
+        let hscan = |no_values| {
+            HScan {
+                key: "user".to_string(),
+                cursor: 0,
+                opt: ScanOpt::default(),
+                no_values,
+            }
+            .exec(&backend)
+        };
+        assert_eq!(
+            hscan(false),
+            scan_reply(
+                0,
+                vec![
+                    BulkString::from("name").into(),
+                    BulkString::from("alice").into()
+                ]
+            )
+        );
+        assert_eq!(
+            hscan(true),
+            scan_reply(0, vec![BulkString::from("name").into()])
+        );
+
+        let cmd = HScan {
+            key: "missing".to_string(),
+            cursor: 0,
+            opt: ScanOpt::default(),
+            no_values: false,
+        };
+        assert_eq!(cmd.exec(&backend), scan_reply(0, vec![]));
+
+        backend.set("string".to_string(), BulkString::from("v"));
+        let cmd = HScan {
+            key: "string".to_string(),
+            cursor: 0,
+            opt: ScanOpt::default(),
+            no_values: false,
+        };
+        assert_eq!(cmd.exec(&backend), BackendErr::WrongType.into());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+// server cmd
+use crate::{
+    cmd::{
+        extract_args, map_reply, parse_int, parse_opt, validate_cmd_min, CmdErr, CmdExecutor,
+        ConfigGet, Hello,
+    },
+    glob_match, Array, Backend, BulkString, RespFrame, MAX_STRING_LEN,
+};
+
+// cmd hello
+impl CmdExecutor for Hello {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        if let Some(protocol) = self.protocol {
+            backend.set_protocol(protocol);
+        }
+
+        let info = vec![
+            ("server".to_string(), BulkString::from("redis").into()),
+            (
+                "version".to_string(),
+                BulkString::from(env!("CARGO_PKG_VERSION")).into(),
+            ),
+            (
+                "proto".to_string(),
+                RespFrame::Integer(backend.protocol() as i64),
+            ),
+            ("mode".to_string(), BulkString::from("standalone").into()),
+            ("role".to_string(), BulkString::from("master").into()),
+            ("modules".to_string(), Array::new([]).into()),
+        ];
+        map_reply(backend, info)
+    }
+}
+
+impl TryFrom<Array> for Hello {
+    type Error = CmdErr;
+
+    // HELLO [protover [AUTH username password] [SETNAME clientname]]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["hello"], 0)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let protocol = match args.next().map(parse_int).transpose() {
+            Ok(None) => None,
+            Ok(Some(protocol @ 2..=3)) => Some(protocol as u8),
+            Ok(Some(_)) => return Err(CmdErr::NoProto),
+            Err(_) => {
+                return Err(CmdErr::Other(
+                    "Protocol version is not an integer or out of range".to_string(),
+                ))
+            }
+        };
+
+        // there are neither users nor client names, both options are accepted and ignored
+        while let Some(opt) = args.next() {
+            let skip = match parse_opt(opt)?.as_str() {
+                "AUTH" => 2,
+                "SETNAME" => 1,
+                _ => return Err(CmdErr::SyntaxErr),
+            };
+            if args.by_ref().take(skip).count() != skip {
+                return Err(CmdErr::SyntaxErr);
+            }
+        }
+
+        Ok(Hello { protocol })
+    }
+}
+
+// cmd config get
+impl CmdExecutor for ConfigGet {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let params = [
+            ("databases", backend.databases().to_string()),
+            ("proto-max-bulk-len", MAX_STRING_LEN.to_string()),
+        ];
+
+        let matched = params
+            .into_iter()
+            .filter(|(name, _)| {
+                self.patterns
+                    .iter()
+                    .any(|pattern| glob_match(pattern, name.as_bytes()))
+            })
+            .map(|(name, value)| (name.to_string(), BulkString::from(value).into()))
+            .collect();
+        map_reply(backend, matched)
+    }
+}
+
+impl TryFrom<Array> for ConfigGet {
+    type Error = CmdErr;
+
+    // CONFIG GET parameter [parameter ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["config", "get"], 1)?;
+
+        extract_args(value, 2)?
+            .into_iter()
+            .map(|frame| match frame {
+                // parameter names are case-insensitive
+                RespFrame::BulkString(pattern) => Ok(pattern.to_ascii_lowercase()),
+                _ => Err(CmdErr::InvalidArg("Invalid parameter.".to_string())),
+            })
+            .collect::<Result<_, _>>()
+            .map(|patterns| ConfigGet { patterns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Map, SimpleError};
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+
+    fn cmd(args: &[&str]) -> Array {
+        Array::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    #[test]
+    fn test_hello_from_array() -> Result<()> {
+        let hello = Hello::try_from(cmd(&["hello"]))?;
+        assert_eq!(hello.protocol, None);
+
+        let hello = Hello::try_from(cmd(&["hello", "3", "auth", "default", "pwd"]))?;
+        assert_eq!(hello.protocol, Some(3));
+
+        let ret = Hello::try_from(cmd(&["hello", "4"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "NOPROTO unsupported protocol version"
+        );
+
+        let ret = Hello::try_from(cmd(&["hello", "3", "setname"]));
+        assert_eq!(ret.unwrap_err().to_string(), "ERR syntax error");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let ret = Hello::try_from(cmd(&["hello"]))?.exec(&backend);
+        assert!(matches!(ret, RespFrame::Array(ref items) if items.len() == 12));
+        assert_eq!(backend.protocol(), 2);
+
+        // a second connection gets its own handle on the same keyspace
+        let other = backend.clone();
+
+        let ret = Hello::try_from(cmd(&["hello", "3"]))?.exec(&backend);
+        let RespFrame::Map(info) = ret else {
+            panic!("expected a map reply, got {:?}", ret);
+        };
+        assert_eq!(info.get("proto"), Some(&RespFrame::Integer(3)));
+        assert_eq!(backend.protocol(), 3);
+
+        // the protocol belongs to the connection, the other one still speaks RESP2
+        assert_eq!(other.protocol(), 2);
+        backend.set("k".to_string(), BulkString::from("v"));
+        assert_eq!(other.get("k")?, Some(BulkString::from("v")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_get_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let ret = ConfigGet::try_from(cmd(&["config", "get", "DATA*"]))?.exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new([
+                BulkString::from("databases").into(),
+                BulkString::from("16").into(),
+            ])
+            .into()
+        );
+
+        backend.set_protocol(3);
+        let ret = ConfigGet::try_from(cmd(&["config", "get", "*", "databases"]))?.exec(&backend);
+        let expected = BTreeMap::from([
+            ("databases".to_string(), BulkString::from("16").into()),
+            (
+                "proto-max-bulk-len".to_string(),
+                BulkString::from(MAX_STRING_LEN.to_string()).into(),
+            ),
+        ]);
+        assert_eq!(ret, Map::from(expected).into());
+
+        let ret = ConfigGet::try_from(cmd(&["config", "get", "missing"]))?.exec(&backend);
+        assert_eq!(ret, Map::new().into());
+
+        let ret = ConfigGet::try_from(cmd(&["config", "set", "databases", "1"]));
+        assert_eq!(
+            RespFrame::from(ret.unwrap_err()),
+            SimpleError::new("ERR unknown subcommand 'set'. Try CONFIG HELP.").into()
+        );
+
+        Ok(())
+    }
+}
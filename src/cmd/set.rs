@@ -0,0 +1,644 @@
+use crate::cmd::list::parse_key;
+use crate::cmd::{
+    extract_args, parse_int, parse_opt, parse_random_count, set_reply, validate_cmd,
+    validate_cmd_min, CmdErr, CmdExecutor, SAdd, SCard, SDiff, SDiffStore, SInter, SInterCard,
+    SInterStore, SIsMember, SMIsMember, SMembers, SMove, SPop, SRandMember, SRem, SUnion,
+    SUnionStore,
+};
+use crate::{Array, Backend, BulkString, Null, RespFrame, SetOperator};
+
+// cmd sadd
+impl CmdExecutor for SAdd {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.sadd(self.key, self.members) {
+            Ok(added) => RespFrame::Integer(added as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SAdd {
+    type Error = CmdErr;
+
+    // SADD key member [member ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members_args(value, "sadd")?;
+        Ok(SAdd { key, members })
+    }
+}
+
+// cmd srem
+impl CmdExecutor for SRem {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.srem(&self.key, &self.members) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SRem {
+    type Error = CmdErr;
+
+    // SREM key member [member ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members_args(value, "srem")?;
+        Ok(SRem { key, members })
+    }
+}
+
+// cmd smembers
+impl CmdExecutor for SMembers {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.smembers(&self.key) {
+            Ok(members) => set_reply(backend, members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SMembers {
+    type Error = CmdErr;
+
+    // SMEMBERS key
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["smembers"], 1)?;
+
+        let key = parse_key(extract_args(value, 1)?.into_iter().next())?;
+        Ok(SMembers { key })
+    }
+}
+
+// cmd sismember
+impl CmdExecutor for SIsMember {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.sismember(&self.key, &self.member) {
+            Ok(found) => RespFrame::Integer(found as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SIsMember {
+    type Error = CmdErr;
+
+    // SISMEMBER key member
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["sismember"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let member = parse_member(args.next())?;
+        Ok(SIsMember { key, member })
+    }
+}
+
+// cmd smismember
+impl CmdExecutor for SMIsMember {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.smismember(&self.key, &self.members) {
+            Ok(found) => Array::new(
+                found
+                    .into_iter()
+                    .map(|found| RespFrame::Integer(found as i64))
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SMIsMember {
+    type Error = CmdErr;
+
+    // SMISMEMBER key member [member ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members_args(value, "smismember")?;
+        Ok(SMIsMember { key, members })
+    }
+}
+
+// cmd scard
+impl CmdExecutor for SCard {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.scard(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SCard {
+    type Error = CmdErr;
+
+    // SCARD key
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["scard"], 1)?;
+
+        let key = parse_key(extract_args(value, 1)?.into_iter().next())?;
+        Ok(SCard { key })
+    }
+}
+
+// cmd spop
+impl CmdExecutor for SPop {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let ret = backend.spop(&self.key, self.count.unwrap_or(1));
+        match (ret, self.count) {
+            (Ok(members), Some(_)) => set_reply(backend, members),
+            (Ok(members), None) => single_reply(members),
+            (Err(e), _) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SPop {
+    type Error = CmdErr;
+
+    // SPOP key [count]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["spop"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let count = args
+            .next()
+            .map(|count| {
+                usize::try_from(parse_int(count)?).map_err(|_| {
+                    CmdErr::Other("value is out of range, must be positive".to_string())
+                })
+            })
+            .transpose()?;
+        if args.next().is_some() {
+            return Err(CmdErr::SyntaxErr);
+        }
+
+        Ok(SPop { key, count })
+    }
+}
+
+// cmd srandmember
+impl CmdExecutor for SRandMember {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let ret = backend.srandmember(&self.key, self.count.unwrap_or(1));
+        match (ret, self.count) {
+            // members of a negative count may repeat, so they are never a set
+            (Ok(members), Some(_)) => Array::new(
+                members
+                    .into_iter()
+                    .map(|member| BulkString::from(member).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            (Ok(members), None) => single_reply(members),
+            (Err(e), _) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SRandMember {
+    type Error = CmdErr;
+
+    // SRANDMEMBER key [count]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["srandmember"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let count = args.next().map(parse_random_count).transpose()?;
+        if args.next().is_some() {
+            return Err(CmdErr::SyntaxErr);
+        }
+
+        Ok(SRandMember { key, count })
+    }
+}
+
+// cmd smove
+impl CmdExecutor for SMove {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.smove(&self.src, &self.dest, &self.member) {
+            Ok(moved) => RespFrame::Integer(moved as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SMove {
+    type Error = CmdErr;
+
+    // SMOVE source destination member
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["smove"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let src = parse_key(args.next())?;
+        let dest = parse_key(args.next())?;
+        let member = parse_member(args.next())?;
+        Ok(SMove { src, dest, member })
+    }
+}
+
+// cmd sinter
+impl CmdExecutor for SInter {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        set_op_reply(backend, SetOperator::Inter, &self.keys)
+    }
+}
+
+impl TryFrom<Array> for SInter {
+    type Error = CmdErr;
+
+    // SINTER key [key ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let keys = parse_keys_args(value, "sinter")?;
+        Ok(SInter { keys })
+    }
+}
+
+// cmd sunion
+impl CmdExecutor for SUnion {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        set_op_reply(backend, SetOperator::Union, &self.keys)
+    }
+}
+
+impl TryFrom<Array> for SUnion {
+    type Error = CmdErr;
+
+    // SUNION key [key ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let keys = parse_keys_args(value, "sunion")?;
+        Ok(SUnion { keys })
+    }
+}
+
+// cmd sdiff
+impl CmdExecutor for SDiff {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        set_op_reply(backend, SetOperator::Diff, &self.keys)
+    }
+}
+
+impl TryFrom<Array> for SDiff {
+    type Error = CmdErr;
+
+    // SDIFF key [key ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let keys = parse_keys_args(value, "sdiff")?;
+        Ok(SDiff { keys })
+    }
+}
+
+// cmd sinterstore
+impl CmdExecutor for SInterStore {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        set_op_store_reply(backend, SetOperator::Inter, self.dest, &self.keys)
+    }
+}
+
+impl TryFrom<Array> for SInterStore {
+    type Error = CmdErr;
+
+    // SINTERSTORE destination key [key ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (dest, keys) = parse_key_members_args(value, "sinterstore")?;
+        Ok(SInterStore { dest, keys })
+    }
+}
+
+// cmd sunionstore
+impl CmdExecutor for SUnionStore {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        set_op_store_reply(backend, SetOperator::Union, self.dest, &self.keys)
+    }
+}
+
+impl TryFrom<Array> for SUnionStore {
+    type Error = CmdErr;
+
+    // SUNIONSTORE destination key [key ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (dest, keys) = parse_key_members_args(value, "sunionstore")?;
+        Ok(SUnionStore { dest, keys })
+    }
+}
+
+// cmd sdiffstore
+impl CmdExecutor for SDiffStore {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        set_op_store_reply(backend, SetOperator::Diff, self.dest, &self.keys)
+    }
+}
+
+impl TryFrom<Array> for SDiffStore {
+    type Error = CmdErr;
+
+    // SDIFFSTORE destination key [key ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (dest, keys) = parse_key_members_args(value, "sdiffstore")?;
+        Ok(SDiffStore { dest, keys })
+    }
+}
+
+// cmd sintercard
+impl CmdExecutor for SInterCard {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.sintercard(&self.keys, self.limit) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for SInterCard {
+    type Error = CmdErr;
+
+    // SINTERCARD numkeys key [key ...] [LIMIT limit]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["sintercard"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let numkeys = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+        if numkeys <= 0 {
+            return Err(CmdErr::Other(
+                "numkeys should be greater than 0".to_string(),
+            ));
+        }
+        if numkeys as usize > args.len() {
+            return Err(CmdErr::Other(
+                "Number of keys can't be greater than number of args".to_string(),
+            ));
+        }
+
+        let keys = args
+            .by_ref()
+            .take(numkeys as usize)
+            .map(|key| parse_key(Some(key)))
+            .collect::<Result<_, _>>()?;
+        let limit = match args.next().map(parse_opt).transpose()?.as_deref() {
+            None => 0,
+            Some("LIMIT") => usize::try_from(parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?)
+                .map_err(|_| CmdErr::Other("LIMIT can't be negative".to_string()))?,
+            _ => return Err(CmdErr::SyntaxErr),
+        };
+        if args.next().is_some() {
+            return Err(CmdErr::SyntaxErr);
+        }
+
+        Ok(SInterCard { keys, limit })
+    }
+}
+
+fn set_op_reply(backend: &Backend, op: SetOperator, keys: &[String]) -> RespFrame {
+    match backend.set_op(op, keys) {
+        Ok(members) => set_reply(backend, members),
+        Err(e) => e.into(),
+    }
+}
+
+fn set_op_store_reply(
+    backend: &Backend,
+    op: SetOperator,
+    dest: String,
+    keys: &[String],
+) -> RespFrame {
+    match backend.set_op_store(op, dest, keys) {
+        Ok(len) => RespFrame::Integer(len as i64),
+        Err(e) => e.into(),
+    }
+}
+
+// A single member without count, a missing key or an empty set is a null.
+fn single_reply(members: Vec<String>) -> RespFrame {
+    match members.into_iter().next() {
+        Some(member) => BulkString::from(member).into(),
+        None => RespFrame::Null(Null),
+    }
+}
+
+pub(super) fn parse_member(frame: Option<RespFrame>) -> Result<String, CmdErr> {
+    match frame {
+        Some(RespFrame::BulkString(member)) => Ok(String::from_utf8(member.0)?),
+        _ => Err(CmdErr::InvalidArg("Invalid member".to_string())),
+    }
+}
+
+// key [key ...]
+fn parse_keys_args(value: Array, name: &'static str) -> Result<Vec<String>, CmdErr> {
+    validate_cmd_min(&value, &[name], 1)?;
+
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|key| parse_key(Some(key)))
+        .collect()
+}
+
+// key member [member ...], also destination key [key ...]
+pub(super) fn parse_key_members_args(
+    value: Array,
+    name: &'static str,
+) -> Result<(String, Vec<String>), CmdErr> {
+    validate_cmd_min(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = parse_key(args.next())?;
+    let members = args
+        .map(|member| parse_member(Some(member)))
+        .collect::<Result<_, _>>()?;
+    Ok((key, members))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BackendErr, Set};
+    use anyhow::Result;
+
+    fn array(args: &[&str]) -> Array {
+        Array::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    fn members(frame: RespFrame) -> Vec<String> {
+        let items = match frame {
+            RespFrame::Array(items) => items.to_vec(),
+            RespFrame::Set(items) => items.to_vec(),
+            frame => panic!("expected members, got {:?}", frame),
+        };
+        let mut members = items
+            .into_iter()
+            .map(|item| match item {
+                RespFrame::BulkString(member) => String::from_utf8(member.0).unwrap(),
+                item => panic!("expected a member, got {:?}", item),
+            })
+            .collect::<Vec<_>>();
+        members.sort();
+        members
+    }
+
+    #[test]
+    fn test_set_from_array() -> Result<()> {
+        let cmd = SAdd::try_from(array(&["sadd", "s", "a", "b"]))?;
+        assert_eq!(cmd.key, "s");
+        assert_eq!(cmd.members, ["a", "b"]);
+
+        let cmd = SPop::try_from(array(&["spop", "s", "2"]))?;
+        assert_eq!(cmd.count, Some(2));
+        let ret = SPop::try_from(array(&["spop", "s", "-1"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR value is out of range, must be positive"
+        );
+
+        let cmd = SRandMember::try_from(array(&["srandmember", "s", "-5"]))?;
+        assert_eq!(cmd.count, Some(-5));
+        let ret = SRandMember::try_from(array(&["srandmember", "s", &i64::MIN.to_string()]));
+        assert_eq!(ret.unwrap_err().to_string(), "ERR value is out of range");
+        let ret = SRandMember::try_from(array(&["srandmember", "s", "-9223372036854775807"]));
+        assert_eq!(ret.unwrap_err().to_string(), "ERR value is out of range");
+
+        let cmd = SMove::try_from(array(&["smove", "a", "b", "m"]))?;
+        assert_eq!(
+            (cmd.src, cmd.dest, cmd.member),
+            ("a".into(), "b".into(), "m".into())
+        );
+
+        assert!(SAdd::try_from(array(&["sadd", "s"])).is_err());
+        assert!(SIsMember::try_from(array(&["sismember", "s", "a", "b"])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let ret = SAdd::try_from(array(&["sadd", "s", "a", "b", "a"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        let ret = SMembers::try_from(array(&["smembers", "s"]))?.exec(&backend);
+        assert_eq!(members(ret), ["a", "b"]);
+        let ret = SIsMember::try_from(array(&["sismember", "s", "a"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = SMIsMember::try_from(array(&["smismember", "s", "x", "b"]))?.exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new([RespFrame::Integer(0), RespFrame::Integer(1)]).into()
+        );
+        let ret = SCard::try_from(array(&["scard", "s"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+
+        let ret = SRandMember::try_from(array(&["srandmember", "s", "-4"]))?.exec(&backend);
+        assert!(matches!(ret, RespFrame::Array(ref items) if items.len() == 4));
+        let ret = SRandMember::try_from(array(&["srandmember", "missing"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+
+        let ret = SMove::try_from(array(&["smove", "s", "t", "a"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = SRem::try_from(array(&["srem", "t", "a", "x"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+
+        let ret = SPop::try_from(array(&["spop", "s"]))?.exec(&backend);
+        assert_eq!(ret, BulkString::from("b").into());
+        let ret = SPop::try_from(array(&["spop", "s"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+        let ret = SPop::try_from(array(&["spop", "s", "3"]))?.exec(&backend);
+        assert_eq!(ret, Array::new([]).into());
+
+        backend.set("string".to_string(), BulkString::from("v"));
+        let ret = SAdd::try_from(array(&["sadd", "string", "a"]))?.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_op_from_array() -> Result<()> {
+        let cmd = SInterStore::try_from(array(&["sinterstore", "dest", "a", "b"]))?;
+        assert_eq!(cmd.dest, "dest");
+        assert_eq!(cmd.keys, ["a", "b"]);
+        assert!(SUnionStore::try_from(array(&["sunionstore", "dest"])).is_err());
+        assert!(SDiff::try_from(array(&["sdiff"])).is_err());
+
+        let cmd = SInterCard::try_from(array(&["sintercard", "2", "a", "b", "limit", "5"]))?;
+        assert_eq!(cmd.keys, ["a", "b"]);
+        assert_eq!(cmd.limit, 5);
+        let cmd = SInterCard::try_from(array(&["sintercard", "1", "a"]))?;
+        assert_eq!(cmd.limit, 0);
+
+        let ret = SInterCard::try_from(array(&["sintercard", "0", "a"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR numkeys should be greater than 0"
+        );
+        let ret = SInterCard::try_from(array(&["sintercard", "3", "a", "b"]));
+        assert_eq!(
+            ret.unwrap_err().to_string(),
+            "ERR Number of keys can't be greater than number of args"
+        );
+        let ret = SInterCard::try_from(array(&["sintercard", "1", "a", "limit", "-1"]));
+        assert_eq!(ret.unwrap_err().to_string(), "ERR LIMIT can't be negative");
+        let ret = SInterCard::try_from(array(&["sintercard", "1", "a", "b"]));
+        assert_eq!(ret.unwrap_err().to_string(), "ERR syntax error");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_op_cmd() -> Result<()> {
+        let backend = Backend::new();
+        SAdd::try_from(array(&["sadd", "a", "1", "2", "3"]))?.exec(&backend);
+        SAdd::try_from(array(&["sadd", "b", "2", "3", "4"]))?.exec(&backend);
+
+        let ret = SInter::try_from(array(&["sinter", "a", "b"]))?.exec(&backend);
+        assert_eq!(members(ret), ["2", "3"]);
+        let ret = SUnion::try_from(array(&["sunion", "a", "b", "missing"]))?.exec(&backend);
+        assert_eq!(members(ret), ["1", "2", "3", "4"]);
+        let ret = SDiff::try_from(array(&["sdiff", "a", "b"]))?.exec(&backend);
+        assert_eq!(members(ret), ["1"]);
+
+        let ret = SDiffStore::try_from(array(&["sdiffstore", "d", "b", "a"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = SMembers::try_from(array(&["smembers", "d"]))?.exec(&backend);
+        assert_eq!(members(ret), ["4"]);
+        let ret = SInterStore::try_from(array(&["sinterstore", "d", "a", "missing"]))?;
+        assert_eq!(ret.exec(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.exists(&["d".to_string()]), 0);
+
+        let ret = SInterCard::try_from(array(&["sintercard", "2", "a", "b", "limit", "1"]))?;
+        assert_eq!(ret.exec(&backend), RespFrame::Integer(1));
+
+        backend.set("string".to_string(), BulkString::from("v"));
+        let ret = SUnionStore::try_from(array(&["sunionstore", "d", "a", "string"]))?;
+        assert_eq!(ret.exec(&backend), BackendErr::WrongType.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_cmd_resp3() -> Result<()> {
+        let backend = Backend::new();
+        backend.set_protocol(3);
+        backend.sadd("s".to_string(), vec!["a".to_string(), "b".to_string()])?;
+
+        let ret = SMembers::try_from(array(&["smembers", "s"]))?.exec(&backend);
+        assert!(matches!(ret, RespFrame::Set(_)));
+        assert_eq!(members(ret), ["a", "b"]);
+        let ret = SMembers::try_from(array(&["smembers", "missing"]))?.exec(&backend);
+        assert_eq!(ret, Set::new(vec![]).into());
+
+        let ret = SPop::try_from(array(&["spop", "s", "5"]))?.exec(&backend);
+        assert!(matches!(ret, RespFrame::Set(_)));
+        assert_eq!(members(ret), ["a", "b"]);
+
+        backend.sadd("s".to_string(), vec!["a".to_string()])?;
+        let ret = SInter::try_from(array(&["sinter", "s", "s"]))?.exec(&backend);
+        assert_eq!(ret, Set::new(vec![BulkString::from("a").into()]).into());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,876 @@
+use crate::cmd::list::parse_key;
+use crate::cmd::set::{parse_key_members_args, parse_member};
+use std::ops::Bound;
+
+use crate::cmd::{
+    extract_args, parse_float, parse_int, parse_opt, validate_cmd, validate_cmd_min, CmdErr,
+    CmdExecutor, ZAdd, ZCard, ZCount, ZIncrBy, ZLexCount, ZMScore, ZRange, ZRangeStore, ZRank,
+    ZRem, ZRemRangeByLex, ZRemRangeByRank, ZRemRangeByScore, ZRevRank, ZScore,
+};
+use crate::{
+    parse_f64, Array, Backend, BulkString, LexBound, Null, NullArray, RespFrame, ZAddOpt, ZRangeBy,
+    ZRangeOpt,
+};
+
+// cmd zadd
+impl CmdExecutor for ZAdd {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        if self.incr {
+            let Some((increment, member)) = self.pairs.into_iter().next() else {
+                return RespFrame::Null(Null);
+            };
+            return match backend.zincr_by(self.key, member, increment, self.opt) {
+                Ok(Some(score)) => score_reply(backend, score),
+                Ok(None) => RespFrame::Null(Null),
+                Err(e) => e.into(),
+            };
+        }
+
+        match backend.zadd(self.key, self.pairs, self.opt) {
+            Ok(count) => RespFrame::Integer(count as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for ZAdd {
+    type Error = CmdErr;
+
+    // ZADD key [NX | XX] [GT | LT] [CH] [INCR] score member [score member ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["zadd"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter().peekable();
+        let key = parse_key(args.next())?;
+
+        let mut opt = ZAddOpt::default();
+        let mut incr = false;
+        // options come first, the first argument that is none of them is a score
+        while let Some(arg) = args.peek().cloned() {
+            match parse_opt(arg)?.as_str() {
+                "NX" => opt.nx = true,
+                "XX" => opt.xx = true,
+                "GT" => opt.gt = true,
+                "LT" => opt.lt = true,
+                "CH" => opt.ch = true,
+                "INCR" => incr = true,
+                _ => break,
+            }
+            args.next();
+        }
+
+        let args = args.collect::<Vec<_>>();
+        if args.is_empty() || args.len() % 2 != 0 {
+            return Err(CmdErr::SyntaxErr);
+        }
+        if opt.nx && opt.xx {
+            return Err(CmdErr::Other(
+                "XX and NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if (opt.nx && (opt.gt || opt.lt)) || (opt.gt && opt.lt) {
+            return Err(CmdErr::Other(
+                "GT, LT, and/or NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if incr && args.len() > 2 {
+            return Err(CmdErr::Other(
+                "INCR option supports a single increment-element pair".to_string(),
+            ));
+        }
+
+        let mut pairs = Vec::with_capacity(args.len() / 2);
+        let mut args = args.into_iter();
+        while let (Some(score), Some(member)) = (args.next(), args.next()) {
+            pairs.push((parse_float(score)?, parse_member(Some(member))?));
+        }
+
+        Ok(ZAdd {
+            key,
+            pairs,
+            opt,
+            incr,
+        })
+    }
+}
+
+// cmd zrem
+impl CmdExecutor for ZRem {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.zrem(&self.key, &self.members) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for ZRem {
+    type Error = CmdErr;
+
+    // ZREM key member [member ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members_args(value, "zrem")?;
+        Ok(ZRem { key, members })
+    }
+}
+
+// cmd zscore
+impl CmdExecutor for ZScore {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.zscore(&self.key, &self.member) {
+            Ok(Some(score)) => score_reply(backend, score),
+            Ok(None) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for ZScore {
+    type Error = CmdErr;
+
+    // ZSCORE key member
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["zscore"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let member = parse_member(args.next())?;
+        Ok(ZScore { key, member })
+    }
+}
+
+// cmd zmscore
+impl CmdExecutor for ZMScore {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.zmscore(&self.key, &self.members) {
+            Ok(scores) => Array::new(
+                scores
+                    .into_iter()
+                    .map(|score| match score {
+                        Some(score) => score_reply(backend, score),
+                        None => RespFrame::Null(Null),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for ZMScore {
+    type Error = CmdErr;
+
+    // ZMSCORE key member [member ...]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, members) = parse_key_members_args(value, "zmscore")?;
+        Ok(ZMScore { key, members })
+    }
+}
+
+// cmd zincrby
+impl CmdExecutor for ZIncrBy {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let opt = ZAddOpt::default();
+        match backend.zincr_by(self.key, self.member, self.increment, opt) {
+            Ok(Some(score)) => score_reply(backend, score),
+            Ok(None) => RespFrame::Null(Null),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for ZIncrBy {
+    type Error = CmdErr;
+
+    // ZINCRBY key increment member
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["zincrby"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let increment = parse_float(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+        let member = parse_member(args.next())?;
+        Ok(ZIncrBy {
+            key,
+            increment,
+            member,
+        })
+    }
+}
+
+// cmd zcard
+impl CmdExecutor for ZCard {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.zcard(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for ZCard {
+    type Error = CmdErr;
+
+    // ZCARD key
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd(&value, &["zcard"], 1)?;
+
+        let key = parse_key(extract_args(value, 1)?.into_iter().next())?;
+        Ok(ZCard { key })
+    }
+}
+
+// cmd zrank
+impl CmdExecutor for ZRank {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        rank_reply(backend, &self.key, &self.member, false, self.with_score)
+    }
+}
+
+impl TryFrom<Array> for ZRank {
+    type Error = CmdErr;
+
+    // ZRANK key member [WITHSCORE]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank_args(value, "zrank")?;
+        Ok(ZRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+// cmd zrevrank
+impl CmdExecutor for ZRevRank {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        rank_reply(backend, &self.key, &self.member, true, self.with_score)
+    }
+}
+
+impl TryFrom<Array> for ZRevRank {
+    type Error = CmdErr;
+
+    // ZREVRANK key member [WITHSCORE]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank_args(value, "zrevrank")?;
+        Ok(ZRevRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+// cmd zrange
+impl CmdExecutor for ZRange {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        let members = match backend.zrange(&self.key, &self.range, self.opt) {
+            Ok(members) => members,
+            Err(e) => return e.into(),
+        };
+
+        let member = |member: String| RespFrame::from(BulkString::from(member));
+        let items: Vec<RespFrame> = match (self.with_scores, backend.protocol() >= 3) {
+            (false, _) => members.into_iter().map(|(m, _)| member(m)).collect(),
+            // RESP2 flattens member and score pairs, RESP3 keeps every pair in an array
+            (true, false) => members
+                .into_iter()
+                .flat_map(|(m, score)| [member(m), score_reply(backend, score)])
+                .collect(),
+            (true, true) => members
+                .into_iter()
+                .map(|(m, score)| Array::new([member(m), score_reply(backend, score)]).into())
+                .collect(),
+        };
+        Array::new(items).into()
+    }
+}
+
+impl TryFrom<Array> for ZRange {
+    type Error = CmdErr;
+
+    // ZRANGE key start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["zrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let (range, opt, with_scores) = parse_range_query(args, true)?;
+        Ok(ZRange {
+            key,
+            range,
+            opt,
+            with_scores,
+        })
+    }
+}
+
+// cmd zrangestore
+impl CmdExecutor for ZRangeStore {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        match backend.zrange_store(self.dest, &self.src, &self.range, self.opt) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<Array> for ZRangeStore {
+    type Error = CmdErr;
+
+    // ZRANGESTORE dst src min max [BYSCORE | BYLEX] [REV] [LIMIT offset count]
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        validate_cmd_min(&value, &["zrangestore"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let dest = parse_key(args.next())?;
+        let src = parse_key(args.next())?;
+        let (range, opt, _) = parse_range_query(args, false)?;
+        Ok(ZRangeStore {
+            dest,
+            src,
+            range,
+            opt,
+        })
+    }
+}
+
+// cmd zcount
+impl CmdExecutor for ZCount {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        count_reply(backend, &self.key, &self.range)
+    }
+}
+
+impl TryFrom<Array> for ZCount {
+    type Error = CmdErr;
+
+    // ZCOUNT key min max
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, min, max) = parse_key_range_args(value, "zcount")?;
+        let range = ZRangeBy::Score(parse_score_bound(min)?, parse_score_bound(max)?);
+        Ok(ZCount { key, range })
+    }
+}
+
+// cmd zlexcount
+impl CmdExecutor for ZLexCount {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        count_reply(backend, &self.key, &self.range)
+    }
+}
+
+impl TryFrom<Array> for ZLexCount {
+    type Error = CmdErr;
+
+    // ZLEXCOUNT key min max
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, min, max) = parse_key_range_args(value, "zlexcount")?;
+        let range = ZRangeBy::Lex(parse_lex_bound(min)?, parse_lex_bound(max)?);
+        Ok(ZLexCount { key, range })
+    }
+}
+
+// cmd zremrangebyrank
+impl CmdExecutor for ZRemRangeByRank {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        remrange_reply(backend, &self.key, &self.range)
+    }
+}
+
+impl TryFrom<Array> for ZRemRangeByRank {
+    type Error = CmdErr;
+
+    // ZREMRANGEBYRANK key start stop
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, start, stop) = parse_key_range_args(value, "zremrangebyrank")?;
+        let range = ZRangeBy::Rank(parse_int(start)?, parse_int(stop)?);
+        Ok(ZRemRangeByRank { key, range })
+    }
+}
+
+// cmd zremrangebyscore
+impl CmdExecutor for ZRemRangeByScore {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        remrange_reply(backend, &self.key, &self.range)
+    }
+}
+
+impl TryFrom<Array> for ZRemRangeByScore {
+    type Error = CmdErr;
+
+    // ZREMRANGEBYSCORE key min max
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, min, max) = parse_key_range_args(value, "zremrangebyscore")?;
+        let range = ZRangeBy::Score(parse_score_bound(min)?, parse_score_bound(max)?);
+        Ok(ZRemRangeByScore { key, range })
+    }
+}
+
+// cmd zremrangebylex
+impl CmdExecutor for ZRemRangeByLex {
+    fn exec(self, backend: &Backend) -> RespFrame {
+        remrange_reply(backend, &self.key, &self.range)
+    }
+}
+
+impl TryFrom<Array> for ZRemRangeByLex {
+    type Error = CmdErr;
+
+    // ZREMRANGEBYLEX key min max
+    fn try_from(value: Array) -> Result<Self, Self::Error> {
+        let (key, min, max) = parse_key_range_args(value, "zremrangebylex")?;
+        let range = ZRangeBy::Lex(parse_lex_bound(min)?, parse_lex_bound(max)?);
+        Ok(ZRemRangeByLex { key, range })
+    }
+}
+
+fn count_reply(backend: &Backend, key: &str, range: &ZRangeBy) -> RespFrame {
+    match backend.zcount(key, range) {
+        Ok(count) => RespFrame::Integer(count as i64),
+        Err(e) => e.into(),
+    }
+}
+
+fn remrange_reply(backend: &Backend, key: &str, range: &ZRangeBy) -> RespFrame {
+    match backend.zremrange(key, range) {
+        Ok(removed) => RespFrame::Integer(removed as i64),
+        Err(e) => e.into(),
+    }
+}
+
+// Scores are doubles for RESP3 connections and bulk strings for RESP2 ones.
+fn score_reply(backend: &Backend, score: f64) -> RespFrame {
+    if backend.protocol() >= 3 {
+        return RespFrame::Double(score);
+    }
+    BulkString::from(format_score(score)).into()
+}
+
+// Score as redis formats it with %.17g, but with the shortest digits that read back the same:
+// an exponent past 17 integral digits or below 1e-4, like 1e+300 and 1e-05.
+fn format_score(score: f64) -> String {
+    if !score.is_finite() {
+        return score.to_string();
+    }
+
+    let sci = format!("{:e}", score);
+    let (mantissa, exp) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    if (-4..17).contains(&exp) {
+        return score.to_string();
+    }
+
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exp.abs())
+}
+
+// The rank alone or, with its score, both in an array. A missing member is a null of that shape.
+fn rank_reply(
+    backend: &Backend,
+    key: &str,
+    member: &str,
+    rev: bool,
+    with_score: bool,
+) -> RespFrame {
+    match (backend.zrank(key, member, rev), with_score) {
+        (Ok(Some((rank, _))), false) => RespFrame::Integer(rank as i64),
+        (Ok(Some((rank, score))), true) => {
+            Array::new([RespFrame::Integer(rank as i64), score_reply(backend, score)]).into()
+        }
+        (Ok(None), false) => RespFrame::Null(Null),
+        (Ok(None), true) => RespFrame::NullArray(NullArray),
+        (Err(e), _) => e.into(),
+    }
+}
+
+// start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]
+// With REV, score and lex ranges are given from their high end.
+fn parse_range_query(
+    mut args: impl Iterator<Item = RespFrame>,
+    allow_scores: bool,
+) -> Result<(ZRangeBy, ZRangeOpt, bool), CmdErr> {
+    let start = args.next().ok_or(CmdErr::SyntaxErr)?;
+    let stop = args.next().ok_or(CmdErr::SyntaxErr)?;
+
+    let (mut by_score, mut by_lex, mut with_scores) = (false, false, false);
+    let mut limit = None;
+    let mut opt = ZRangeOpt::default();
+    while let Some(arg) = args.next() {
+        match parse_opt(arg)?.as_str() {
+            "BYSCORE" if !by_lex => by_score = true,
+            "BYLEX" if !by_score => by_lex = true,
+            "REV" => opt.rev = true,
+            "WITHSCORES" if allow_scores => with_scores = true,
+            "LIMIT" => {
+                let offset = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+                let count = parse_int(args.next().ok_or(CmdErr::SyntaxErr)?)?;
+                limit = Some((offset, count));
+            }
+            _ => return Err(CmdErr::SyntaxErr),
+        }
+    }
+
+    if limit.is_some() && !by_score && !by_lex {
+        return Err(CmdErr::Other(
+            "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+                .to_string(),
+        ));
+    }
+    if with_scores && by_lex {
+        return Err(CmdErr::Other(
+            "syntax error, WITHSCORES not supported in combination with BYLEX".to_string(),
+        ));
+    }
+    if let Some((offset, count)) = limit {
+        // a negative offset matches nothing and a negative count takes everything
+        opt.offset = usize::try_from(offset).unwrap_or_default();
+        opt.count = match offset < 0 {
+            true => Some(0),
+            false => usize::try_from(count).ok(),
+        };
+    }
+
+    let (min, max) = match opt.rev && (by_score || by_lex) {
+        true => (stop, start),
+        false => (start, stop),
+    };
+    let range = match (by_score, by_lex) {
+        (true, _) => ZRangeBy::Score(parse_score_bound(min)?, parse_score_bound(max)?),
+        (_, true) => ZRangeBy::Lex(parse_lex_bound(min)?, parse_lex_bound(max)?),
+        _ => ZRangeBy::Rank(parse_int(min)?, parse_int(max)?),
+    };
+    Ok((range, opt, with_scores))
+}
+
+// A score, exclusive when prefixed with `(`. Infinities are written -inf and +inf.
+fn parse_score_bound(frame: RespFrame) -> Result<Bound<f64>, CmdErr> {
+    let not_float = || CmdErr::Other("min or max is not a float".to_string());
+
+    let RespFrame::BulkString(bound) = frame else {
+        return parse_float(frame)
+            .map(Bound::Included)
+            .map_err(|_| not_float());
+    };
+    match bound.strip_prefix(b"(") {
+        Some(score) => parse_f64(score).map(Bound::Excluded),
+        None => parse_f64(&bound).map(Bound::Included),
+    }
+    .ok_or_else(not_float)
+}
+
+// A member prefixed with `[` or `(` for inclusive and exclusive, or `-` and `+` for the ends.
+fn parse_lex_bound(frame: RespFrame) -> Result<LexBound, CmdErr> {
+    let invalid = || CmdErr::Other("min or max not valid string range item".to_string());
+
+    let RespFrame::BulkString(bound) = frame else {
+        return Err(invalid());
+    };
+    match bound.split_first() {
+        Some((b'-', [])) => Ok(LexBound::NegInf),
+        Some((b'+', [])) => Ok(LexBound::PosInf),
+        Some((b'[', member)) => Ok(LexBound::Included(String::from_utf8(member.to_vec())?)),
+        Some((b'(', member)) => Ok(LexBound::Excluded(String::from_utf8(member.to_vec())?)),
+        _ => Err(invalid()),
+    }
+}
+
+// key min max
+fn parse_key_range_args(
+    value: Array,
+    name: &'static str,
+) -> Result<(String, RespFrame, RespFrame), CmdErr> {
+    validate_cmd(&value, &[name], 3)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = parse_key(args.next())?;
+    let min = args.next().ok_or(CmdErr::SyntaxErr)?;
+    let max = args.next().ok_or(CmdErr::SyntaxErr)?;
+    Ok((key, min, max))
+}
+
+// key member [WITHSCORE]
+fn parse_rank_args(value: Array, name: &'static str) -> Result<(String, String, bool), CmdErr> {
+    validate_cmd_min(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = parse_key(args.next())?;
+    let member = parse_member(args.next())?;
+    let with_score = match args.next().map(parse_opt).transpose()?.as_deref() {
+        None => false,
+        Some("WITHSCORE") => true,
+        _ => return Err(CmdErr::SyntaxErr),
+    };
+    if args.next().is_some() {
+        return Err(CmdErr::SyntaxErr);
+    }
+
+    Ok((key, member, with_score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BackendErr, RespEncode};
+    use anyhow::Result;
+
+    fn array(args: &[&str]) -> Array {
+        Array::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    #[test]
+    fn test_zadd_from_array() -> Result<()> {
+        let cmd = ZAdd::try_from(array(&["zadd", "z", "xx", "ch", "1", "a", "-inf", "b"]))?;
+        assert_eq!(cmd.key, "z");
+        assert_eq!(
+            cmd.pairs,
+            [(1.0, "a".to_string()), (f64::NEG_INFINITY, "b".to_string())]
+        );
+        assert!(cmd.opt.xx && cmd.opt.ch && !cmd.incr);
+
+        let cmd = ZAdd::try_from(array(&["zadd", "z", "gt", "incr", "2.5", "a"]))?;
+        assert!(cmd.opt.gt && cmd.incr);
+
+        // a member may look like an option once the scores started
+        let cmd = ZAdd::try_from(array(&["zadd", "z", "1", "nx"]))?;
+        assert_eq!(cmd.pairs, [(1.0, "nx".to_string())]);
+
+        let err = |args: &[&str]| ZAdd::try_from(array(args)).unwrap_err().to_string();
+        assert_eq!(
+            err(&["zadd", "z", "nx", "xx", "1", "a"]),
+            "ERR XX and NX options at the same time are not compatible"
+        );
+        assert_eq!(
+            err(&["zadd", "z", "gt", "lt", "1", "a"]),
+            "ERR GT, LT, and/or NX options at the same time are not compatible"
+        );
+        assert_eq!(
+            err(&["zadd", "z", "incr", "1", "a", "2", "b"]),
+            "ERR INCR option supports a single increment-element pair"
+        );
+        assert_eq!(err(&["zadd", "z", "1", "a", "2"]), "ERR syntax error");
+        assert_eq!(
+            err(&["zadd", "z", "nan", "a"]),
+            "ERR value is not a valid float"
+        );
+
+        let cmd = ZRank::try_from(array(&["zrank", "z", "a", "withscore"]))?;
+        assert!(cmd.with_score);
+        assert!(ZRevRank::try_from(array(&["zrevrank", "z", "a", "withscores"])).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sorted_set_cmd() -> Result<()> {
+        let backend = Backend::new();
+
+        let ret = ZAdd::try_from(array(&["zadd", "z", "1", "a", "2", "b", "3", "c"]))?;
+        assert_eq!(ret.exec(&backend), RespFrame::Integer(3));
+        let ret = ZAdd::try_from(array(&["zadd", "z", "ch", "5", "a", "2", "b"]))?;
+        assert_eq!(ret.exec(&backend), RespFrame::Integer(1));
+        let ret = ZAdd::try_from(array(&["zadd", "z", "nx", "incr", "1", "a"]))?;
+        assert_eq!(ret.exec(&backend), RespFrame::Null(Null));
+        let ret = ZAdd::try_from(array(&["zadd", "z", "incr", "0.5", "a"]))?;
+        assert_eq!(ret.exec(&backend), BulkString::from("5.5").into());
+
+        let ret = ZIncrBy::try_from(array(&["zincrby", "z", "-10", "a"]))?.exec(&backend);
+        assert_eq!(ret, BulkString::from("-4.5").into());
+        let ret = ZScore::try_from(array(&["zscore", "z", "c"]))?.exec(&backend);
+        assert_eq!(ret, BulkString::from("3").into());
+        let ret = ZMScore::try_from(array(&["zmscore", "z", "b", "x"]))?.exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new([BulkString::from("2").into(), RespFrame::Null(Null)]).into()
+        );
+
+        let ret = ZRank::try_from(array(&["zrank", "z", "a"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(0));
+        let ret = ZRevRank::try_from(array(&["zrevrank", "z", "a", "withscore"]))?;
+        assert_eq!(
+            ret.exec(&backend),
+            Array::new([RespFrame::Integer(2), BulkString::from("-4.5").into()]).into()
+        );
+        let ret = ZRank::try_from(array(&["zrank", "z", "x", "withscore"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::NullArray(NullArray));
+        let ret = ZRank::try_from(array(&["zrank", "z", "x"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Null(Null));
+
+        let ret = ZRem::try_from(array(&["zrem", "z", "a", "x"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let ret = ZCard::try_from(array(&["zcard", "z"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+
+        let ret = ZIncrBy::try_from(array(&["zincrby", "z", "inf", "b"]))?.exec(&backend);
+        assert_eq!(ret, BulkString::from("inf").into());
+        let ret = ZIncrBy::try_from(array(&["zincrby", "z", "-inf", "b"]))?.exec(&backend);
+        assert_eq!(ret, BackendErr::ScoreNaN.into());
+
+        backend.set("string".to_string(), BulkString::from("v"));
+        let ret = ZAdd::try_from(array(&["zadd", "string", "1", "a"]))?.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        // large and tiny scores switch to an exponent like %.17g does
+        let args = [
+            "zadd", "big", "1e300", "a", "1e-300", "b", "1e16", "c", "0.0001", "d",
+        ];
+        ZAdd::try_from(array(&args))?.exec(&backend);
+        let scores = ZMScore::try_from(array(&["zmscore", "big", "a", "b", "c", "d"]))?;
+        assert_eq!(
+            scores.exec(&backend),
+            Array::new(
+                ["1e+300", "1e-300", "10000000000000000", "0.0001"]
+                    .map(|score| BulkString::from(score).into())
+            )
+            .into()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_from_array() -> Result<()> {
+        let cmd = ZRange::try_from(array(&["zrange", "z", "0", "-1", "withscores"]))?;
+        assert_eq!(cmd.range, ZRangeBy::Rank(0, -1));
+        assert!(cmd.with_scores);
+
+        let args = [
+            "zrange", "z", "(5", "-inf", "byscore", "rev", "limit", "1", "-1",
+        ];
+        let cmd = ZRange::try_from(array(&args))?;
+        assert_eq!(
+            cmd.range,
+            ZRangeBy::Score(Bound::Included(f64::NEG_INFINITY), Bound::Excluded(5.0))
+        );
+        let opt = ZRangeOpt {
+            rev: true,
+            offset: 1,
+            count: None,
+        };
+        assert_eq!(cmd.opt, opt);
+
+        let cmd = ZRangeStore::try_from(array(&["zrangestore", "d", "z", "[a", "+", "bylex"]))?;
+        assert_eq!(
+            cmd.range,
+            ZRangeBy::Lex(LexBound::Included("a".to_string()), LexBound::PosInf)
+        );
+        let cmd = ZCount::try_from(array(&["zcount", "z", "1", "+inf"]))?;
+        assert_eq!(
+            cmd.range,
+            ZRangeBy::Score(Bound::Included(1.0), Bound::Included(f64::INFINITY))
+        );
+
+        let err = |args: &[&str]| ZRange::try_from(array(args)).unwrap_err().to_string();
+        assert_eq!(
+            err(&["zrange", "z", "0", "1", "limit", "0", "1"]),
+            "ERR syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+        );
+        assert_eq!(
+            err(&["zrange", "z", "-", "+", "bylex", "withscores"]),
+            "ERR syntax error, WITHSCORES not supported in combination with BYLEX"
+        );
+        assert_eq!(
+            err(&["zrange", "z", "a", "1", "byscore"]),
+            "ERR min or max is not a float"
+        );
+        assert_eq!(
+            err(&["zrange", "z", "a", "+", "bylex"]),
+            "ERR min or max not valid string range item"
+        );
+        assert_eq!(
+            err(&["zrange", "z", "0", "1", "byscore", "bylex"]),
+            "ERR syntax error"
+        );
+        let ret = ZRangeStore::try_from(array(&["zrangestore", "d", "z", "0", "1", "withscores"]));
+        assert_eq!(ret.unwrap_err().to_string(), "ERR syntax error");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_cmd() -> Result<()> {
+        let backend = Backend::new();
+        let args = ["zadd", "z", "1", "a", "2", "b", "3", "c", "4", "d"];
+        ZAdd::try_from(array(&args))?.exec(&backend);
+        let frames = |values: &[&str]| -> RespFrame {
+            Array::new(
+                values
+                    .iter()
+                    .map(|v| BulkString::from(*v).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+
+        let ret = ZRange::try_from(array(&["zrange", "z", "0", "1", "withscores"]))?;
+        assert_eq!(ret.exec(&backend), frames(&["a", "1", "b", "2"]));
+        let ret = ZRange::try_from(array(&["zrange", "z", "0", "0", "rev"]))?;
+        assert_eq!(ret.exec(&backend), frames(&["d"]));
+        let args = [
+            "zrange", "z", "+inf", "(1", "byscore", "rev", "limit", "1", "2",
+        ];
+        let ret = ZRange::try_from(array(&args))?;
+        assert_eq!(ret.exec(&backend), frames(&["c", "b"]));
+        let args = ["zrange", "z", "0", "10", "byscore", "limit", "-1", "2"];
+        let ret = ZRange::try_from(array(&args))?;
+        assert_eq!(ret.exec(&backend), frames(&[]));
+
+        let ret = ZCount::try_from(array(&["zcount", "z", "(1", "3"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        let ret = ZLexCount::try_from(array(&["zlexcount", "z", "-", "+"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(4));
+
+        let args = ["zrangestore", "dest", "z", "-", "(c", "bylex"];
+        let ret = ZRangeStore::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        let ret = ZRange::try_from(array(&["zrange", "dest", "0", "-1"]))?;
+        assert_eq!(ret.exec(&backend), frames(&["a", "b"]));
+
+        let args = ["zremrangebyscore", "z", "-inf", "(2"];
+        let ret = ZRemRangeByScore::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let args = ["zremrangebyrank", "z", "-1", "-1"];
+        let ret = ZRemRangeByRank::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(1));
+        let args = ["zremrangebylex", "z", "[b", "[c"];
+        let ret = ZRemRangeByLex::try_from(array(&args))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Integer(2));
+        assert_eq!(backend.exists(&["z".to_string()]), 0);
+
+        backend.set("string".to_string(), BulkString::from("v"));
+        let ret = ZCount::try_from(array(&["zcount", "string", "0", "1"]))?.exec(&backend);
+        assert_eq!(ret, BackendErr::WrongType.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sorted_set_cmd_resp3() -> Result<()> {
+        let backend = Backend::new();
+        backend.set_protocol(3);
+
+        ZAdd::try_from(array(&["zadd", "z", "1.5", "a"]))?.exec(&backend);
+        let ret = ZScore::try_from(array(&["zscore", "z", "a"]))?.exec(&backend);
+        assert_eq!(ret, RespFrame::Double(1.5));
+        assert_eq!(ret.encode(), b",+1.5\r\n");
+
+        let ret = ZRank::try_from(array(&["zrank", "z", "a", "withscore"]))?.exec(&backend);
+        assert_eq!(
+            ret,
+            Array::new([RespFrame::Integer(0), RespFrame::Double(1.5)]).into()
+        );
+
+        // every member and score pair is an array of its own
+        let ret = ZRange::try_from(array(&["zrange", "z", "0", "-1", "withscores"]))?;
+        let pair = Array::new([BulkString::from("a").into(), RespFrame::Double(1.5)]);
+        assert_eq!(ret.exec(&backend), Array::new([pair.into()]).into());
+
+        Ok(())
+    }
+}
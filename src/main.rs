@@ -1,5 +1,5 @@
 use anyhow::Result;
-use easy_redis::{network, Backend};
+use easy_redis::{network, Backend, DEFAULT_DATABASES};
 use tokio::net::TcpListener;
 use tracing::{error, info};
 
@@ -10,7 +10,8 @@ async fn main() -> Result<()> {
     let addr = "0.0.0.0:6379";
     info!("Listening on {}", addr);
     let listener = TcpListener::bind(addr).await?;
-    let backend = Backend::new();
+    let backend = Backend::with_databases(databases()?);
+    tokio::spawn(backend.clone().active_expire());
 
     loop {
         let (stream, remote) = listener.accept().await?;
@@ -29,3 +30,19 @@ async fn main() -> Result<()> {
         });
     }
 }
+
+// Number of databases, set with `--databases <n>`.
+fn databases() -> Result<usize> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--databases" {
+            let n = args.next().unwrap_or_default().parse::<usize>();
+            return match n {
+                Ok(n) if n > 0 => Ok(n),
+                _ => anyhow::bail!("--databases expects a positive integer"),
+            };
+        }
+    }
+
+    Ok(DEFAULT_DATABASES)
+}
@@ -1,4 +1,4 @@
-use crate::cmd::{Cmd, CmdExecutor};
+use crate::cmd::Cmd;
 use crate::{Backend, RespDecode, RespEncode, RespErr, RespFrame};
 use anyhow::Result;
 use bytes::BytesMut;
@@ -36,9 +36,9 @@ impl Decoder for RespFrameCodec {
 }
 
 #[derive(Debug)]
-struct RedisReq {
+struct RedisReq<'a> {
     frame: RespFrame,
-    backend: Backend,
+    backend: &'a Backend,
 }
 
 #[derive(Debug)]
@@ -46,6 +46,7 @@ struct RedisRsp {
     frame: RespFrame,
 }
 
+// The backend handle is owned by the connection, it keeps the database the client selected.
 pub async fn handle_stream(stream: TcpStream, backend: Backend) -> Result<()> {
     let mut framed = Framed::new(stream, RespFrameCodec);
 
@@ -56,7 +57,7 @@ pub async fn handle_stream(stream: TcpStream, backend: Backend) -> Result<()> {
 
                 let req = RedisReq {
                     frame,
-                    backend: backend.clone(),
+                    backend: &backend,
                 };
 
                 let rsp = handle_req(req).await?;
@@ -70,12 +71,15 @@ pub async fn handle_stream(stream: TcpStream, backend: Backend) -> Result<()> {
     }
 }
 
-async fn handle_req(req: RedisReq) -> Result<RedisRsp> {
+async fn handle_req(req: RedisReq<'_>) -> Result<RedisRsp> {
     let (frame, backend) = (req.frame, req.backend);
-    let cmd = Cmd::try_from(frame)?;
-
-    info!("Execute command: {:?}", cmd);
-    let frame = cmd.exec(&backend);
+    let frame = match Cmd::try_from(frame) {
+        Ok(cmd) => {
+            info!("Execute command: {:?}", cmd);
+            cmd.exec_blocking(backend).await
+        }
+        Err(e) => e.into(),
+    };
 
     Ok(RedisRsp { frame })
 }
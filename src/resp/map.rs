@@ -1,5 +1,5 @@
 use crate::resp::{calc_total_len, parse_len, BUF_CAP, CRLF_LEN};
-use crate::{RespDecode, RespEncode, RespErr, RespFrame, SimpleString};
+use crate::{BulkString, RespDecode, RespEncode, RespErr, RespFrame};
 use bytes::{Buf, BytesMut};
 use std::collections::BTreeMap;
 use std::ops::{Deref, DerefMut};
@@ -10,14 +10,14 @@ pub struct Map(BTreeMap<String, RespFrame>);
 // The RESP map encodes a collection of key-value tuples, i.e., a dictionary or a hash.
 // %<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>
 //
-// !!here only support string key encode with to simple string!!
+// Keys are encoded as bulk strings, they may come from user data and hold any byte.
 impl RespEncode for Map {
     fn encode(self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(BUF_CAP);
         buf.extend_from_slice(&format!("%{}\r\n", self.len()).into_bytes());
 
         for (key, value) in self.0 {
-            buf.extend_from_slice(&SimpleString::new(key).encode());
+            buf.extend_from_slice(&BulkString::from(key).encode());
             buf.extend_from_slice(&value.encode());
         }
         buf
@@ -39,9 +39,11 @@ impl RespDecode for Map {
 
         let mut frames = Map::new();
         for _ in 0..len {
-            let key = SimpleString::decode(buf)?;
+            let key = BulkString::decode(buf)?;
+            let key = String::from_utf8(key.0)
+                .map_err(|e| RespErr::InvalidFrameType(format!("map key: {}", e)))?;
             let value = RespFrame::decode(buf)?;
-            frames.insert(key.0, value);
+            frames.insert(key, value);
         }
 
         Ok(frames)
@@ -106,7 +108,7 @@ mod tests {
         let frame: RespFrame = map.into();
         assert_eq!(
             frame.encode(),
-            b"%3\r\n+foo\r\n,+1.23\r\n+hello\r\n$5\r\nworld\r\n+int\r\n:+456\r\n"
+            b"%3\r\n$3\r\nfoo\r\n,+1.23\r\n$5\r\nhello\r\n$5\r\nworld\r\n$3\r\nint\r\n:+456\r\n"
         )
     }
 
@@ -123,17 +125,33 @@ mod tests {
 
         let mut buf = BytesMut::new();
         buf.extend_from_slice(
-            b"%3\r\n+foo\r\n,+1.23\r\n+hello\r\n$5\r\nworld\r\n+int\r\n:+456\r\n",
+            b"%3\r\n$3\r\nfoo\r\n,+1.23\r\n$5\r\nhello\r\n$5\r\nworld\r\n$3\r\nint\r\n:+456\r\n",
         );
 
         let frame = Map::decode(&mut buf)?;
 
         assert_eq!(frame, map);
 
-        buf.extend_from_slice(b"%3\r\n+foo\r\n,+1.23\r\n+hello\r\n$5\r\nworld\r\n+int\r\n");
+        buf.extend_from_slice(
+            b"%3\r\n$3\r\nfoo\r\n,+1.23\r\n$5\r\nhello\r\n$5\r\nworld\r\n$3\r\nint\r\n",
+        );
         let ret = Map::decode(&mut buf);
         assert_eq!(ret.unwrap_err(), RespErr::NotComplete);
 
         Ok(())
     }
+
+    #[test]
+    fn test_map_binary_key() -> Result<()> {
+        let mut map = Map::new();
+        map.insert("a\r\n+b".to_string(), BulkString::from("v").into());
+
+        let encoded = RespFrame::from(map.clone()).encode();
+        assert_eq!(encoded, b"%1\r\n$5\r\na\r\n+b\r\n$1\r\nv\r\n");
+
+        let mut buf = BytesMut::from(&encoded[..]);
+        assert_eq!(RespFrame::decode(&mut buf)?, map.into());
+
+        Ok(())
+    }
 }
@@ -120,9 +120,9 @@ fn calc_total_len(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result<us
             Ok(total)
         }
         "%" => {
-            // map
+            // map, keys are bulk strings
             for _ in 0..len {
-                let key_len = SimpleString::expect_len(data)?;
+                let key_len = RespFrame::expect_len(data)?;
                 if data.len() < key_len {
                     return Err(RespErr::NotComplete);
                 }